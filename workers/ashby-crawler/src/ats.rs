@@ -0,0 +1,498 @@
+use std::collections::HashSet;
+
+use worker::wasm_bindgen::JsValue;
+use worker::*;
+
+use crate::geo;
+
+// ═══════════════════════════════════════════════════════════════════════════
+// AtsSource — shared fetch/upsert shape across ATS providers
+// ═══════════════════════════════════════════════════════════════════════════
+//
+// Lever and Greenhouse started as copy-pasted fetch/upsert pairs: same
+// INSERT/ON CONFLICT skeleton, same board-tracking upsert, same company-name
+// backfill, same batching, diverging only in which extra columns each
+// provider populates. `AtsSource` pulls that skeleton out once — a provider
+// implements `fetch`, `external_id`, and `to_job_row` (plus `raw_locations`
+// for multi-location radius search) and gets upsert, batching, board
+// tracking, sync-run metrics, and location normalization for free via
+// `upsert_jobs_to_d1`.
+
+pub trait AtsSource {
+    /// `Debug` is required so `upsert_jobs_to_d1` can quarantine a posting it
+    /// has to skip (e.g. no `external_id`) into `_invalid_records` instead of
+    /// just dropping it — every provider's posting type already derives it.
+    type Posting: std::fmt::Debug;
+
+    /// `source_kind` column value, e.g. `"lever"`.
+    fn source_kind() -> &'static str;
+    /// Board-tracking table name, e.g. `"lever_boards"`.
+    fn board_table() -> &'static str;
+    /// Board-tracking table's primary-key column name — each provider's
+    /// table names it differently (`lever_boards.site`,
+    /// `greenhouse_boards.token`, `workable_boards.shortcode`) since they
+    /// predate this trait.
+    fn board_key_column() -> &'static str;
+    /// Public board URL for the tracking row, e.g. `https://jobs.lever.co/{site}`.
+    fn board_url(site: &str) -> String;
+
+    /// Fetch all postings for one board/account by its provider-specific
+    /// site/token.
+    async fn fetch(site: &str) -> Result<Vec<Self::Posting>>;
+
+    /// The `jobs.external_id` for a posting, or `None` to skip it (e.g. no
+    /// canonical URL to key on).
+    fn external_id(posting: &Self::Posting) -> Option<String>;
+
+    /// Every raw location string on the posting (primary location first, if
+    /// there is one), for multi-location radius search via `job_locations`.
+    /// Default: none — a provider that doesn't carry per-posting location
+    /// strings beyond what it puts in `JobRow::location` just doesn't get
+    /// geo-radius matches for its jobs.
+    fn raw_locations(_posting: &Self::Posting) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Map one posting onto the shared `jobs` row shape. `company_name` is
+    /// already resolved (board-provided name, falling back to a title-cased
+    /// site slug) so every provider doesn't redo that fallback itself.
+    fn to_job_row(posting: &Self::Posting, site: &str, company_name: &str) -> JobRow;
+}
+
+/// How an `extra` (provider-specific) column behaves on `ON CONFLICT`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExtraUpdateMode {
+    /// `col=excluded.col` — always takes the newest value (the provider's
+    /// current snapshot of a JSON/array field like `categories`, `lists`,
+    /// `departments`).
+    Overwrite,
+    /// `col=COALESCE(excluded.col, col)` — keeps the existing value when the
+    /// new one is NULL/empty, for fields that may be sparse in any one fetch.
+    CoalesceIfNull,
+}
+
+/// One provider-specific column beyond [`JobRow`]'s common core, with the
+/// bind value already converted and its update behavior on conflict.
+pub struct ExtraColumn {
+    name: &'static str,
+    value: JsValue,
+    mode: ExtraUpdateMode,
+    wrap_nullif: bool,
+    byte_len: usize,
+}
+
+impl ExtraColumn {
+    /// A text/JSON column — bound as `NULLIF(?,'')` so an empty string reads
+    /// back as `NULL` like every other text column in this table.
+    pub fn text(name: &'static str, value: &str, mode: ExtraUpdateMode) -> Self {
+        Self { name, value: value.into(), mode, wrap_nullif: true, byte_len: value.len() }
+    }
+
+    /// A numeric column, bound as-is (`JsValue::NULL` for absent).
+    pub fn numeric(name: &'static str, value: JsValue, mode: ExtraUpdateMode) -> Self {
+        Self { name, value, mode, wrap_nullif: false, byte_len: 0 }
+    }
+}
+
+/// The common `jobs` row shape every `AtsSource` maps a posting onto.
+/// `location_city`/`location_region`/`location_country`/`location_remote`/
+/// `location_lat`/`location_lng` are filled in by `upsert_jobs_to_d1` itself
+/// from `raw_locations`/`geo::parse_location`, not by the provider.
+pub struct JobRow {
+    pub source_id: String,
+    pub company_key: String,
+    pub company_name: String,
+    pub title: String,
+    pub url: String,
+    pub description: String,
+    pub location: String,
+    /// ISO 8601 posting/creation timestamp, or empty if the provider doesn't
+    /// carry one (defaults to `datetime('now')` on first insert).
+    pub posted_at: String,
+    pub country: String,
+    pub workplace_type: String,
+    pub salary_min: JsValue,
+    pub salary_max: JsValue,
+    pub salary_currency: String,
+    pub extra: Vec<ExtraColumn>,
+    pub(crate) location_city: String,
+    pub(crate) location_region: String,
+    pub(crate) location_country: String,
+    pub(crate) location_remote: bool,
+    pub(crate) location_lat: JsValue,
+    pub(crate) location_lng: JsValue,
+}
+
+impl Default for JobRow {
+    fn default() -> Self {
+        Self {
+            source_id: String::new(),
+            company_key: String::new(),
+            company_name: String::new(),
+            title: String::new(),
+            url: String::new(),
+            description: String::new(),
+            location: String::new(),
+            posted_at: String::new(),
+            country: String::new(),
+            workplace_type: String::new(),
+            salary_min: JsValue::NULL,
+            salary_max: JsValue::NULL,
+            salary_currency: String::new(),
+            extra: Vec::new(),
+            location_city: String::new(),
+            location_region: String::new(),
+            location_country: String::new(),
+            location_remote: false,
+            location_lat: JsValue::NULL,
+            location_lng: JsValue::NULL,
+        }
+    }
+}
+
+/// Title-case a hyphen/underscore-separated slug into a readable company
+/// name fallback, e.g. `"hello-world"` → `"Hello World"`. Shared by every
+/// provider that doesn't get a board display name from its API response.
+fn title_case_slug(slug: &str) -> String {
+    slug.split(|c: char| c == '-' || c == '_')
+        .map(|w| {
+            let mut chars = w.chars();
+            match chars.next() {
+                None => String::new(),
+                Some(c) => c.to_uppercase().to_string() + chars.as_str(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Which of `external_ids` already have a `jobs` row, so the upsert loop can
+/// tell inserts from updates without relying on D1's `changes()`/affected-row
+/// semantics. Chunked the same way as `get_job_bodies` in lib.rs — D1 has no
+/// array-bind support, so a batch lookup goes through `IN (?1, ?2, ...)`.
+async fn existing_external_ids(db: &D1Database, external_ids: &[String]) -> Result<HashSet<String>> {
+    let mut out = HashSet::with_capacity(external_ids.len());
+    const CHUNK_SIZE: usize = 100;
+    for chunk in external_ids.chunks(CHUNK_SIZE) {
+        let placeholders: Vec<String> = (1..=chunk.len()).map(|i| format!("?{i}")).collect();
+        let sql = format!("SELECT external_id FROM jobs WHERE external_id IN ({})", placeholders.join(", "));
+        let binds: Vec<JsValue> = chunk.iter().map(|id| id.clone().into()).collect();
+        let rows = db.prepare(&sql).bind(&binds)?.all().await?.results::<serde_json::Value>()?;
+        for row in rows {
+            if let Some(id) = row["external_id"].as_str() {
+                out.insert(id.to_string());
+            }
+        }
+    }
+    Ok(out)
+}
+
+enum InsertWrap {
+    Raw,
+    NullIfEmpty,
+    PostedAt,
+}
+
+struct Col {
+    name: &'static str,
+    value: JsValue,
+    insert: InsertWrap,
+    update: Option<ExtraUpdateMode>,
+}
+
+/// Build the `INSERT ... ON CONFLICT(external_id) DO UPDATE SET ...`
+/// statement for one job row. Rebuilt per-row rather than cached as a
+/// `const` (the way single-provider callers used to) because the extra
+/// column set is only known once `JobRow` exists — negligible cost next to
+/// the D1 round trip itself.
+fn prepare_job_stmt(db: &D1Database, source_kind: &str, external_id: &str, row: &JobRow) -> Result<(D1PreparedStatement, usize, usize)> {
+    let mut cols: Vec<Col> = vec![
+        Col { name: "source_id", value: row.source_id.clone().into(), insert: InsertWrap::Raw, update: Some(ExtraUpdateMode::Overwrite) },
+        Col { name: "company_key", value: row.company_key.clone().into(), insert: InsertWrap::Raw, update: Some(ExtraUpdateMode::Overwrite) },
+        Col { name: "company_name", value: row.company_name.clone().into(), insert: InsertWrap::NullIfEmpty, update: Some(ExtraUpdateMode::CoalesceIfNull) },
+        Col { name: "title", value: row.title.clone().into(), insert: InsertWrap::Raw, update: Some(ExtraUpdateMode::Overwrite) },
+        Col { name: "url", value: row.url.clone().into(), insert: InsertWrap::Raw, update: Some(ExtraUpdateMode::Overwrite) },
+        Col { name: "description", value: row.description.clone().into(), insert: InsertWrap::NullIfEmpty, update: Some(ExtraUpdateMode::CoalesceIfNull) },
+        Col { name: "location", value: row.location.clone().into(), insert: InsertWrap::NullIfEmpty, update: Some(ExtraUpdateMode::CoalesceIfNull) },
+        Col { name: "country", value: row.country.clone().into(), insert: InsertWrap::NullIfEmpty, update: Some(ExtraUpdateMode::CoalesceIfNull) },
+        Col { name: "workplace_type", value: row.workplace_type.clone().into(), insert: InsertWrap::NullIfEmpty, update: Some(ExtraUpdateMode::CoalesceIfNull) },
+        Col { name: "posted_at", value: row.posted_at.clone().into(), insert: InsertWrap::PostedAt, update: Some(ExtraUpdateMode::CoalesceIfNull) },
+        Col { name: "ats_created_at", value: row.posted_at.clone().into(), insert: InsertWrap::NullIfEmpty, update: Some(ExtraUpdateMode::Overwrite) },
+        Col { name: "salary_min", value: row.salary_min.clone(), insert: InsertWrap::Raw, update: Some(ExtraUpdateMode::CoalesceIfNull) },
+        Col { name: "salary_max", value: row.salary_max.clone(), insert: InsertWrap::Raw, update: Some(ExtraUpdateMode::CoalesceIfNull) },
+        Col { name: "salary_currency", value: row.salary_currency.clone().into(), insert: InsertWrap::NullIfEmpty, update: Some(ExtraUpdateMode::CoalesceIfNull) },
+        Col { name: "location_city", value: row.location_city.clone().into(), insert: InsertWrap::NullIfEmpty, update: Some(ExtraUpdateMode::CoalesceIfNull) },
+        Col { name: "location_region", value: row.location_region.clone().into(), insert: InsertWrap::NullIfEmpty, update: Some(ExtraUpdateMode::CoalesceIfNull) },
+        Col { name: "location_country", value: row.location_country.clone().into(), insert: InsertWrap::NullIfEmpty, update: Some(ExtraUpdateMode::CoalesceIfNull) },
+        Col { name: "location_remote", value: (row.location_remote as i32).into(), insert: InsertWrap::Raw, update: Some(ExtraUpdateMode::Overwrite) },
+        Col { name: "location_lat", value: row.location_lat.clone(), insert: InsertWrap::Raw, update: Some(ExtraUpdateMode::CoalesceIfNull) },
+        Col { name: "location_lng", value: row.location_lng.clone(), insert: InsertWrap::Raw, update: Some(ExtraUpdateMode::CoalesceIfNull) },
+    ];
+    for extra in &row.extra {
+        cols.push(Col {
+            name: extra.name,
+            value: extra.value.clone(),
+            insert: if extra.wrap_nullif { InsertWrap::NullIfEmpty } else { InsertWrap::Raw },
+            update: Some(extra.mode),
+        });
+    }
+
+    let mut col_names = vec!["external_id".to_string(), "source_kind".to_string()];
+    let mut insert_exprs = vec!["?1".to_string(), format!("'{source_kind}'")];
+    let mut binds: Vec<JsValue> = vec![external_id.into()];
+    let mut update_clauses = Vec::new();
+    let mut byte_estimate = external_id.len() + row.title.len() + row.url.len() + row.description.len() + row.location.len();
+
+    for (i, col) in cols.iter().enumerate() {
+        let placeholder = format!("?{}", i + 2);
+        insert_exprs.push(match col.insert {
+            InsertWrap::Raw => placeholder.clone(),
+            InsertWrap::NullIfEmpty => format!("NULLIF({placeholder},'')"),
+            InsertWrap::PostedAt => format!("COALESCE(NULLIF({placeholder},''), datetime('now'))"),
+        });
+        col_names.push(col.name.to_string());
+        binds.push(col.value.clone());
+        if let Some(mode) = col.update {
+            update_clauses.push(match mode {
+                ExtraUpdateMode::Overwrite => format!("{0}=excluded.{0}", col.name),
+                ExtraUpdateMode::CoalesceIfNull => format!("{0}=COALESCE(excluded.{0}, {0})", col.name),
+            });
+        }
+    }
+    update_clauses.push("status='open'".to_string());
+    update_clauses.push("closed_at=NULL".to_string());
+    update_clauses.push("updated_at=datetime('now')".to_string());
+    for extra in &row.extra {
+        byte_estimate += extra.byte_len;
+    }
+
+    let sql = format!(
+        "INSERT INTO jobs ({}, updated_at) VALUES ({}, datetime('now'))
+         ON CONFLICT(external_id) DO UPDATE SET {}",
+        col_names.join(", "),
+        insert_exprs.join(", "),
+        update_clauses.join(", "),
+    );
+
+    let bind_count = binds.len();
+    Ok((db.prepare(&sql).bind(&binds)?, bind_count, byte_estimate))
+}
+
+/// Janitor pass: after a board's postings are successfully fetched, close
+/// (`status='closed'`, `closed_at=now`) any `jobs` row for this
+/// `source_kind`/`site` whose `external_id` isn't in `fetched_ids` — the
+/// company removed the posting (or the whole board), but the row is kept
+/// rather than hard-deleted so job history survives for `?include_closed=1`
+/// reads. A posting that reappears later flips back to `status='open'` via
+/// the regular `ON CONFLICT` upsert in [`prepare_job_stmt`], not here.
+/// Callers only run this after a non-empty fetch — an empty/404 response is
+/// ambiguous between "board has zero postings" and "board unreachable", so
+/// it's left to each provider's own board-lifecycle tracking instead of
+/// closing every job on one blip.
+pub async fn reconcile_closed_jobs(
+    db: &D1Database,
+    source_kind: &str,
+    site: &str,
+    fetched_ids: &[String],
+) -> Result<crate::BatchOutcome> {
+    let still_open: Vec<String> = db
+        .prepare("SELECT external_id FROM jobs WHERE source_kind=?1 AND source_id=?2 AND status != 'closed'")
+        .bind(&[source_kind.into(), site.into()])?
+        .all().await?
+        .results::<serde_json::Value>()?
+        .iter()
+        .filter_map(|row| row["external_id"].as_str().map(str::to_string))
+        .collect();
+
+    let fetched: HashSet<&String> = fetched_ids.iter().collect();
+    let stale: Vec<String> = still_open.into_iter().filter(|id| !fetched.contains(id)).collect();
+    if stale.is_empty() {
+        return Ok(crate::BatchOutcome::default());
+    }
+
+    console_log!("[janitor:{}] site '{}': closing {} job(s) no longer in the feed", source_kind, site, stale.len());
+    let stmts: Vec<(String, D1PreparedStatement)> = stale.iter()
+        .map(|id| Ok((format!("close:{id}"), db.prepare(
+            "UPDATE jobs SET status='closed', closed_at=datetime('now'), updated_at=datetime('now') WHERE external_id=?1"
+        ).bind(&[id.clone().into()])?)))
+        .collect::<Result<_>>()?;
+
+    const BATCH_SIZE: usize = 100;
+    let mut outcome = crate::BatchOutcome::default();
+    for chunk in stmts.chunks(BATCH_SIZE) {
+        outcome.merge(crate::run_batch_resilient(db, chunk.to_vec()).await);
+    }
+    Ok(outcome)
+}
+
+/// Upsert a board/account's postings into the shared `jobs` table for any
+/// `AtsSource`: resolves the company name (board-provided, falling back to
+/// a title-cased site slug), normalizes each posting's location(s) (gazetteer
+/// first, then a best-effort external geocode), writes `job_locations` rows
+/// for radius search, packs job statements by bind-count/byte budget via
+/// [`crate::pack_batches`], runs them resiliently via
+/// [`crate::run_batch_resilient`], upserts the provider's board-tracking row,
+/// backfills the `companies.name`, and records a `sync_runs` row — the same
+/// five things `upsert_lever_jobs_to_d1`/`upsert_greenhouse_jobs_to_d1` used
+/// to each do by hand.
+pub async fn upsert_jobs_to_d1<S: AtsSource>(
+    db: &D1Database,
+    postings: &[S::Posting],
+    site: &str,
+    board_name: Option<&str>,
+) -> Result<crate::BatchOutcome> {
+    let started_at = js_sys::Date::now();
+    let company_name = board_name
+        .map(str::trim)
+        .filter(|n| !n.is_empty())
+        .map(str::to_string)
+        .unwrap_or_else(|| title_case_slug(site));
+
+    let mut skipped = 0usize;
+    let candidate_ids: Vec<String> = postings.iter()
+        .filter_map(|p| {
+            let id = S::external_id(p);
+            if id.is_none() {
+                skipped += 1;
+            }
+            id
+        })
+        .collect();
+    let existing_ids = existing_external_ids(db, &candidate_ids).await?;
+
+    let mut job_stmts: Vec<((String, D1PreparedStatement), usize, usize)> = Vec::with_capacity(postings.len());
+    let mut location_stmts: Vec<(String, D1PreparedStatement)> = Vec::new();
+    let mut inserted = 0usize;
+    let mut updated = 0usize;
+
+    for posting in postings {
+        let Some(external_id) = S::external_id(posting) else {
+            crate::record_invalid(db, "posting", site, &format!("{posting:?}"), "external_id() returned None").await;
+            continue;
+        };
+        if existing_ids.contains(&external_id) { updated += 1; } else { inserted += 1; }
+
+        let raws = S::raw_locations(posting);
+        let mut normalized_locations: Vec<(String, geo::NormalizedLocation)> = Vec::with_capacity(raws.len());
+        for raw in &raws {
+            let mut normalized = geo::parse_location(raw);
+            if normalized.point.is_none() && !normalized.remote {
+                normalized.point = geo::geocode_external(raw).await;
+            }
+            normalized_locations.push((raw.clone(), normalized));
+        }
+        let primary = normalized_locations.first().map(|(_, n)| n.clone()).unwrap_or_default();
+
+        let mut row = S::to_job_row(posting, site, &company_name);
+        row.location_city = primary.city.clone().unwrap_or_default();
+        row.location_region = primary.region.clone().unwrap_or_default();
+        row.location_country = primary.country.clone().unwrap_or_default();
+        row.location_remote = primary.remote;
+        row.location_lat = primary.point.map(|p| p.lat).map(JsValue::from_f64).unwrap_or(JsValue::NULL);
+        row.location_lng = primary.point.map(|p| p.lng).map(JsValue::from_f64).unwrap_or(JsValue::NULL);
+
+        for (idx, (raw, normalized)) in normalized_locations.iter().enumerate() {
+            location_stmts.push((format!("job_locations_del:{external_id}:{idx}"), db.prepare(
+                "DELETE FROM job_locations WHERE external_id=?1 AND raw=?2"
+            ).bind(&[external_id.clone().into(), raw.clone().into()])?));
+            location_stmts.push((format!("job_locations_ins:{external_id}:{idx}"), db.prepare(
+                "INSERT INTO job_locations (external_id, raw, city, region, country, remote, lat, lng)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)"
+            ).bind(&[
+                external_id.clone().into(),
+                raw.clone().into(),
+                normalized.city.clone().unwrap_or_default().into(),
+                normalized.region.clone().unwrap_or_default().into(),
+                normalized.country.clone().unwrap_or_default().into(),
+                (normalized.remote as i32).into(),
+                normalized.point.map(|p| p.lat).map(JsValue::from_f64).unwrap_or(JsValue::NULL),
+                normalized.point.map(|p| p.lng).map(JsValue::from_f64).unwrap_or(JsValue::NULL),
+            ])?));
+        }
+
+        let (stmt, bind_count, byte_estimate) = prepare_job_stmt(db, S::source_kind(), &external_id, &row)?;
+        job_stmts.push(((external_id, stmt), bind_count, byte_estimate));
+    }
+
+    let mut outcome = crate::BatchOutcome::default();
+    for batch in crate::pack_batches(job_stmts, crate::D1_BATCH_MAX_BINDS, crate::D1_BATCH_MAX_BYTES) {
+        outcome.merge(crate::run_batch_resilient(db, batch).await);
+    }
+    if !outcome.failed.is_empty() {
+        console_log!(
+            "[job-sync:{}] site '{}': {} job row(s) failed to commit: {:?}",
+            S::source_kind(), site, outcome.failed.len(), outcome.failed
+        );
+    }
+
+    const LOCATION_BATCH_SIZE: usize = 100;
+    for chunk in location_stmts.chunks(LOCATION_BATCH_SIZE) {
+        let location_outcome = crate::run_batch_resilient(db, chunk.to_vec()).await;
+        for (label, reason) in &location_outcome.failed {
+            console_log!("[job-sync:{}] job_locations write failed for {}: {}", S::source_kind(), label, reason);
+        }
+    }
+
+    // Janitor pass: close `jobs` rows this board used to have that didn't
+    // come back in this fetch (see `reconcile_closed_jobs`). Skipped on an
+    // empty fetch — that's routed through the board's own retry/lifecycle
+    // tracking below instead.
+    if !postings.is_empty() {
+        match reconcile_closed_jobs(db, S::source_kind(), site, &candidate_ids).await {
+            Ok(o) if !o.failed.is_empty() => console_log!(
+                "[job-sync:{}] site '{}': {} stale-job close(s) failed: {:?}",
+                S::source_kind(), site, o.failed.len(), o.failed
+            ),
+            Ok(_) => {}
+            Err(e) => console_log!("[job-sync:{}] site '{}': janitor reconciliation failed: {:?}", S::source_kind(), site, e),
+        }
+    }
+
+    // Board tracking + company-name backfill — small, best-effort bookkeeping
+    // rather than job data, so failures are logged rather than folded into
+    // the job `BatchOutcome` returned to the caller.
+    let board_table = S::board_table();
+    let board_key_column = S::board_key_column();
+    let tracking_stmts = vec![
+        (format!("{board_table}:{site}"), db.prepare(&format!(
+            "INSERT INTO {board_table} ({board_key_column}, url, first_seen, last_seen, crawl_id, last_synced_at, job_count, is_active)
+             VALUES (?1, ?2, datetime('now'), datetime('now'), 'job-sync', datetime('now'), ?3, 1)
+             ON CONFLICT({board_key_column}) DO UPDATE SET
+               last_synced_at=datetime('now'),
+               job_count=?3,
+               is_active=1,
+               retry_count=0,
+               next_retry_at=NULL,
+               last_error=NULL,
+               sync_state='done',
+               updated_at=datetime('now')"
+        )).bind(&[
+            site.into(),
+            S::board_url(site).into(),
+            (outcome.committed as f64).into(),
+        ])?),
+        (format!("companies:{site}"), if !company_name.is_empty() {
+            db.prepare(
+                "UPDATE companies SET name=?1, updated_at=datetime('now') WHERE key=?2 AND (name IS NULL OR name='' OR name=key)"
+            ).bind(&[company_name.clone().into(), site.into()])?
+        } else {
+            db.prepare("UPDATE companies SET updated_at=datetime('now') WHERE key=?1")
+                .bind(&[site.into()])?
+        }),
+    ];
+    let tracking_outcome = crate::run_batch_resilient(db, tracking_stmts).await;
+    for (label, reason) in &tracking_outcome.failed {
+        console_log!("[job-sync:{}] tracking write failed for {}: {}", S::source_kind(), label, reason);
+    }
+
+    crate::record_sync_run(db, S::source_kind(), site, &crate::SyncRunMetrics {
+        fetched: postings.len(),
+        inserted,
+        updated,
+        skipped,
+        errors: outcome.failed.len(),
+        duration_ms: (js_sys::Date::now() - started_at) as i64,
+    }).await;
+
+    Ok(outcome)
+}
@@ -2,6 +2,7 @@ use worker::*;
 
 use crate::rig_compat;
 use crate::types::DiscoveredBoard;
+use crate::warc;
 
 /// Build the enrichment pipeline (Rig ResultPipeline pattern).
 /// Each named step propagates errors; step names appear in error responses.
@@ -46,12 +47,43 @@ pub fn build_enrichment_pipeline() -> rig_compat::ResultPipeline {
         // Step 4: Structured extraction via SlugExtractor (industries + tech signals)
         .then("extract_metadata", |mut val| {
             if let Some(slug) = val.get("slug").and_then(|s| s.as_str()).map(String::from) {
-                val["extracted"] = rig_compat::SlugExtractor::extract(&slug);
+                let mut extracted = rig_compat::SlugExtractor::extract(&slug);
+                // Prefer the archived page's company name (Step 0, offline) over
+                // the slug-derived guess when Common Crawl gave us one.
+                if let Some(name) = val.get("archived_company_name").and_then(|v| v.as_str()) {
+                    extracted["company_name"] = serde_json::json!(name);
+                }
+                val["extracted"] = extracted;
             }
             Ok(val)
         })
 }
 
+/// Step 0 (async, run before the `ResultPipeline`): pull `<title>`/OpenGraph
+/// metadata out of the board's archived Common Crawl snapshot, when its WARC
+/// pointers are present, so enrichment doesn't have to hit the live ATS API.
+/// Best-effort — any fetch/parse failure just means this board enriches from
+/// its slug alone, same as before this module existed.
+async fn enrich_from_warc(board: &DiscoveredBoard) -> serde_json::Value {
+    match warc::fetch_archived_board_meta(board).await {
+        Ok(Some(meta)) => {
+            let mut extra = serde_json::json!({});
+            if let Some(name) = meta.company_name() {
+                extra["archived_company_name"] = serde_json::json!(name);
+            }
+            if let Some(desc) = meta.og_description.or(meta.meta_description) {
+                extra["archived_description"] = serde_json::json!(desc);
+            }
+            extra
+        }
+        Ok(None) => serde_json::json!({}),
+        Err(e) => {
+            console_log!("[enrich:warc] token={} fetch failed: {:?}", board.token, e);
+            serde_json::json!({})
+        }
+    }
+}
+
 /// Run SlugExtractor + ResultPipeline on a batch of boards and persist enrichment
 /// columns (company_name, industry_tags, tech_signals, enriched_at) back to D1.
 pub async fn auto_enrich_boards(db: &D1Database, boards: &[DiscoveredBoard]) -> Result<usize> {
@@ -66,11 +98,16 @@ pub async fn auto_enrich_boards(db: &D1Database, boards: &[DiscoveredBoard]) ->
     let mut stmts = Vec::with_capacity(boards.len());
 
     for board in boards {
-        let row = serde_json::json!({
+        let mut row = serde_json::json!({
             "slug":      board.token,
             "url":       board.url,
             "last_seen": board.timestamp,
         });
+        if let (serde_json::Value::Object(extra), serde_json::Value::Object(row_obj)) =
+            (enrich_from_warc(board).await, &mut row)
+        {
+            row_obj.extend(extra);
+        }
 
         let enriched = match pipeline.run(row) {
             Ok(v) => v,
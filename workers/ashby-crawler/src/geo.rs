@@ -0,0 +1,281 @@
+use worker::*;
+
+// ═══════════════════════════════════════════════════════════════════════════
+// Location normalization + geocoding
+// ═══════════════════════════════════════════════════════════════════════════
+//
+// ATS location strings are free text ("Remote - US", "San Francisco, CA",
+// "SF", "Lisbon, Portugal") that can't be searched together or by distance as
+// raw strings. `parse_location` normalizes one into city/region/country/
+// remote components plus a best-effort lat/lng from a small built-in
+// gazetteer (no network call, covers common cases instantly); its callers
+// fall back to `geocode_external` (a single best-effort HTTP lookup) when
+// the gazetteer doesn't recognize it. A location that can't be resolved to
+// coordinates at all keeps its normalized text components (or just the raw
+// string) so it's still searchable as plain text — only radius search misses
+// it, not the rest of the index.
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GeoPoint {
+    pub lat: f64,
+    pub lng: f64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NormalizedLocation {
+    pub city: Option<String>,
+    pub region: Option<String>,
+    pub country: Option<String>,
+    pub remote: bool,
+    pub point: Option<GeoPoint>,
+}
+
+/// `(lowercase aliases, city, region, country ISO-2, lat, lng)`. Deliberately
+/// small — common tech-hub cities plus a handful of ambiguous abbreviations —
+/// not an attempt at a complete world gazetteer. Anything missing falls back
+/// to `geocode_external`.
+const GAZETTEER: &[(&[&str], &str, &str, &str, f64, f64)] = &[
+    (&["san francisco", "sf", "san francisco, ca"], "San Francisco", "CA", "US", 37.7749, -122.4194),
+    (&["new york", "new york city", "nyc", "ny"], "New York", "NY", "US", 40.7128, -74.0060),
+    (&["los angeles", "la"], "Los Angeles", "CA", "US", 34.0522, -118.2437),
+    (&["seattle"], "Seattle", "WA", "US", 47.6062, -122.3321),
+    (&["austin"], "Austin", "TX", "US", 30.2672, -97.7431),
+    (&["chicago"], "Chicago", "IL", "US", 41.8781, -87.6298),
+    (&["boston"], "Boston", "MA", "US", 42.3601, -71.0589),
+    (&["denver"], "Denver", "CO", "US", 39.7392, -104.9903),
+    (&["washington", "washington dc", "dc"], "Washington", "DC", "US", 38.9072, -77.0369),
+    (&["toronto"], "Toronto", "ON", "CA", 43.6532, -79.3832),
+    (&["vancouver"], "Vancouver", "BC", "CA", 49.2827, -123.1207),
+    (&["london"], "London", "", "GB", 51.5072, -0.1276),
+    (&["dublin"], "Dublin", "", "IE", 53.3498, -6.2603),
+    (&["berlin"], "Berlin", "", "DE", 52.5200, 13.4050),
+    (&["munich"], "Munich", "", "DE", 48.1351, 11.5820),
+    (&["amsterdam"], "Amsterdam", "", "NL", 52.3676, 4.9041),
+    (&["paris"], "Paris", "", "FR", 48.8566, 2.3522),
+    (&["lisbon"], "Lisbon", "", "PT", 38.7223, -9.1393),
+    (&["madrid"], "Madrid", "", "ES", 40.4168, -3.7038),
+    (&["barcelona"], "Barcelona", "", "ES", 41.3851, 2.1734),
+    (&["zurich"], "Zurich", "", "CH", 47.3769, 8.5417),
+    (&["stockholm"], "Stockholm", "", "SE", 59.3293, 18.0686),
+    (&["warsaw"], "Warsaw", "", "PL", 52.2297, 21.0122),
+    (&["krakow", "cracow"], "Krakow", "", "PL", 50.0647, 19.9450),
+    (&["tel aviv"], "Tel Aviv", "", "IL", 32.0853, 34.7818),
+    (&["bangalore", "bengaluru"], "Bangalore", "", "IN", 12.9716, 77.5946),
+    (&["mumbai", "bombay"], "Mumbai", "", "IN", 19.0760, 72.8777),
+    (&["singapore"], "Singapore", "", "SG", 1.3521, 103.8198),
+    (&["sydney"], "Sydney", "", "AU", -33.8688, 151.2093),
+    (&["melbourne"], "Melbourne", "", "AU", -37.8136, 144.9631),
+    (&["tokyo"], "Tokyo", "", "JP", 35.6762, 139.6503),
+    (&["sao paulo", "são paulo"], "Sao Paulo", "", "BR", -23.5505, -46.6333),
+    (&["mexico city"], "Mexico City", "", "MX", 19.4326, -99.1332),
+];
+
+/// Country names/abbreviations that show up in ATS location strings, mapped
+/// to an ISO-3166-1 alpha-2 code. Not exhaustive — an unrecognized country
+/// token is kept, uppercased, rather than dropped.
+fn normalize_country(raw: &str) -> String {
+    match raw.trim().to_lowercase().as_str() {
+        "us" | "usa" | "u.s." | "u.s.a." | "united states" | "united states of america" => "US".to_string(),
+        "uk" | "u.k." | "united kingdom" | "great britain" => "GB".to_string(),
+        "canada" | "ca" => "CA".to_string(),
+        "germany" | "de" => "DE".to_string(),
+        "france" | "fr" => "FR".to_string(),
+        "portugal" | "pt" => "PT".to_string(),
+        "spain" | "es" => "ES".to_string(),
+        "ireland" | "ie" => "IE".to_string(),
+        "netherlands" | "nl" | "the netherlands" => "NL".to_string(),
+        "india" | "in" => "IN".to_string(),
+        "australia" | "au" => "AU".to_string(),
+        "japan" | "jp" => "JP".to_string(),
+        "brazil" | "br" => "BR".to_string(),
+        "mexico" | "mx" => "MX".to_string(),
+        other => other.to_uppercase(),
+    }
+}
+
+fn gazetteer_lookup(key: &str) -> Option<NormalizedLocation> {
+    let key = key.trim().to_lowercase();
+    GAZETTEER.iter().find(|(aliases, ..)| aliases.contains(&key.as_str())).map(
+        |(_, city, region, country, lat, lng)| NormalizedLocation {
+            city: Some((*city).to_string()),
+            region: if region.is_empty() { None } else { Some((*region).to_string()) },
+            country: Some((*country).to_string()),
+            remote: false,
+            point: Some(GeoPoint { lat: *lat, lng: *lng }),
+        },
+    )
+}
+
+/// Parse a raw ATS location string into its structured components. Gazetteer
+/// lookup only — does not make a network call; pair with `geocode_external`
+/// for a fallback when `point` comes back `None`.
+pub fn parse_location(raw: &str) -> NormalizedLocation {
+    let trimmed = raw.trim();
+    if trimmed.is_empty() {
+        return NormalizedLocation::default();
+    }
+
+    let lower = trimmed.to_lowercase();
+    if lower.starts_with("remote") {
+        // "Remote", "Remote - US", "Remote (US)", "Remote, US"
+        let rest = trimmed[6..]
+            .trim_start_matches(|c: char| c.is_whitespace() || matches!(c, '-' | ',' | '('))
+            .trim_end_matches(')')
+            .trim();
+        let mut loc = NormalizedLocation { remote: true, ..Default::default() };
+        if !rest.is_empty() {
+            loc.country = Some(normalize_country(rest));
+        }
+        return loc;
+    }
+
+    // "City, Region/Country" — try the whole string first (handles single
+    // gazetteer keys like "new york city"), then the part before the comma.
+    if let Some(hit) = gazetteer_lookup(trimmed) {
+        return hit;
+    }
+    if let Some((city_part, rest)) = trimmed.split_once(',') {
+        if let Some(mut hit) = gazetteer_lookup(city_part) {
+            let rest = rest.trim();
+            if !rest.is_empty() && hit.region.is_none() {
+                // Heuristic: a 2-letter token is a US state/province code,
+                // anything longer is a country name.
+                if rest.len() <= 2 {
+                    hit.region = Some(rest.to_uppercase());
+                } else {
+                    hit.country = Some(normalize_country(rest));
+                }
+            }
+            return hit;
+        }
+        return NormalizedLocation {
+            city: Some(city_part.trim().to_string()),
+            country: if rest.trim().is_empty() { None } else { Some(normalize_country(rest.trim())) },
+            ..Default::default()
+        };
+    }
+
+    NormalizedLocation { city: Some(trimmed.to_string()), ..Default::default() }
+}
+
+/// Best-effort external geocode via OpenStreetMap Nominatim, for place names
+/// the built-in gazetteer doesn't recognize. A failed fetch/parse/empty
+/// result just means this location stays text-only (no radius match) —
+/// never propagated as an error.
+pub async fn geocode_external(query: &str) -> Option<GeoPoint> {
+    let url = format!(
+        "https://nominatim.openstreetmap.org/search?q={}&format=json&limit=1",
+        urlencode(query)
+    );
+    let mut headers = Headers::new();
+    headers.set("User-Agent", "ashby-crawler/0.4 (job location geocoding)").ok()?;
+    let req = Request::new_with_init(&url, RequestInit::new().with_method(Method::Get).with_headers(headers)).ok()?;
+    let mut resp = Fetch::Request(req).send().await.ok()?;
+    if resp.status_code() != 200 {
+        return None;
+    }
+    let text = resp.text().await.ok()?;
+    let results: Vec<serde_json::Value> = serde_json::from_str(&text).ok()?;
+    let hit = results.first()?;
+    let lat: f64 = hit.get("lat")?.as_str()?.parse().ok()?;
+    let lng: f64 = hit.get("lon")?.as_str()?.parse().ok()?;
+    Some(GeoPoint { lat, lng })
+}
+
+fn urlencode(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '~') {
+            out.push(c);
+        } else {
+            let mut buf = [0u8; 4];
+            for b in c.encode_utf8(&mut buf).as_bytes() {
+                out.push_str(&format!("%{b:02X}"));
+            }
+        }
+    }
+    out
+}
+
+/// Great-circle distance between two points, in kilometers.
+pub fn haversine_km(a: GeoPoint, b: GeoPoint) -> f64 {
+    const EARTH_RADIUS_KM: f64 = 6371.0;
+    let (lat1, lat2) = (a.lat.to_radians(), b.lat.to_radians());
+    let d_lat = (b.lat - a.lat).to_radians();
+    let d_lng = (b.lng - a.lng).to_radians();
+    let h = (d_lat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (d_lng / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_KM * h.sqrt().asin()
+}
+
+/// A rough lat/lng bounding box around `center` out to `radius_km`, for a
+/// cheap SQL pre-filter before the exact haversine check in Rust — D1/SQLite
+/// has no trig functions to compute distance in the query itself.
+pub fn bounding_box(center: GeoPoint, radius_km: f64) -> (f64, f64, f64, f64) {
+    const KM_PER_DEGREE_LAT: f64 = 111.0;
+    let lat_delta = radius_km / KM_PER_DEGREE_LAT;
+    let lng_delta = radius_km / (KM_PER_DEGREE_LAT * center.lat.to_radians().cos().abs().max(0.01));
+    (center.lat - lat_delta, center.lat + lat_delta, center.lng - lng_delta, center.lng + lng_delta)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_location_remote_with_country() {
+        let loc = parse_location("Remote - US");
+        assert!(loc.remote);
+        assert_eq!(loc.country.as_deref(), Some("US"));
+    }
+
+    #[test]
+    fn parse_location_bare_remote() {
+        let loc = parse_location("Remote");
+        assert!(loc.remote);
+        assert_eq!(loc.country, None);
+    }
+
+    #[test]
+    fn parse_location_gazetteer_city_state() {
+        let loc = parse_location("San Francisco, CA");
+        assert_eq!(loc.city.as_deref(), Some("San Francisco"));
+        assert_eq!(loc.region.as_deref(), Some("CA"));
+        assert_eq!(loc.country.as_deref(), Some("US"));
+        assert!(loc.point.is_some());
+    }
+
+    #[test]
+    fn parse_location_unrecognized_city_keeps_text() {
+        let loc = parse_location("Springfield");
+        assert_eq!(loc.city.as_deref(), Some("Springfield"));
+        assert_eq!(loc.point, None);
+    }
+
+    #[test]
+    fn parse_location_empty_is_default() {
+        assert_eq!(parse_location(""), NormalizedLocation::default());
+        assert_eq!(parse_location("   "), NormalizedLocation::default());
+    }
+
+    #[test]
+    fn haversine_km_same_point_is_zero() {
+        let p = GeoPoint { lat: 37.7749, lng: -122.4194 };
+        assert!(haversine_km(p, p) < 1e-9);
+    }
+
+    #[test]
+    fn haversine_km_sf_to_nyc_is_roughly_right() {
+        let sf = GeoPoint { lat: 37.7749, lng: -122.4194 };
+        let nyc = GeoPoint { lat: 40.7128, lng: -74.0060 };
+        let km = haversine_km(sf, nyc);
+        assert!((km - 4129.0).abs() < 50.0, "expected ~4129km, got {km}");
+    }
+
+    #[test]
+    fn bounding_box_contains_center() {
+        let center = GeoPoint { lat: 40.7128, lng: -74.0060 };
+        let (lat_min, lat_max, lng_min, lng_max) = bounding_box(center, 100.0);
+        assert!(lat_min < center.lat && center.lat < lat_max);
+        assert!(lng_min < center.lng && center.lng < lng_max);
+    }
+}
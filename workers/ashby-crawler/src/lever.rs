@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 use worker::*;
 
+use crate::ats::{self, AtsSource, ExtraColumn, ExtraUpdateMode, JobRow};
+
 // ═══════════════════════════════════════════════════════════════════════════
 // Lever Postings API v0 types
 // ═══════════════════════════════════════════════════════════════════════════
@@ -122,181 +124,125 @@ pub async fn fetch_lever_board_jobs(site: &str) -> Result<Vec<LeverPosting>> {
 }
 
 // ═══════════════════════════════════════════════════════════════════════════
-// UPSERT
+// UPSERT — via the generic `ats::AtsSource` pipeline
 // ═══════════════════════════════════════════════════════════════════════════
 
-/// Upsert Lever postings into D1 `jobs` table.
-/// External ID = `hostedUrl` (canonical URL, same pattern as Greenhouse).
-pub async fn upsert_lever_jobs_to_d1(
-    db: &D1Database,
-    postings: &[LeverPosting],
-    site: &str,
-) -> Result<usize> {
-    let company_name: String = site
-        .split(|c: char| c == '-' || c == '_')
-        .map(|w| {
-            let mut chars = w.chars();
-            match chars.next() {
-                None => String::new(),
-                Some(c) => c.to_uppercase().to_string() + chars.as_str(),
-            }
-        })
-        .collect::<Vec<_>>()
-        .join(" ");
+/// Unix-ms timestamp → ISO 8601, without pulling in a chrono dependency.
+/// Good enough for dates 2000-2099 (simplified Gregorian arithmetic).
+fn unix_ms_to_iso8601(ms: f64) -> String {
+    let secs = (ms / 1000.0) as i64;
+    let days_since_epoch = secs / 86400;
+    let time_of_day = secs % 86400;
+    let hours = time_of_day / 3600;
+    let minutes = (time_of_day % 3600) / 60;
+    let seconds = time_of_day % 60;
+
+    let mut remaining = days_since_epoch;
+    let mut year = 1970i64;
+    loop {
+        let days_in_year = if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) { 366 } else { 365 };
+        if remaining < days_in_year { break; }
+        remaining -= days_in_year;
+        year += 1;
+    }
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let month_days: [i64; 12] = [31, if leap { 29 } else { 28 }, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
+    let mut month = 0usize;
+    for (i, &d) in month_days.iter().enumerate() {
+        if remaining < d { month = i; break; }
+        remaining -= d;
+    }
+    let day = remaining + 1;
+    format!("{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z", year, month + 1, day, hours, minutes, seconds)
+}
 
-    // Maps to Lever-specific columns in the jobs table (schema.ts lines 102-112)
-    const JOB_SQL: &str = "INSERT INTO jobs (
-                external_id, source_kind, source_id, company_key, company_name,
-                title, url, description, location,
-                posted_at,
-                categories, workplace_type, country,
-                opening, opening_plain,
-                description_body, description_body_plain,
-                additional, additional_plain,
-                lists, ats_created_at, updated_at
-            ) VALUES (
-                ?1, 'lever', ?2, ?3, ?4,
-                ?5, ?6, NULLIF(?7,''), NULLIF(?8,''),
-                COALESCE(NULLIF(?9,''), datetime('now')),
-                NULLIF(?10,''), NULLIF(?11,''), NULLIF(?12,''),
-                NULLIF(?13,''), NULLIF(?14,''),
-                NULLIF(?15,''), NULLIF(?16,''),
-                NULLIF(?17,''), NULLIF(?18,''),
-                NULLIF(?19,''), NULLIF(?9,''), datetime('now')
-            )
-            ON CONFLICT(external_id) DO UPDATE SET
-                source_id=excluded.source_id,
-                company_key=excluded.company_key,
-                company_name=COALESCE(excluded.company_name, company_name),
-                title=excluded.title,
-                url=excluded.url,
-                description=COALESCE(excluded.description, description),
-                location=COALESCE(excluded.location, location),
-                posted_at=COALESCE(excluded.posted_at, posted_at),
-                categories=excluded.categories,
-                workplace_type=COALESCE(excluded.workplace_type, workplace_type),
-                country=COALESCE(excluded.country, country),
-                opening=COALESCE(excluded.opening, opening),
-                opening_plain=COALESCE(excluded.opening_plain, opening_plain),
-                description_body=COALESCE(excluded.description_body, description_body),
-                description_body_plain=COALESCE(excluded.description_body_plain, description_body_plain),
-                additional=COALESCE(excluded.additional, additional),
-                additional_plain=COALESCE(excluded.additional_plain, additional_plain),
-                lists=excluded.lists,
-                ats_created_at=excluded.ats_created_at,
-                updated_at=datetime('now')";
+pub struct LeverSource;
 
-    let mut stmts = Vec::with_capacity(postings.len() + 2);
-    let mut count = 0usize;
+impl AtsSource for LeverSource {
+    type Posting = LeverPosting;
 
-    for posting in postings {
-        let url = posting.hosted_url.as_deref().unwrap_or("");
-        if url.is_empty() {
-            console_log!("[job-sync:lever] skipping posting {} (no hostedUrl) from site {}", posting.id, site);
-            continue;
-        }
-        let external_id = url.to_string();
+    fn source_kind() -> &'static str { "lever" }
+    fn board_table() -> &'static str { "lever_boards" }
+    fn board_key_column() -> &'static str { "site" }
+    fn board_url(site: &str) -> String { format!("https://jobs.lever.co/{site}") }
 
-        let description = posting.description.as_deref().unwrap_or("");
-        let location = posting.categories.as_ref()
-            .and_then(|c| c.location.as_deref())
-            .unwrap_or("");
+    async fn fetch(site: &str) -> Result<Vec<Self::Posting>> {
+        fetch_lever_board_jobs(site).await
+    }
 
-        // Convert createdAt (unix ms) to ISO 8601 without chrono dependency
-        let created_at_iso = posting.created_at
-            .map(|ms| {
-                let secs = (ms / 1000.0) as i64;
-                // Use simple arithmetic — good enough for dates 2000-2099
-                let days_since_epoch = secs / 86400;
-                let time_of_day = secs % 86400;
-                let hours = time_of_day / 3600;
-                let minutes = (time_of_day % 3600) / 60;
-                let seconds = time_of_day % 60;
+    fn external_id(posting: &Self::Posting) -> Option<String> {
+        posting.hosted_url.as_deref().filter(|u| !u.is_empty()).map(str::to_string)
+    }
 
-                // Days since 1970-01-01 to Y-M-D (simplified Gregorian)
-                let mut remaining = days_since_epoch;
-                let mut year = 1970i64;
-                loop {
-                    let days_in_year = if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) { 366 } else { 365 };
-                    if remaining < days_in_year { break; }
-                    remaining -= days_in_year;
-                    year += 1;
-                }
-                let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
-                let month_days: [i64; 12] = [31, if leap { 29 } else { 28 }, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
-                let mut month = 0usize;
-                for (i, &d) in month_days.iter().enumerate() {
-                    if remaining < d { month = i; break; }
-                    remaining -= d;
+    fn raw_locations(posting: &Self::Posting) -> Vec<String> {
+        // Every distinct raw location string on the posting (primary +
+        // `allLocations`), so radius search ("near:... within:...") can match
+        // on any of a multi-site posting's locations, not just the first.
+        let mut locations: Vec<String> = Vec::new();
+        if let Some(loc) = posting.categories.as_ref().and_then(|c| c.location.as_deref()) {
+            if !loc.is_empty() {
+                locations.push(loc.to_string());
+            }
+        }
+        if let Some(all) = posting.categories.as_ref().and_then(|c| c.all_locations.as_ref()) {
+            for loc in all {
+                if !loc.is_empty() && !locations.contains(loc) {
+                    locations.push(loc.clone());
                 }
-                let day = remaining + 1;
-                format!("{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z", year, month + 1, day, hours, minutes, seconds)
-            })
-            .unwrap_or_default();
+            }
+        }
+        locations
+    }
 
+    fn to_job_row(posting: &Self::Posting, site: &str, company_name: &str) -> JobRow {
         let categories_json = posting.categories.as_ref()
             .map(|c| serde_json::to_string(c).unwrap_or_default())
             .unwrap_or_default();
         let lists_json = posting.lists.as_ref()
             .map(|l| serde_json::to_string(l).unwrap_or_default())
             .unwrap_or_default();
-
-        stmts.push(db.prepare(JOB_SQL).bind(&[
-            external_id.into(),                                         // ?1  external_id
-            site.into(),                                                // ?2  source_id
-            site.into(),                                                // ?3  company_key
-            company_name.clone().into(),                                // ?4  company_name
-            posting.text.clone().into(),                                // ?5  title
-            url.into(),                                                 // ?6  url
-            description.into(),                                         // ?7  description
-            location.into(),                                            // ?8  location
-            created_at_iso.into(),                                      // ?9  posted_at / ats_created_at
-            categories_json.into(),                                     // ?10 categories
-            posting.workplace_type.as_deref().unwrap_or("").into(),     // ?11 workplace_type
-            posting.country.as_deref().unwrap_or("").into(),            // ?12 country
-            posting.opening.as_deref().unwrap_or("").into(),            // ?13 opening
-            posting.opening_plain.as_deref().unwrap_or("").into(),      // ?14 opening_plain
-            posting.description_body.as_deref().unwrap_or("").into(),   // ?15 description_body
-            posting.description_body_plain.as_deref().unwrap_or("").into(), // ?16 description_body_plain
-            posting.additional.as_deref().unwrap_or("").into(),         // ?17 additional
-            posting.additional_plain.as_deref().unwrap_or("").into(),   // ?18 additional_plain
-            lists_json.into(),                                          // ?19 lists
-        ])?);
-        count += 1;
-    }
-
-    // Track in lever_boards table
-    stmts.push(db.prepare(
-        "INSERT INTO lever_boards (site, url, first_seen, last_seen, crawl_id, last_synced_at, job_count, is_active)
-         VALUES (?1, ?2, datetime('now'), datetime('now'), 'job-sync', datetime('now'), ?3, 1)
-         ON CONFLICT(site) DO UPDATE SET
-           last_synced_at=datetime('now'),
-           job_count=?3,
-           is_active=1,
-           updated_at=datetime('now')"
-    ).bind(&[
-        site.into(),
-        format!("https://jobs.lever.co/{}", site).into(),
-        (count as f64).into(),
-    ])?);
-
-    // Update company name when we only have a slug or empty name
-    if !company_name.is_empty() {
-        stmts.push(db.prepare(
-            "UPDATE companies SET name=?1, updated_at=datetime('now') WHERE key=?2 AND (name IS NULL OR name='' OR name=key)"
-        ).bind(&[
-            company_name.clone().into(),
-            site.into(),
-        ])?);
-    } else {
-        stmts.push(db.prepare("UPDATE companies SET updated_at=datetime('now') WHERE key=?1")
-            .bind(&[site.into()])?);
-    }
-
-    const BATCH_SIZE: usize = 100;
-    for chunk in stmts.chunks(BATCH_SIZE) {
-        let _ = db.batch(chunk.to_vec()).await;
+        let posted_at = posting.created_at.map(unix_ms_to_iso8601).unwrap_or_default();
+
+        JobRow {
+            source_id: site.to_string(),
+            company_key: site.to_string(),
+            company_name: company_name.to_string(),
+            title: posting.text.clone(),
+            url: posting.hosted_url.clone().unwrap_or_default(),
+            description: posting.description.clone().unwrap_or_default(),
+            location: posting.categories.as_ref().and_then(|c| c.location.clone()).unwrap_or_default(),
+            posted_at,
+            country: posting.country.clone().unwrap_or_default(),
+            workplace_type: posting.workplace_type.clone().unwrap_or_default(),
+            salary_min: posting.salary_range.as_ref().and_then(|s| s.min).map(worker::wasm_bindgen::JsValue::from_f64).unwrap_or(worker::wasm_bindgen::JsValue::NULL),
+            salary_max: posting.salary_range.as_ref().and_then(|s| s.max).map(worker::wasm_bindgen::JsValue::from_f64).unwrap_or(worker::wasm_bindgen::JsValue::NULL),
+            salary_currency: posting.salary_range.as_ref().and_then(|s| s.currency.clone()).unwrap_or_default(),
+            extra: vec![
+                ExtraColumn::text("categories", &categories_json, ExtraUpdateMode::Overwrite),
+                ExtraColumn::text("opening", posting.opening.as_deref().unwrap_or(""), ExtraUpdateMode::CoalesceIfNull),
+                ExtraColumn::text("opening_plain", posting.opening_plain.as_deref().unwrap_or(""), ExtraUpdateMode::CoalesceIfNull),
+                ExtraColumn::text("description_body", posting.description_body.as_deref().unwrap_or(""), ExtraUpdateMode::CoalesceIfNull),
+                ExtraColumn::text("description_body_plain", posting.description_body_plain.as_deref().unwrap_or(""), ExtraUpdateMode::CoalesceIfNull),
+                ExtraColumn::text("additional", posting.additional.as_deref().unwrap_or(""), ExtraUpdateMode::CoalesceIfNull),
+                ExtraColumn::text("additional_plain", posting.additional_plain.as_deref().unwrap_or(""), ExtraUpdateMode::CoalesceIfNull),
+                ExtraColumn::text("lists", &lists_json, ExtraUpdateMode::Overwrite),
+            ],
+            ..Default::default()
+        }
     }
+}
 
-    Ok(count)
+/// Upsert Lever postings into D1 `jobs` table.
+/// External ID = `hostedUrl` (canonical URL, same pattern as Greenhouse).
+///
+/// Returns a [`crate::BatchOutcome`] rather than a bare count: job statements
+/// run through `crate::run_batch_resilient`, so a malformed posting or a
+/// transient D1 error fails (and is reported against) only that posting's
+/// `external_id` instead of silently dropping the rest of the chunk.
+pub async fn upsert_lever_jobs_to_d1(
+    db: &D1Database,
+    postings: &[LeverPosting],
+    site: &str,
+) -> Result<crate::BatchOutcome> {
+    ats::upsert_jobs_to_d1::<LeverSource>(db, postings, site, None).await
 }
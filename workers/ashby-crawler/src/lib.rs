@@ -1,9 +1,28 @@
 use futures::future::{join, join_all};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
 use worker::*;
 use worker::wasm_bindgen::JsValue;
 
+// Multi-provider module split (Common Crawl discovery → D1 → enrichment) that
+// the original Ashby-only pipeline below is gradually migrating onto. Most of
+// these modules back the handlers and cron tick further down; `types`,
+// `enrichment` and `warc` are not yet called from them. `enrichment`/`warc`
+// reuse the inline `mod rig_compat` and the shared `types::DiscoveredBoard`.
+mod types;
+mod enrichment;
+mod ats;
+mod geo;
+mod greenhouse;
+mod lever;
+mod migrations;
+mod query_parser;
+mod tasks;
+mod warc;
+mod workable;
+mod workers;
+
 // ═══════════════════════════════════════════════════════════════════════════
 // MODULE: rig_compat — Rig framework patterns adapted for CF Workers/WASM
 // ═══════════════════════════════════════════════════════════════════════════
@@ -21,6 +40,167 @@ mod rig_compat {
     use serde::{Deserialize, Serialize};
     use std::collections::HashMap;
 
+    // ── 0. TYPO TOLERANCE (shared bounded-edit-distance term expansion) ───
+    //
+    // Used by both `Bm25Index` and `InMemoryVectorStore` below: expand a query
+    // token to nearby corpus terms (`Bm25Index` expands every token,
+    // `InMemoryVectorStore` only tokens with no exact vocabulary match)
+    // instead of scoring a typo as a miss, so "kubernets"/"postgress" still find
+    // "kubernetes"/"postgres". Each index keeps its own first-character +
+    // length bucketed vocabulary (built in `rebuild_index`) so a query token
+    // only needs to be compared against same-bucket candidates rather than
+    // the whole vocabulary.
+
+    /// Edit-distance budget per query-token length, using the same length
+    /// bands MeiliSearch uses for typo tolerance: too short to fuzz safely
+    /// (distance ≤1 on a 3-letter word covers half the alphabet), ≤1 for
+    /// common typo lengths, ≤2 once there's enough signal left after a
+    /// two-character slip. `max_typos` (from a caller wanting fewer typos, or
+    /// `Some(0)` for exact-only matching) caps the band's budget rather than
+    /// raising it.
+    fn typo_distance_budget(token_len: usize, max_typos: Option<usize>) -> usize {
+        let band_budget = match token_len {
+            0..=3 => 0,
+            4..=8 => 1,
+            _ => 2,
+        };
+        match max_typos {
+            Some(cap) => band_budget.min(cap),
+            None => band_budget,
+        }
+    }
+
+    /// Decay applied to a fuzzy term match's score contribution per unit of
+    /// edit distance, so an exact match always outranks a 1-edit fuzzy match,
+    /// which in turn outranks a 2-edit one.
+    const TYPO_DECAY: f64 = 0.6;
+
+    /// Damerau-Levenshtein edit distance (insert/delete/substitute, plus
+    /// adjacent-transposition as a single edit — the "optimal string
+    /// alignment" variant, O(len_a * len_b) with three kept rows) so a
+    /// transposed pair like "kubenretes"/"kubernetes" costs 1 edit instead of
+    /// 2 under plain Levenshtein.
+    fn levenshtein(a: &str, b: &str) -> usize {
+        let a: Vec<char> = a.chars().collect();
+        let b: Vec<char> = b.chars().collect();
+        let (la, lb) = (a.len(), b.len());
+        if la == 0 { return lb; }
+        if lb == 0 { return la; }
+        let mut prev2 = vec![0usize; lb + 1];
+        let mut prev: Vec<usize> = (0..=lb).collect();
+        let mut cur = vec![0usize; lb + 1];
+        for i in 1..=la {
+            cur[0] = i;
+            for j in 1..=lb {
+                let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+                let mut best = (prev[j] + 1).min(cur[j - 1] + 1).min(prev[j - 1] + cost);
+                if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                    best = best.min(prev2[j - 2] + 1);
+                }
+                cur[j] = best;
+            }
+            std::mem::swap(&mut prev2, &mut prev);
+            std::mem::swap(&mut prev, &mut cur);
+        }
+        prev[lb]
+    }
+
+    /// Bucket vocabulary terms by (first char, char count) so `fuzzy_variants`
+    /// only scans same-bucket candidates instead of the full vocabulary.
+    fn bucket_vocab<'a>(terms: impl Iterator<Item = &'a String>) -> HashMap<(char, usize), Vec<String>> {
+        let mut buckets: HashMap<(char, usize), Vec<String>> = HashMap::new();
+        for term in terms {
+            if let Some(c) = term.chars().next() {
+                buckets.entry((c, term.chars().count())).or_default().push(term.clone());
+            }
+        }
+        buckets
+    }
+
+    /// Cap on how many expansions (fuzzy or prefix) a single query token may
+    /// contribute, so a short/common token sitting in a large same-length
+    /// bucket can't fan out into scoring a large fraction of the corpus.
+    /// Closest matches are kept — by edit distance for `fuzzy_variants`, by
+    /// shortest extension for `prefix_variants`.
+    const MAX_TOKEN_EXPANSIONS: usize = 10;
+
+    /// Find `(variant, distance)` pairs for `token` among bucketed vocabulary
+    /// terms within `token`'s typo-distance budget (see [`typo_distance_budget`];
+    /// `max_typos` caps or, with `Some(0)`, disables it entirely for callers
+    /// that need exact matching). Candidate lengths are restricted to
+    /// `token.len() ± budget` — a cheap length-difference prefilter, since
+    /// edit distance can never be smaller than the length difference —
+    /// before the full Damerau-Levenshtein DP table runs on what's left.
+    fn fuzzy_variants(token: &str, buckets: &HashMap<(char, usize), Vec<String>>, max_typos: Option<usize>) -> Vec<(String, usize)> {
+        let token_len = token.chars().count();
+        let budget = typo_distance_budget(token_len, max_typos);
+        if budget == 0 {
+            return Vec::new();
+        }
+        let Some(first) = token.chars().next() else { return Vec::new() };
+        let mut variants = Vec::new();
+        for len in token_len.saturating_sub(budget)..=token_len + budget {
+            let Some(candidates) = buckets.get(&(first, len)) else { continue };
+            for candidate in candidates {
+                if candidate == token {
+                    continue;
+                }
+                let dist = levenshtein(token, candidate);
+                if dist > 0 && dist <= budget {
+                    variants.push((candidate.clone(), dist));
+                }
+            }
+        }
+        variants.sort_by_key(|(_, dist)| *dist);
+        variants.truncate(MAX_TOKEN_EXPANSIONS);
+        variants
+    }
+
+    /// How many characters longer than `token` a vocabulary term may be to
+    /// still count as a completion of it in `prefix_variants` — bounds the
+    /// scan to the same first-character buckets `fuzzy_variants` uses
+    /// instead of a full-vocabulary `starts_with` sweep.
+    const PREFIX_MAX_EXTRA_LEN: usize = 8;
+
+    /// Vocabulary terms that start with `token` and are longer than it —
+    /// used for the final token of a query, so a still-being-typed word
+    /// ("strip") still matches its completions ("stripe") in the corpus
+    /// instead of waiting for the user to finish typing.
+    fn prefix_variants(token: &str, buckets: &HashMap<(char, usize), Vec<String>>) -> Vec<String> {
+        let token_len = token.chars().count();
+        if token_len == 0 {
+            return Vec::new();
+        }
+        let Some(first) = token.chars().next() else { return Vec::new() };
+        let mut variants = Vec::new();
+        for len in (token_len + 1)..=(token_len + PREFIX_MAX_EXTRA_LEN) {
+            let Some(candidates) = buckets.get(&(first, len)) else { continue };
+            for candidate in candidates {
+                if candidate.starts_with(token) {
+                    variants.push(candidate.clone());
+                }
+            }
+        }
+        variants.sort_by_key(|v| v.len());
+        variants.truncate(MAX_TOKEN_EXPANSIONS);
+        variants
+    }
+
+    /// Cheap content hash over a corpus' `(id, text)` pairs — shared by
+    /// `Bm25Index`/`InMemoryVectorStore` to tag a snapshot on save and, on
+    /// load, to check a stored snapshot against the current corpus without
+    /// doing a full rebuild just to find out it's stale.
+    pub(crate) fn corpus_content_hash<'a>(docs: impl Iterator<Item = (&'a str, &'a str)>) -> String {
+        let mut input = String::new();
+        for (id, text) in docs {
+            input.push_str(id);
+            input.push('\0');
+            input.push_str(text);
+            input.push('\n');
+        }
+        crate::sha256_hex(input.as_bytes())
+    }
+
     // ── 1. VECTOR STORE (mirrors rig::vector_store) ──────────────────────
 
     /// A document stored in the vector index, mirroring rig's VectorStoreDocument.
@@ -39,14 +219,40 @@ mod rig_compat {
         pub text: String,
         pub score: f64,
         pub metadata: HashMap<String, String>,
+        /// Cropped window of `text` centered on the densest cluster of query
+        /// terms. `None` unless populated via `Bm25Index::rank_with_snippets`
+        /// / `InMemoryVectorStore::top_n_with_snippets` — see section 11.
+        #[serde(default)]
+        pub snippet: Option<String>,
+        /// Same crop as `snippet`, with each matched term wrapped in
+        /// `HighlightTags`. `None` under the same conditions as `snippet`.
+        #[serde(default)]
+        pub highlighted: Option<String>,
     }
 
+    /// Default `add_document`/`remove_document` calls tolerated between full
+    /// `rebuild_index` passes before `maybe_rebuild_index` forces one — each
+    /// insert/remove skews `idf` a little further from the corpus's true
+    /// distribution (and leaves the affected document's embedding stale),
+    /// so this bounds how far the index is allowed to drift.
+    const VECTOR_STORE_DEFAULT_DIRTY_THRESHOLD: usize = 50;
+
     /// In-memory vector store with cosine similarity.
     /// Mirrors rig::vector_store::InMemoryVectorStore but uses TF-IDF
     /// embeddings instead of requiring an LLM embedding model.
     pub struct InMemoryVectorStore {
         documents: Vec<VectorDocument>,
         idf: HashMap<String, f64>,
+        vocab_buckets: HashMap<(char, usize), Vec<String>>,
+        /// Running per-term document frequency, maintained incrementally by
+        /// `add_document`/`remove_document` in O(that document's vocabulary)
+        /// — `idf` itself is only ever recomputed from this by
+        /// `rebuild_index`/`maybe_rebuild_index`, since embeddings need the
+        /// full corpus-wide IDF to stay comparable to each other.
+        doc_freq: HashMap<String, f64>,
+        /// Count of `add_document`/`remove_document` calls since the last
+        /// full `rebuild_index`. See `is_dirty`/`maybe_rebuild_index`.
+        dirty_inserts: usize,
     }
 
     impl InMemoryVectorStore {
@@ -54,6 +260,9 @@ mod rig_compat {
             Self {
                 documents: Vec::new(),
                 idf: HashMap::new(),
+                vocab_buckets: HashMap::new(),
+                doc_freq: HashMap::new(),
+                dirty_inserts: 0,
             }
         }
 
@@ -66,18 +275,33 @@ mod rig_compat {
                 .collect()
         }
 
-        /// Build TF-IDF embedding for a single document against the corpus IDF.
-        fn tf_idf_embed(&self, text: &str) -> Vec<f64> {
+        /// Build a TF-IDF query embedding against the corpus IDF. Tokens with
+        /// no exact vocabulary entry are expanded to nearby corpus terms (see
+        /// `fuzzy_variants`), each contributing `TYPO_DECAY.powi(distance)`
+        /// of a normal occurrence instead of being dropped as a miss.
+        /// `max_typos` caps (or, with `Some(0)`, disables) that expansion —
+        /// see `fuzzy_variants`. Also returns the `"original->variant"`
+        /// corrections actually used, for `top_n` to surface in
+        /// `SearchResult::metadata`.
+        fn tf_idf_embed(&self, text: &str, max_typos: Option<usize>) -> (Vec<f64>, Vec<String>) {
             let tokens = Self::tokenize(text);
             let total = tokens.len() as f64;
             if total == 0.0 {
-                return vec![0.0; self.idf.len()];
+                return (vec![0.0; self.idf.len()], Vec::new());
             }
 
-            // Term frequency
-            let mut tf: HashMap<&str, f64> = HashMap::new();
+            // Term frequency, with fuzzy expansion for unmatched tokens
+            let mut tf: HashMap<String, f64> = HashMap::new();
+            let mut corrections = Vec::new();
             for t in &tokens {
-                *tf.entry(t.as_str()).or_default() += 1.0;
+                if self.idf.contains_key(t) {
+                    *tf.entry(t.clone()).or_default() += 1.0;
+                    continue;
+                }
+                for (variant, dist) in fuzzy_variants(t, &self.vocab_buckets, max_typos) {
+                    *tf.entry(variant.clone()).or_default() += TYPO_DECAY.powi(dist as i32);
+                    corrections.push(format!("{t}->{variant}"));
+                }
             }
             for v in tf.values_mut() {
                 *v /= total;
@@ -86,12 +310,20 @@ mod rig_compat {
             // Build vector in deterministic IDF key order
             let mut keys: Vec<&String> = self.idf.keys().collect();
             keys.sort();
-            keys.iter()
+            let embedding = keys
+                .iter()
                 .map(|k| tf.get(k.as_str()).unwrap_or(&0.0) * self.idf.get(*k).unwrap_or(&0.0))
-                .collect()
+                .collect();
+            (embedding, corrections)
         }
 
-        /// Recompute IDF from all stored document texts, then regenerate embeddings.
+        /// Recompute `doc_freq`/IDF from all stored document texts from
+        /// scratch, then regenerate every embedding. Not required for
+        /// steady-state ingestion (see `add_document`/`remove_document`,
+        /// which maintain `doc_freq` incrementally) — kept as the
+        /// consistency-restoring pass `maybe_rebuild_index` calls once
+        /// drift crosses a threshold, or after bulk document loads that
+        /// bypassed incremental tracking entirely (e.g. `load_document`).
         pub fn rebuild_index(&mut self) {
             let n = self.documents.len() as f64;
             if n == 0.0 {
@@ -107,12 +339,15 @@ mod rig_compat {
                     *doc_freq.entry(token).or_default() += 1.0;
                 }
             }
+            self.doc_freq = doc_freq.clone();
+            self.dirty_inserts = 0;
 
             // IDF = ln(N / df)
             self.idf = doc_freq
                 .into_iter()
                 .map(|(k, df)| (k, (n / df).ln()))
                 .collect();
+            self.vocab_buckets = bucket_vocab(self.idf.keys());
 
             // Regenerate all embeddings
             let idf = &self.idf;
@@ -139,6 +374,14 @@ mod rig_compat {
         }
 
         /// Add a document and return its tokens (for persistence).
+        ///
+        /// Folds the new document into `doc_freq` in O(its vocabulary)
+        /// instead of waiting for a full `rebuild_index` pass, so `idf`
+        /// can track steady-state ingestion cheaply — but `idf` itself and
+        /// every document's `embedding` (this one included — it's pushed
+        /// with `embedding: vec![]`) stay stale until `rebuild_index` or
+        /// `maybe_rebuild_index` actually runs, since embeddings need the
+        /// full corpus-wide IDF to stay comparable to each other.
         pub fn add_document(
             &mut self,
             id: String,
@@ -146,24 +389,83 @@ mod rig_compat {
             metadata: HashMap<String, String>,
         ) -> Vec<String> {
             let tokens = Self::tokenize(&text);
+            let unique: std::collections::HashSet<&str> = tokens.iter().map(String::as_str).collect();
+            for term in unique {
+                *self.doc_freq.entry(term.to_string()).or_default() += 1.0;
+            }
             self.documents.push(VectorDocument {
                 id,
                 text,
                 embedding: vec![], // filled on rebuild_index
                 metadata,
             });
+            self.dirty_inserts += 1;
             tokens
         }
 
+        /// Remove a document by id, reversing its per-token `doc_freq`
+        /// contribution in O(that document's vocabulary) rather than
+        /// recomputing the whole corpus. Like `add_document`, leaves `idf`
+        /// stale until a rebuild. Returns `true` if a document with this id
+        /// was found and removed.
+        pub fn remove_document(&mut self, id: &str) -> bool {
+            let Some(pos) = self.documents.iter().position(|d| d.id == id) else { return false };
+            let doc = self.documents.remove(pos);
+            let unique: std::collections::HashSet<String> = Self::tokenize(&doc.text).into_iter().collect();
+            for term in unique {
+                if let Some(count) = self.doc_freq.get_mut(&term) {
+                    *count -= 1.0;
+                    if *count <= 0.0 {
+                        self.doc_freq.remove(&term);
+                    }
+                }
+            }
+            self.dirty_inserts += 1;
+            true
+        }
+
         /// Load a pre-computed document (from D1 persistence).
         pub fn load_document(&mut self, doc: VectorDocument) {
             self.documents.push(doc);
         }
 
         pub fn set_idf(&mut self, idf: HashMap<String, f64>) {
+            self.vocab_buckets = bucket_vocab(idf.keys());
             self.idf = idf;
         }
 
+        /// Count of `add_document`/`remove_document` calls since the last
+        /// full `rebuild_index`. See `is_dirty`/`maybe_rebuild_index`.
+        pub fn dirty_count(&self) -> usize {
+            self.dirty_inserts
+        }
+
+        /// `true` once `dirty_count()` has reached `threshold` — pass this
+        /// (or `VECTOR_STORE_DEFAULT_DIRTY_THRESHOLD`) to decide whether
+        /// `idf` has drifted far enough from the live corpus to be worth
+        /// paying for a full `rebuild_index`.
+        pub fn is_dirty(&self, threshold: usize) -> bool {
+            self.dirty_inserts >= threshold
+        }
+
+        /// Run `rebuild_index` only if `is_dirty(threshold)` — lets batched
+        /// ingestion call this after every document without re-embedding
+        /// the whole corpus on every single insert. Returns whether a
+        /// rebuild actually ran.
+        pub fn maybe_rebuild_index(&mut self, threshold: usize) -> bool {
+            if self.is_dirty(threshold) {
+                self.rebuild_index();
+                true
+            } else {
+                false
+            }
+        }
+
+        /// `maybe_rebuild_index` with `VECTOR_STORE_DEFAULT_DIRTY_THRESHOLD`.
+        pub fn maybe_rebuild_index_default(&mut self) -> bool {
+            self.maybe_rebuild_index(VECTOR_STORE_DEFAULT_DIRTY_THRESHOLD)
+        }
+
         /// Cosine similarity between two vectors.
         fn cosine_sim(a: &[f64], b: &[f64]) -> f64 {
             if a.len() != b.len() || a.is_empty() {
@@ -179,17 +481,35 @@ mod rig_compat {
         }
 
         /// Semantic search: embed query with TF-IDF, rank by cosine similarity.
-        /// Mirrors rig::vector_store::VectorStoreIndex::top_n().
+        /// Mirrors rig::vector_store::VectorStoreIndex::top_n(). Uses the
+        /// default per-length typo budget; see [`Self::top_n_with_max_typos`]
+        /// to cap or disable fuzzy matching.
         pub fn top_n(&self, query: &str, n: usize) -> Vec<SearchResult> {
-            let query_emb = self.tf_idf_embed(query);
+            self.top_n_with_max_typos(query, n, None)
+        }
+
+        /// Like [`Self::top_n`], but `max_typos` caps the per-length fuzzy
+        /// budget, or disables fuzzy matching entirely with `Some(0)`, for
+        /// callers that need exact-token matching.
+        pub fn top_n_with_max_typos(&self, query: &str, n: usize, max_typos: Option<usize>) -> Vec<SearchResult> {
+            let (query_emb, corrections) = self.tf_idf_embed(query, max_typos);
+            let corrections_note = (!corrections.is_empty()).then(|| corrections.join(", "));
             let mut scored: Vec<SearchResult> = self
                 .documents
                 .iter()
-                .map(|doc| SearchResult {
-                    id: doc.id.clone(),
-                    text: doc.text.clone(),
-                    score: Self::cosine_sim(&query_emb, &doc.embedding),
-                    metadata: doc.metadata.clone(),
+                .map(|doc| {
+                    let mut metadata = doc.metadata.clone();
+                    if let Some(note) = &corrections_note {
+                        metadata.insert("typo_corrections".to_string(), note.clone());
+                    }
+                    SearchResult {
+                        id: doc.id.clone(),
+                        text: doc.text.clone(),
+                        score: Self::cosine_sim(&query_emb, &doc.embedding),
+                        metadata,
+                        snippet: None,
+                        highlighted: None,
+                    }
                 })
                 .collect();
             scored.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
@@ -197,6 +517,22 @@ mod rig_compat {
             scored
         }
 
+        /// Like [`Self::top_n`], but also populates each hit's
+        /// `snippet`/`highlighted` via [`build_snippet`] (section 11),
+        /// cropped to the densest cluster of query terms. `tags` controls
+        /// the highlight delimiters — `HighlightTags::default()` for
+        /// `<em>`/`</em>`.
+        pub fn top_n_with_snippets(&self, query: &str, n: usize, tags: HighlightTags) -> Vec<SearchResult> {
+            let query_tokens = Self::tokenize(query);
+            let mut results = self.top_n(query, n);
+            for r in &mut results {
+                let (snippet, highlighted) = build_snippet(&r.text, &query_tokens, tags);
+                r.snippet = snippet;
+                r.highlighted = highlighted;
+            }
+            results
+        }
+
         pub fn len(&self) -> usize {
             self.documents.len()
         }
@@ -204,6 +540,60 @@ mod rig_compat {
         pub fn documents(&self) -> &[VectorDocument] {
             &self.documents
         }
+
+        /// Content hash of the current corpus — see `corpus_content_hash`.
+        pub fn content_hash(&self) -> String {
+            corpus_content_hash(self.documents.iter().map(|d| (d.id.as_str(), d.text.as_str())))
+        }
+
+        /// Serializable form of the index, for `search_index_snapshots` to
+        /// survive a Worker cold start without re-tokenizing/re-embedding the
+        /// whole corpus from D1. `doc_freq`/`dirty_inserts` aren't part of
+        /// it — they only matter between `add_document`/`remove_document`
+        /// calls and a rebuild, and a snapshot is always saved right after
+        /// one (see `build_hybrid_index`), so a restored store is
+        /// never dirty.
+        pub fn to_snapshot(&self) -> VectorStoreSnapshot {
+            VectorStoreSnapshot {
+                version: VECTOR_STORE_SNAPSHOT_VERSION,
+                corpus_hash: self.content_hash(),
+                idf: self.idf.clone(),
+                documents: self.documents.clone(),
+            }
+        }
+
+        /// Restore from a snapshot saved by `to_snapshot`. Only the `idf`
+        /// and `documents` (with their already-computed `embedding`s) need
+        /// restoring — `vocab_buckets` is cheap to rebuild from `idf`'s
+        /// keys, and `doc_freq`/`dirty_inserts` start fresh since this path
+        /// never calls `add_document`/`remove_document` afterward.
+        pub fn from_snapshot(snapshot: VectorStoreSnapshot) -> std::result::Result<Self, String> {
+            if snapshot.version > VECTOR_STORE_SNAPSHOT_VERSION {
+                return Err(format!(
+                    "VectorStoreSnapshot version {} is newer than supported version {VECTOR_STORE_SNAPSHOT_VERSION}",
+                    snapshot.version
+                ));
+            }
+            let vocab_buckets = bucket_vocab(snapshot.idf.keys());
+            Ok(Self {
+                documents: snapshot.documents,
+                idf: snapshot.idf,
+                vocab_buckets,
+                doc_freq: HashMap::new(),
+                dirty_inserts: 0,
+            })
+        }
+    }
+
+    const VECTOR_STORE_SNAPSHOT_VERSION: u32 = 1;
+
+    /// See [`InMemoryVectorStore::to_snapshot`]/[`InMemoryVectorStore::from_snapshot`].
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct VectorStoreSnapshot {
+        pub version: u32,
+        pub corpus_hash: String,
+        pub idf: HashMap<String, f64>,
+        pub documents: Vec<VectorDocument>,
     }
 
     // ── 2. PIPELINE (mirrors rig::pipeline) ──────────────────────────────
@@ -303,29 +693,109 @@ mod rig_compat {
         len: u32,
     }
 
+    /// Corpus-level stats exposed by [`Bm25Index::stats`] so callers can
+    /// decide whether incremental drift is worth a periodic full
+    /// `rebuild_index` (e.g. after heavy churn, to compact `vocab_buckets`).
+    #[derive(Debug, Clone, Copy, Serialize)]
+    pub struct Bm25Stats {
+        pub avg_dl: f64,
+        pub doc_count: usize,
+        pub vocab_size: usize,
+    }
+
     /// Okapi BM25 index. Mirrors rig's VectorStore but uses probabilistic ranking
     /// instead of cosine similarity. No embedding model or LLM required.
     pub struct Bm25Index {
         docs: Vec<Bm25Doc>,
         doc_freq: HashMap<String, u32>,
         avg_dl: f64,
+        vocab_buckets: HashMap<(char, usize), Vec<String>>,
+        total_tokens: u64,
     }
 
     impl Bm25Index {
         pub fn new() -> Self {
-            Self { docs: Vec::new(), doc_freq: HashMap::new(), avg_dl: 0.0 }
+            Self {
+                docs: Vec::new(),
+                doc_freq: HashMap::new(),
+                avg_dl: 0.0,
+                vocab_buckets: HashMap::new(),
+                total_tokens: 0,
+            }
         }
 
+        /// Add a document, updating `doc_freq`/`vocab_buckets`/`avg_dl`
+        /// incrementally (O(unique terms in this document)) so streamed,
+        /// append-only ingestion never needs a full `rebuild_index` pass.
         pub fn add_document(&mut self, id: String, text: String, metadata: HashMap<String, String>) {
             let tokens = InMemoryVectorStore::tokenize(&text);
             let len = tokens.len() as u32;
             let mut term_freq: HashMap<String, u32> = HashMap::new();
             for t in &tokens { *term_freq.entry(t.clone()).or_default() += 1; }
+
+            for term in term_freq.keys() {
+                let count = self.doc_freq.entry(term.clone()).or_insert(0);
+                if *count == 0 {
+                    if let Some(first) = term.chars().next() {
+                        self.vocab_buckets.entry((first, term.chars().count())).or_default().push(term.clone());
+                    }
+                }
+                *count += 1;
+            }
+
+            self.total_tokens += len as u64;
             self.docs.push(Bm25Doc { id, text, metadata, term_freq, len });
+            self.avg_dl = self.total_tokens as f64 / self.docs.len() as f64;
+        }
+
+        /// Remove a document by id, reversing its per-term `doc_freq`
+        /// contribution and running totals in O(that document's vocabulary)
+        /// rather than recomputing the whole corpus. Returns `true` if a
+        /// document with this id was found and removed.
+        pub fn remove_document(&mut self, id: &str) -> bool {
+            let Some(pos) = self.docs.iter().position(|d| d.id == id) else { return false };
+            let doc = self.docs.remove(pos);
+            self.total_tokens = self.total_tokens.saturating_sub(doc.len as u64);
+
+            for term in doc.term_freq.keys() {
+                if let Some(count) = self.doc_freq.get_mut(term) {
+                    *count -= 1;
+                    if *count == 0 {
+                        self.doc_freq.remove(term);
+                        if let Some(first) = term.chars().next() {
+                            let key = (first, term.chars().count());
+                            if let Some(bucket) = self.vocab_buckets.get_mut(&key) {
+                                bucket.retain(|t| t != term);
+                                if bucket.is_empty() {
+                                    self.vocab_buckets.remove(&key);
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+
+            self.avg_dl = if self.docs.is_empty() { 0.0 } else { self.total_tokens as f64 / self.docs.len() as f64 };
+            true
+        }
+
+        /// Current corpus stats, for callers deciding whether incremental
+        /// drift is worth a periodic `rebuild_index`.
+        pub fn stats(&self) -> Bm25Stats {
+            Bm25Stats {
+                avg_dl: self.avg_dl,
+                doc_count: self.docs.len(),
+                vocab_size: self.doc_freq.len(),
+            }
         }
 
+        /// Full recompute of `doc_freq`/`avg_dl`/`vocab_buckets` from
+        /// scratch. Not required for append-only workloads (see
+        /// `add_document`/`remove_document`) — kept as a consistency-restoring
+        /// fallback, e.g. after bulk document loads that bypassed them.
         pub fn rebuild_index(&mut self) {
             let total: u32 = self.docs.iter().map(|d| d.len).sum();
+            self.total_tokens = total as u64;
             self.avg_dl = if self.docs.is_empty() { 0.0 } else { total as f64 / self.docs.len() as f64 };
             self.doc_freq.clear();
             for doc in &self.docs {
@@ -333,40 +803,199 @@ mod rig_compat {
                     *self.doc_freq.entry(term.clone()).or_default() += 1;
                 }
             }
+            self.vocab_buckets = bucket_vocab(self.doc_freq.keys());
         }
 
         /// BM25 ranking: mirrors rig::vector_store::VectorStoreIndex::top_n()
-        /// but uses probabilistic IDF weighting (k1=1.5, b=0.75).
+        /// but uses probabilistic IDF weighting (k1=1.5, b=0.75). Every query
+        /// token, exact matches included, is also expanded to nearby corpus terms
+        /// (see `fuzzy_variants`), each matched variant treated as an
+        /// occurrence of the original term but decayed by
+        /// `TYPO_DECAY.powi(distance)` so exact matches still outrank fuzzy
+        /// ones. The final query token is additionally expanded as a prefix
+        /// (see `prefix_variants`, decayed as if at distance 1) so a query
+        /// still being typed still matches. Documents that matched at least
+        /// one fuzzy/prefix variant carry a `"typo_corrections"` metadata entry
+        /// so callers can surface them. Uses the default per-length typo
+        /// budget; see [`Self::rank_with_max_typos`] to cap or disable fuzzy
+        /// matching.
         pub fn rank(&self, query: &str, n: usize) -> Vec<SearchResult> {
+            self.rank_with_max_typos(query, n, None)
+        }
+
+        /// Like [`Self::rank`], but `max_typos` caps the per-length fuzzy
+        /// budget, or disables fuzzy matching entirely with `Some(0)`, for
+        /// callers that need exact-token matching. `max_typos` also disables
+        /// the final-token prefix expansion, since both exist for the same
+        /// "don't require an exact-vocabulary query" reason.
+        pub fn rank_with_max_typos(&self, query: &str, n: usize, max_typos: Option<usize>) -> Vec<SearchResult> {
             let query_tokens = InMemoryVectorStore::tokenize(query);
             let n_docs = self.docs.len() as f64;
             if n_docs == 0.0 { return vec![]; }
 
-            let mut scored: Vec<SearchResult> = self.docs.iter().map(|doc| {
+            // Resolve each query token to itself (if in-vocabulary) plus any
+            // within-budget fuzzy variants, each carrying its decay weight.
+            // The last token also gets prefix-completed against the
+            // vocabulary, at the same decay as a single-edit fuzzy match.
+            let last = query_tokens.len().saturating_sub(1);
+            let resolved: Vec<Vec<(String, f64)>> = query_tokens.iter().enumerate().map(|(i, term)| {
+                let mut matches = Vec::new();
+                if self.doc_freq.contains_key(term) {
+                    matches.push((term.clone(), 1.0));
+                }
+                for (variant, dist) in fuzzy_variants(term, &self.vocab_buckets, max_typos) {
+                    matches.push((variant, TYPO_DECAY.powi(dist as i32)));
+                }
+                if i == last && max_typos != Some(0) {
+                    for variant in prefix_variants(term, &self.vocab_buckets) {
+                        if !matches.iter().any(|(t, _)| *t == variant) {
+                            matches.push((variant, TYPO_DECAY));
+                        }
+                    }
+                }
+                matches
+            }).collect();
+
+            let mut scored = Vec::with_capacity(self.docs.len());
+            for doc in &self.docs {
                 let dl = doc.len as f64;
-                let score: f64 = query_tokens.iter().map(|term| {
-                    let tf = *doc.term_freq.get(term).unwrap_or(&0) as f64;
-                    let df = *self.doc_freq.get(term).unwrap_or(&0) as f64;
-                    if tf == 0.0 || df == 0.0 { return 0.0; }
-                    let idf = ((n_docs - df + 0.5) / (df + 0.5) + 1.0).ln();
-                    let tf_norm = tf * (BM25_K1 + 1.0)
-                        / (tf + BM25_K1 * (1.0 - BM25_B + BM25_B * dl / self.avg_dl.max(1.0)));
-                    idf * tf_norm
-                }).sum();
-                SearchResult {
-                    id: doc.id.clone(),
-                    text: doc.text.clone(),
-                    score,
-                    metadata: doc.metadata.clone(),
+                let mut score = 0.0;
+                let mut corrections: Vec<String> = Vec::new();
+                for (original, matches) in query_tokens.iter().zip(resolved.iter()) {
+                    for (term, decay) in matches {
+                        let tf = *doc.term_freq.get(term).unwrap_or(&0) as f64;
+                        let df = *self.doc_freq.get(term).unwrap_or(&0) as f64;
+                        if tf == 0.0 || df == 0.0 { continue; }
+                        let idf = ((n_docs - df + 0.5) / (df + 0.5) + 1.0).ln();
+                        let tf_norm = tf * (BM25_K1 + 1.0)
+                            / (tf + BM25_K1 * (1.0 - BM25_B + BM25_B * dl / self.avg_dl.max(1.0)));
+                        score += idf * tf_norm * decay;
+                        if term != original {
+                            let correction = format!("{original}->{term}");
+                            if !corrections.contains(&correction) {
+                                corrections.push(correction);
+                            }
+                        }
+                    }
                 }
-            }).filter(|r| r.score > 0.0).collect();
+                if score <= 0.0 {
+                    continue;
+                }
+                let mut metadata = doc.metadata.clone();
+                if !corrections.is_empty() {
+                    metadata.insert("typo_corrections".to_string(), corrections.join(", "));
+                }
+                scored.push(SearchResult { id: doc.id.clone(), text: doc.text.clone(), score, metadata, snippet: None, highlighted: None });
+            }
 
             scored.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
             scored.truncate(n);
             scored
         }
 
+        /// Like [`Self::rank`], but also populates each hit's
+        /// `snippet`/`highlighted` via [`build_snippet`] (section 11),
+        /// cropped to the densest cluster of query terms. `tags` controls
+        /// the highlight delimiters — `HighlightTags::default()` for
+        /// `<em>`/`</em>`. Uses the default per-length typo budget; see
+        /// [`Self::rank_with_snippets_and_max_typos`] to cap or disable it.
+        pub fn rank_with_snippets(&self, query: &str, n: usize, tags: HighlightTags) -> Vec<SearchResult> {
+            self.rank_with_snippets_and_max_typos(query, n, tags, None)
+        }
+
+        /// Like [`Self::rank_with_snippets`], but `max_typos` caps the
+        /// per-length fuzzy budget, or disables fuzzy matching entirely with
+        /// `Some(0)` — see [`Self::rank_with_max_typos`].
+        pub fn rank_with_snippets_and_max_typos(&self, query: &str, n: usize, tags: HighlightTags, max_typos: Option<usize>) -> Vec<SearchResult> {
+            let query_tokens = InMemoryVectorStore::tokenize(query);
+            let mut results = self.rank_with_max_typos(query, n, max_typos);
+            for r in &mut results {
+                let (snippet, highlighted) = build_snippet(&r.text, &query_tokens, tags);
+                r.snippet = snippet;
+                r.highlighted = highlighted;
+            }
+            results
+        }
+
         pub fn len(&self) -> usize { self.docs.len() }
+
+        /// Content hash of the current corpus — see `corpus_content_hash`.
+        pub fn content_hash(&self) -> String {
+            corpus_content_hash(self.docs.iter().map(|d| (d.id.as_str(), d.text.as_str())))
+        }
+
+        /// Serializable form of the index, for `search_index_snapshots` to
+        /// survive a Worker cold start without re-tokenizing the whole
+        /// corpus from D1. Unlike `InMemoryVectorStore`'s snapshot, every
+        /// field here (`doc_freq`/`avg_dl`/`total_tokens`) is read directly
+        /// by `rank_with_max_typos`'s scoring, so all of it has to round-trip.
+        pub fn to_snapshot(&self) -> Bm25Snapshot {
+            Bm25Snapshot {
+                version: BM25_SNAPSHOT_VERSION,
+                corpus_hash: self.content_hash(),
+                avg_dl: self.avg_dl,
+                total_tokens: self.total_tokens,
+                doc_freq: self.doc_freq.clone(),
+                docs: self.docs.iter().map(|d| Bm25DocSnapshot {
+                    id: d.id.clone(),
+                    text: d.text.clone(),
+                    metadata: d.metadata.clone(),
+                    term_freq: d.term_freq.clone(),
+                    len: d.len,
+                }).collect(),
+            }
+        }
+
+        /// Restore from a snapshot saved by `to_snapshot`. `vocab_buckets` is
+        /// rebuilt from `doc_freq`'s keys rather than stored, same tradeoff
+        /// as `InMemoryVectorStore::from_snapshot`.
+        pub fn from_snapshot(snapshot: Bm25Snapshot) -> std::result::Result<Self, String> {
+            if snapshot.version > BM25_SNAPSHOT_VERSION {
+                return Err(format!(
+                    "Bm25Snapshot version {} is newer than supported version {BM25_SNAPSHOT_VERSION}",
+                    snapshot.version
+                ));
+            }
+            let vocab_buckets = bucket_vocab(snapshot.doc_freq.keys());
+            Ok(Self {
+                docs: snapshot.docs.into_iter().map(|d| Bm25Doc {
+                    id: d.id,
+                    text: d.text,
+                    metadata: d.metadata,
+                    term_freq: d.term_freq,
+                    len: d.len,
+                }).collect(),
+                doc_freq: snapshot.doc_freq,
+                avg_dl: snapshot.avg_dl,
+                vocab_buckets,
+                total_tokens: snapshot.total_tokens,
+            })
+        }
+    }
+
+    const BM25_SNAPSHOT_VERSION: u32 = 1;
+
+    /// Serializable mirror of `Bm25Doc` — `Bm25Doc` itself only derives
+    /// `Clone` since it never otherwise needs to cross a serialization
+    /// boundary.
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct Bm25DocSnapshot {
+        pub id: String,
+        pub text: String,
+        pub metadata: HashMap<String, String>,
+        pub term_freq: HashMap<String, u32>,
+        pub len: u32,
+    }
+
+    /// See [`Bm25Index::to_snapshot`]/[`Bm25Index::from_snapshot`].
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct Bm25Snapshot {
+        pub version: u32,
+        pub corpus_hash: String,
+        pub avg_dl: f64,
+        pub total_tokens: u64,
+        pub doc_freq: HashMap<String, u32>,
+        pub docs: Vec<Bm25DocSnapshot>,
     }
 
     // ── 5. RESULT PIPELINE (named steps + error propagation) ─────────────────
@@ -534,7 +1163,371 @@ mod rig_compat {
         }
     }
 
-    // ── 8. CONCURRENT RUNNER (rig_concurrent_demo pattern for CF Workers/WASM) ─
+    // ── 8. HYBRID INDEX (keyword + vector fusion via Reciprocal Rank Fusion) ──
+    //
+    // `Bm25Index::rank` and `InMemoryVectorStore::top_n` each return a
+    // plausible-looking `score`, but the two scales aren't comparable — BM25
+    // is an unbounded probabilistic weight, cosine is bounded [-1, 1] — so
+    // summing or averaging them directly would let whichever ranker happens
+    // to produce larger numbers dominate. Reciprocal Rank Fusion sidesteps
+    // that by discarding the raw scores and fusing on *rank position* alone:
+    // `rrf_score(d) = Σ_r weight_r / (k + rank_r(d))`, 1-based rank, documents
+    // missing from a ranker's list contributing 0 for that ranker.
+
+    const RRF_DEFAULT_K: f64 = 60.0;
+
+    /// Per-ranker bias for [`HybridIndex::search`]. `1.0`/`1.0` weighs BM25
+    /// and cosine equally; raise `bm25` to favor exact keyword matches, raise
+    /// `vector` to favor semantic/paraphrase matches.
+    #[derive(Clone, Copy, Debug)]
+    pub struct RrfWeights {
+        pub bm25: f64,
+        pub vector: f64,
+    }
+
+    impl Default for RrfWeights {
+        fn default() -> Self {
+            Self { bm25: 1.0, vector: 1.0 }
+        }
+    }
+
+    /// Fuses a [`Bm25Index`] and an [`InMemoryVectorStore`] over the same
+    /// corpus into one ranked `search()`, so callers get BM25's strength on
+    /// short, sparse queries and cosine's strength on paraphrased/semantic
+    /// queries without having to pick one. Mirrors the hybrid keyword+vector
+    /// retrieval pattern rig-based search agents typically sit in front of an
+    /// LLM re-ranker.
+    pub struct HybridIndex {
+        keyword: Bm25Index,
+        vector: InMemoryVectorStore,
+    }
+
+    impl Default for HybridIndex {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl HybridIndex {
+        pub fn new() -> Self {
+            Self { keyword: Bm25Index::new(), vector: InMemoryVectorStore::new() }
+        }
+
+        /// Build from already-populated (and already-rebuilt) sub-indexes —
+        /// e.g. ones just restored via `Bm25Index::from_snapshot`/
+        /// `InMemoryVectorStore::from_snapshot`. `keyword`/`vector` are
+        /// private fields with no other way to construct a `HybridIndex`
+        /// around pre-built components.
+        pub fn from_parts(keyword: Bm25Index, vector: InMemoryVectorStore) -> Self {
+            Self { keyword, vector }
+        }
+
+        /// Add a document to both underlying indexes. Call `rebuild_index`
+        /// once after all documents are added.
+        pub fn add_document(&mut self, id: String, text: String, metadata: HashMap<String, String>) {
+            self.keyword.add_document(id.clone(), text.clone(), metadata.clone());
+            self.vector.add_document(id, text, metadata);
+        }
+
+        pub fn rebuild_index(&mut self) {
+            self.keyword.rebuild_index();
+            self.vector.rebuild_index();
+        }
+
+        pub fn len(&self) -> usize {
+            self.keyword.len()
+        }
+
+        /// Fused search with equal ranker weights. See [`Self::search_weighted`]
+        /// to bias toward lexical or semantic matches. Uses the default
+        /// per-length typo budget; see [`Self::search_with_max_typos`] to cap
+        /// or disable it.
+        pub fn search(&self, query: &str, n: usize) -> Vec<SearchResult> {
+            self.search_with_max_typos(query, n, None)
+        }
+
+        /// Like [`Self::search`], but `max_typos` caps the per-length fuzzy
+        /// budget, or disables fuzzy matching entirely with `Some(0)`, for
+        /// both underlying rankers — see [`Bm25Index::rank_with_max_typos`]/
+        /// [`InMemoryVectorStore::top_n_with_max_typos`].
+        pub fn search_with_max_typos(&self, query: &str, n: usize, max_typos: Option<usize>) -> Vec<SearchResult> {
+            self.search_weighted_with_max_typos(query, n, RrfWeights::default(), max_typos)
+        }
+
+        /// Fused search with explicit per-ranker weights. Uses the default
+        /// per-length typo budget; see [`Self::search_weighted_with_max_typos`].
+        pub fn search_weighted(&self, query: &str, n: usize, weights: RrfWeights) -> Vec<SearchResult> {
+            self.search_weighted_with_max_typos(query, n, weights, None)
+        }
+
+        /// Like [`Self::search_weighted`], but `max_typos` caps (or, with
+        /// `Some(0)`, disables) fuzzy matching in both underlying rankers.
+        ///
+        /// Each ranker is asked for its full corpus ranking (not just `n`)
+        /// so a document ranked, say, 50th by BM25 but 2nd by cosine still
+        /// gets its cosine rank counted — truncating each ranker to `n`
+        /// first would silently zero out exactly the documents RRF exists to
+        /// rescue.
+        pub fn search_weighted_with_max_typos(&self, query: &str, n: usize, weights: RrfWeights, max_typos: Option<usize>) -> Vec<SearchResult> {
+            let corpus_size = self.keyword.len().max(self.vector.len()).max(1);
+            let keyword_ranked = self.keyword.rank_with_max_typos(query, corpus_size, max_typos);
+            let vector_ranked = self.vector.top_n_with_max_typos(query, corpus_size, max_typos);
+
+            let mut rrf_scores: HashMap<&str, f64> = HashMap::new();
+            for (rank, r) in keyword_ranked.iter().enumerate() {
+                *rrf_scores.entry(r.id.as_str()).or_default() += weights.bm25 / (RRF_DEFAULT_K + (rank + 1) as f64);
+            }
+            for (rank, r) in vector_ranked.iter().enumerate() {
+                *rrf_scores.entry(r.id.as_str()).or_default() += weights.vector / (RRF_DEFAULT_K + (rank + 1) as f64);
+            }
+
+            // Keep one SearchResult (text/metadata) per id, preferring the
+            // keyword ranker's copy since it always carries the full text,
+            // then fall back to the vector ranker's for ids only it saw.
+            let mut by_id: HashMap<&str, &SearchResult> = HashMap::new();
+            for r in &vector_ranked {
+                by_id.insert(r.id.as_str(), r);
+            }
+            for r in &keyword_ranked {
+                by_id.insert(r.id.as_str(), r);
+            }
+
+            let mut fused: Vec<SearchResult> = rrf_scores
+                .into_iter()
+                .filter_map(|(id, score)| {
+                    by_id.get(id).map(|r| SearchResult {
+                        id: r.id.clone(),
+                        text: r.text.clone(),
+                        score,
+                        metadata: r.metadata.clone(),
+                        snippet: None,
+                        highlighted: None,
+                    })
+                })
+                .collect();
+            fused.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+            fused.truncate(n);
+            fused
+        }
+
+        /// Fused search using a single `alpha` blend knob (`0.0` = pure BM25,
+        /// `1.0` = pure vector), mirroring the `semanticRatio` control
+        /// MeiliSearch exposes for hybrid search — a thin convenience over
+        /// [`Self::search_weighted`] for callers who'd rather tune one dial
+        /// than two independent ranker weights.
+        pub fn search_alpha(&self, query: &str, n: usize, alpha: f64) -> Vec<SearchResult> {
+            let alpha = alpha.clamp(0.0, 1.0);
+            self.search_weighted(query, n, RrfWeights { bm25: 1.0 - alpha, vector: alpha })
+        }
+
+        /// The BM25 ranker's own ranking, bypassing fusion — lets a caller
+        /// report the per-ranker rank/score that fed into [`Self::search_weighted`]
+        /// alongside the fused result.
+        pub fn keyword_rank(&self, query: &str, n: usize) -> Vec<SearchResult> {
+            self.keyword_rank_with_max_typos(query, n, None)
+        }
+
+        /// Like [`Self::keyword_rank`], but `max_typos` caps or disables
+        /// fuzzy matching — see [`Bm25Index::rank_with_max_typos`].
+        pub fn keyword_rank_with_max_typos(&self, query: &str, n: usize, max_typos: Option<usize>) -> Vec<SearchResult> {
+            self.keyword.rank_with_max_typos(query, n, max_typos)
+        }
+
+        /// The vector ranker's own ranking, bypassing fusion — see [`Self::keyword_rank`].
+        pub fn vector_rank(&self, query: &str, n: usize) -> Vec<SearchResult> {
+            self.vector_rank_with_max_typos(query, n, None)
+        }
+
+        /// Like [`Self::vector_rank`], but `max_typos` caps or disables
+        /// fuzzy matching — see [`InMemoryVectorStore::top_n_with_max_typos`].
+        pub fn vector_rank_with_max_typos(&self, query: &str, n: usize, max_typos: Option<usize>) -> Vec<SearchResult> {
+            self.vector.top_n_with_max_typos(query, n, max_typos)
+        }
+
+        /// Serializable form of both sub-indexes, for `search_index_snapshots`.
+        pub fn to_snapshot(&self) -> HybridSnapshot {
+            HybridSnapshot { keyword: self.keyword.to_snapshot(), vector: self.vector.to_snapshot() }
+        }
+
+        /// Restore from a snapshot saved by `to_snapshot`, via `from_parts`.
+        pub fn from_snapshot(snapshot: HybridSnapshot) -> std::result::Result<Self, String> {
+            let keyword = Bm25Index::from_snapshot(snapshot.keyword)?;
+            let vector = InMemoryVectorStore::from_snapshot(snapshot.vector)?;
+            Ok(Self::from_parts(keyword, vector))
+        }
+    }
+
+    /// See [`HybridIndex::to_snapshot`]/[`HybridIndex::from_snapshot`].
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct HybridSnapshot {
+        pub keyword: Bm25Snapshot,
+        pub vector: VectorStoreSnapshot,
+    }
+
+    // ── 9. RANKING RULES (ordered tie-breaking pipeline over SearchResults) ───
+    //
+    // A raw relevance score alone is a blunt sort key — two results can tie
+    // (or near-tie) on score while differing a lot on how tightly they match
+    // the query. `RankingRules` mirrors the ordered ranking-rule approach
+    // modern keyword engines (e.g. Meilisearch) use: each rule is a
+    // comparator tried in order, and only breaks ties left unresolved by the
+    // rules before it — so "most relevant, then tightest phrase match, then
+    // newest" is just `RankingRules::new(vec![Relevance, Proximity,
+    // DescendingAttribute("posted_at".into())])`.
+
+    /// A single rule in a [`RankingRules`] pipeline.
+    #[derive(Clone, Debug)]
+    pub enum RankingRule {
+        /// `SearchResult::score`, descending — the existing relevance order.
+        Relevance,
+        /// Minimum token span in `text` covering at least one occurrence of
+        /// every distinct query token — smaller (tighter phrase match) ranks
+        /// first. Results missing full coverage sort last.
+        Proximity,
+        /// Count of query terms matched exactly rather than via typo
+        /// expansion (per `SearchResult::metadata["typo_corrections"]`, set
+        /// by `Bm25Index::rank`/`InMemoryVectorStore::top_n`) — more exact
+        /// matches rank first.
+        Exactness,
+        /// Ascending by `metadata[key]`, parsed as a number when possible
+        /// and compared lexically otherwise. Results missing the key sort
+        /// last regardless of direction.
+        Attribute(String),
+        /// Same as `Attribute`, but descending.
+        DescendingAttribute(String),
+    }
+
+    impl RankingRule {
+        fn compare(&self, a: &SearchResult, b: &SearchResult, query_tokens: &[String]) -> std::cmp::Ordering {
+            use std::cmp::Ordering;
+            match self {
+                RankingRule::Relevance => {
+                    b.score.partial_cmp(&a.score).unwrap_or(Ordering::Equal)
+                }
+                RankingRule::Proximity => {
+                    match (proximity_span(&a.text, query_tokens), proximity_span(&b.text, query_tokens)) {
+                        (Some(x), Some(y)) => x.cmp(&y),
+                        (Some(_), None) => Ordering::Less,
+                        (None, Some(_)) => Ordering::Greater,
+                        (None, None) => Ordering::Equal,
+                    }
+                }
+                RankingRule::Exactness => {
+                    let ea = exactness_count(a, query_tokens);
+                    let eb = exactness_count(b, query_tokens);
+                    eb.cmp(&ea)
+                }
+                RankingRule::Attribute(key) => compare_attribute(a, b, key, false),
+                RankingRule::DescendingAttribute(key) => compare_attribute(a, b, key, true),
+            }
+        }
+    }
+
+    /// Compare two results by a `metadata[key]` value: numeric when both
+    /// parse as `f64`, lexical string comparison otherwise. A result missing
+    /// the key sorts last no matter the direction — "unknown" isn't the same
+    /// as "zero" or "worst", so it shouldn't silently win a descending sort.
+    fn compare_attribute(a: &SearchResult, b: &SearchResult, key: &str, descending: bool) -> std::cmp::Ordering {
+        use std::cmp::Ordering;
+        match (a.metadata.get(key), b.metadata.get(key)) {
+            (Some(x), Some(y)) => {
+                let ord = match (x.parse::<f64>(), y.parse::<f64>()) {
+                    (Ok(fx), Ok(fy)) => fx.partial_cmp(&fy).unwrap_or(Ordering::Equal),
+                    _ => x.cmp(y),
+                };
+                if descending { ord.reverse() } else { ord }
+            }
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        }
+    }
+
+    /// Minimum count of contiguous tokens in `text` that covers at least one
+    /// occurrence of every distinct token in `query_tokens` (classic
+    /// minimum-window-substring sliding window). `None` if `text` never
+    /// covers all of them.
+    fn proximity_span(text: &str, query_tokens: &[String]) -> Option<usize> {
+        let needed: std::collections::HashSet<&str> = query_tokens.iter().map(String::as_str).collect();
+        if needed.is_empty() {
+            return None;
+        }
+        let tokens = InMemoryVectorStore::tokenize(text);
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        let mut have = 0usize;
+        let mut left = 0usize;
+        let mut best: Option<usize> = None;
+
+        for right in 0..tokens.len() {
+            let rtok = tokens[right].as_str();
+            if needed.contains(rtok) {
+                let c = counts.entry(rtok).or_insert(0);
+                *c += 1;
+                if *c == 1 {
+                    have += 1;
+                }
+            }
+            while have == needed.len() {
+                let span = right - left + 1;
+                best = Some(best.map_or(span, |b| b.min(span)));
+                let ltok = tokens[left].as_str();
+                if needed.contains(ltok) {
+                    let c = counts.get_mut(ltok).unwrap();
+                    *c -= 1;
+                    if *c == 0 {
+                        have -= 1;
+                    }
+                }
+                left += 1;
+            }
+        }
+        best
+    }
+
+    /// Count of `query_tokens` that matched `result` exactly rather than via
+    /// a `Bm25Index`/`InMemoryVectorStore` typo-expansion (tracked by the
+    /// `"original->variant"` pairs in `metadata["typo_corrections"]`).
+    fn exactness_count(result: &SearchResult, query_tokens: &[String]) -> usize {
+        let corrected_originals: std::collections::HashSet<&str> = result
+            .metadata
+            .get("typo_corrections")
+            .map(|s| s.split(", ").filter_map(|pair| pair.split("->").next()).collect())
+            .unwrap_or_default();
+        query_tokens.iter().filter(|t| !corrected_originals.contains(t.as_str())).count()
+    }
+
+    /// An ordered ranking-rule pipeline: rules are tried left-to-right as a
+    /// lexicographic comparator over [`SearchResult`]s from `Bm25Index` or
+    /// `HybridIndex`, each rule only breaking ties the rules before it left
+    /// unresolved.
+    pub struct RankingRules {
+        rules: Vec<RankingRule>,
+    }
+
+    impl RankingRules {
+        pub fn new(rules: Vec<RankingRule>) -> Self {
+            Self { rules }
+        }
+
+        /// Re-sort `results` for `query` according to this pipeline.
+        /// `Proximity`/`Exactness` recompute against `query`'s tokens, so
+        /// pass the same query string the results were searched with.
+        pub fn sort(&self, query: &str, mut results: Vec<SearchResult>) -> Vec<SearchResult> {
+            let query_tokens = InMemoryVectorStore::tokenize(query);
+            results.sort_by(|a, b| {
+                for rule in &self.rules {
+                    let ordering = rule.compare(a, b, &query_tokens);
+                    if ordering != std::cmp::Ordering::Equal {
+                        return ordering;
+                    }
+                }
+                std::cmp::Ordering::Equal
+            });
+            results
+        }
+    }
+
+    // ── 10. CONCURRENT RUNNER (rig_concurrent_demo pattern for CF Workers/WASM) ─
     //
     // rig_concurrent_demo uses: Arc<Model> + tokio::task::spawn + JoinHandle
     // CF Workers/WASM translation:
@@ -573,27 +1566,587 @@ mod rig_compat {
                 })
         }
     }
-}
 
-// ═══════════════════════════════════════════════════════════════════════════
-// CORE TYPES
-// ═══════════════════════════════════════════════════════════════════════════
+    // ── 11. SNIPPET / HIGHLIGHT (match-centered text cropping for hits) ──
+    //
+    // `SearchResult::score` says *that* a document matched, not *where*.
+    // This mirrors the crop/highlight pair a keyword search engine returns
+    // alongside each hit: `snippet` is a short window of `text` centered on
+    // the densest cluster of query terms, `highlighted` is that same window
+    // with each matched term wrapped in markup — so a Worker's JSON response
+    // is directly renderable without a second pass over `doc.text`.
+
+    /// Delimiters [`Bm25Index::rank_with_snippets`] /
+    /// [`InMemoryVectorStore::top_n_with_snippets`] wrap each matched token
+    /// in. Defaults to `<em>`/`</em>`.
+    #[derive(Clone, Copy, Debug)]
+    pub struct HighlightTags {
+        pub open: &'static str,
+        pub close: &'static str,
+    }
 
-#[derive(Deserialize, Debug, Clone)]
-struct CdxRecord {
-    url: String,
-    timestamp: String,
-    #[serde(default)]
-    status: Option<String>,
-    #[serde(default)]
-    mime: Option<String>,
-    #[serde(default, rename = "mime-detected")]
-    mime_detected: Option<String>,
-    #[serde(default)]
-    filename: Option<String>,
-    #[serde(default)]
-    offset: Option<String>,
-    #[serde(default)]
+    impl Default for HighlightTags {
+        fn default() -> Self {
+            Self { open: "<em>", close: "</em>" }
+        }
+    }
+
+    /// Width, in tokens, of the sliding window `build_snippet` scores.
+    const SNIPPET_WINDOW_TOKENS: usize = 24;
+
+    /// A token as produced by `InMemoryVectorStore::tokenize`'s rules, plus
+    /// its byte span in the original text — `tokenize` discards both case
+    /// and position, but the cropper/highlighter need to slice back into
+    /// `doc.text` without losing surrounding punctuation or casing.
+    struct SpannedToken {
+        lower: String,
+        start: usize,
+        end: usize,
+    }
+
+    /// Same token boundaries as `InMemoryVectorStore::tokenize` (lowercase,
+    /// split on non-alphanumeric runs, length > 1 byte), but keeping each
+    /// token's `start..end` byte span into `text` instead of discarding it.
+    fn tokenize_with_spans(text: &str) -> Vec<SpannedToken> {
+        fn push_run(tokens: &mut Vec<SpannedToken>, text: &str, start: usize, end: usize) {
+            if end - start > 1 {
+                tokens.push(SpannedToken { lower: text[start..end].to_lowercase(), start, end });
+            }
+        }
+        let mut tokens = Vec::new();
+        let mut run_start: Option<usize> = None;
+        let mut run_end = 0usize;
+        for (i, c) in text.char_indices() {
+            if c.is_alphanumeric() {
+                if run_start.is_none() {
+                    run_start = Some(i);
+                }
+                run_end = i + c.len_utf8();
+            } else if let Some(start) = run_start.take() {
+                push_run(&mut tokens, text, start, run_end);
+            }
+        }
+        if let Some(start) = run_start {
+            push_run(&mut tokens, text, start, run_end);
+        }
+        tokens
+    }
+
+    /// Crop `text` to a `SNIPPET_WINDOW_TOKENS`-wide slice centered on the
+    /// densest cluster of `query_tokens` occurrences — slide the fixed-width
+    /// window one token at a time, score each position by how many tokens in
+    /// it are in `query_tokens`, and keep the best — then build a highlighted
+    /// variant of that same slice with each matched token wrapped in `tags`.
+    /// Tokenization reuses `InMemoryVectorStore::tokenize`'s rules (via
+    /// `tokenize_with_spans`) so offsets line up with how terms were indexed;
+    /// returns `(None, None)` if `text` has no tokens or none of
+    /// `query_tokens` occur in it.
+    fn build_snippet(text: &str, query_tokens: &[String], tags: HighlightTags) -> (Option<String>, Option<String>) {
+        let tokens = tokenize_with_spans(text);
+        if tokens.is_empty() {
+            return (None, None);
+        }
+        let query_set: std::collections::HashSet<&str> = query_tokens.iter().map(String::as_str).collect();
+
+        let window = SNIPPET_WINDOW_TOKENS.min(tokens.len());
+        let mut best_start = 0usize;
+        let mut best_score: i64 = -1;
+        for start in 0..=(tokens.len() - window) {
+            let score = tokens[start..start + window].iter()
+                .filter(|t| query_set.contains(t.lower.as_str()))
+                .count() as i64;
+            if score > best_score {
+                best_score = score;
+                best_start = start;
+            }
+        }
+        if best_score <= 0 {
+            return (None, None);
+        }
+
+        let window_tokens = &tokens[best_start..best_start + window];
+        let span_start = window_tokens.first().unwrap().start;
+        let span_end = window_tokens.last().unwrap().end;
+        let snippet = text[span_start..span_end].to_string();
+
+        let mut highlighted = String::with_capacity(snippet.len());
+        let mut cursor = span_start;
+        for t in window_tokens {
+            highlighted.push_str(&text[cursor..t.start]);
+            if query_set.contains(t.lower.as_str()) {
+                highlighted.push_str(tags.open);
+                highlighted.push_str(&text[t.start..t.end]);
+                highlighted.push_str(tags.close);
+            } else {
+                highlighted.push_str(&text[t.start..t.end]);
+            }
+            cursor = t.end;
+        }
+        highlighted.push_str(&text[cursor..span_end]);
+
+        (Some(snippet), Some(highlighted))
+    }
+
+    // ── 12. BM25F FIELDED INDEX (per-field boosted scoring over structured docs) ─
+    //
+    // `Bm25Index` treats a document as one bag of tokens — a hit in
+    // `AshbyJobPosting::title` counts the same as the same term buried in
+    // `description_plain`. BM25F fixes that by length-normalizing term
+    // frequency *per field* (each field keeping its own `avg_dl` and `b`),
+    // summing those normalized frequencies across fields with per-field
+    // boost weights into one `tf_tilde` per term, then applying the
+    // *global* `k1` saturation and IDF once. That global k1/IDF step is
+    // the defining difference from naively ranking each field with its own
+    // `Bm25Index` and summing the scores — doing that would also let `idf`
+    // and `k1` vary per field, double-counting how "globally rare" a term
+    // is once per field instead of once per document.
+
+    const BM25F_K1: f64 = 1.5;
+
+    /// Length-normalization `b` and relevance boost for one field in a
+    /// [`Bm25fIndex`]. `b` behaves like `Bm25Index`'s: `0.0` ignores this
+    /// field's length entirely, `1.0` fully normalizes by its own
+    /// `avg_dl`. `boost` scales this field's contribution to `tf_tilde`
+    /// before the shared `k1` saturation — e.g. `FieldWeight::new(3.0,
+    /// 0.75)` for `title` makes a title hit count ~3x a same-`b`
+    /// `description` hit.
+    #[derive(Clone, Copy, Debug)]
+    pub struct FieldWeight {
+        pub boost: f64,
+        pub b: f64,
+    }
+
+    impl FieldWeight {
+        pub fn new(boost: f64, b: f64) -> Self {
+            Self { boost, b }
+        }
+    }
+
+    /// Neutral field weight used for a field `add_document` supplies that
+    /// wasn't registered at construction — counts once, normalized like a
+    /// typical Okapi BM25 field.
+    const BM25F_DEFAULT_FIELD_WEIGHT: FieldWeight = FieldWeight { boost: 1.0, b: 0.75 };
+
+    #[derive(Clone, Copy, Default)]
+    struct FieldStats {
+        avg_dl: f64,
+        total_tokens: u64,
+    }
+
+    #[derive(Clone)]
+    struct Bm25fDoc {
+        id: String,
+        metadata: HashMap<String, String>,
+        /// field name -> (per-term frequency in that field, field length in tokens)
+        fields: HashMap<String, (HashMap<String, u32>, u32)>,
+        /// All field values joined (for `SearchResult::text`/snippet cropping).
+        text: String,
+    }
+
+    /// BM25F index: mirrors [`Bm25Index`] but scores named fields (title,
+    /// description, location, …) separately before combining them, so a
+    /// hit in a high-boost field like `title` outranks the same term
+    /// buried in `description`. See the section-12 header comment above
+    /// for the scoring formula.
+    pub struct Bm25fIndex {
+        docs: Vec<Bm25fDoc>,
+        doc_freq: HashMap<String, u32>,
+        field_weights: HashMap<String, FieldWeight>,
+        field_stats: HashMap<String, FieldStats>,
+    }
+
+    impl Bm25fIndex {
+        /// `field_weights` registers every field this index expects, e.g.
+        /// `[("title".into(), FieldWeight::new(3.0, 0.75)),
+        /// ("description".into(), FieldWeight::new(1.0, 0.75)),
+        /// ("location".into(), FieldWeight::new(1.5, 0.75))]` to map onto
+        /// `AshbyJobPosting::{title, description_plain, location}`. A field
+        /// `add_document` supplies that isn't in this map scores with
+        /// `BM25F_DEFAULT_FIELD_WEIGHT` instead of being rejected.
+        pub fn new(field_weights: impl IntoIterator<Item = (String, FieldWeight)>) -> Self {
+            Self {
+                docs: Vec::new(),
+                doc_freq: HashMap::new(),
+                field_weights: field_weights.into_iter().collect(),
+                field_stats: HashMap::new(),
+            }
+        }
+
+        fn field_weight(&self, field: &str) -> FieldWeight {
+            self.field_weights.get(field).copied().unwrap_or(BM25F_DEFAULT_FIELD_WEIGHT)
+        }
+
+        /// Add a document with named fields, e.g. `[("title".into(),
+        /// job.title.clone()), ("description".into(),
+        /// job.description_plain.clone().unwrap_or_default()),
+        /// ("location".into(), job.location.clone().unwrap_or_default())]`.
+        /// `doc_freq` is deduped across a document's fields — a term
+        /// appearing in both `title` and `description` only counts once
+        /// toward that document's contribution — while each field's
+        /// running `avg_dl` updates from this document's length in that
+        /// field alone, mirroring `Bm25Index::add_document`'s incremental
+        /// bookkeeping.
+        pub fn add_document(&mut self, id: String, fields: Vec<(String, String)>, metadata: HashMap<String, String>) {
+            let mut field_data: HashMap<String, (HashMap<String, u32>, u32)> = HashMap::new();
+            let mut doc_terms: std::collections::HashSet<String> = std::collections::HashSet::new();
+            let mut text_parts = Vec::with_capacity(fields.len());
+
+            for (field, value) in &fields {
+                let tokens = InMemoryVectorStore::tokenize(value);
+                let len = tokens.len() as u32;
+                let mut term_freq: HashMap<String, u32> = HashMap::new();
+                for t in &tokens {
+                    *term_freq.entry(t.clone()).or_default() += 1;
+                    doc_terms.insert(t.clone());
+                }
+                let stats = self.field_stats.entry(field.clone()).or_default();
+                stats.total_tokens += len as u64;
+                field_data.insert(field.clone(), (term_freq, len));
+                text_parts.push(value.clone());
+            }
+
+            for term in &doc_terms {
+                *self.doc_freq.entry(term.clone()).or_insert(0) += 1;
+            }
+
+            self.docs.push(Bm25fDoc {
+                id,
+                metadata,
+                fields: field_data,
+                text: text_parts.join("\n"),
+            });
+
+            // Every field's avg_dl is over all docs, not just the ones that
+            // supplied it — a doc missing a field implicitly contributes a
+            // length-0 entry to that field's average, same as Bm25Index's
+            // single implicit field.
+            let doc_count = self.docs.len() as f64;
+            for stats in self.field_stats.values_mut() {
+                stats.avg_dl = stats.total_tokens as f64 / doc_count;
+            }
+        }
+
+        /// BM25F ranking: length-normalizes each field's term frequency by
+        /// that field's own `avg_dl`/`b`, sums the normalized frequencies
+        /// across fields weighted by each field's `boost` into one
+        /// `tf_tilde` per term, then applies the single global `k1`
+        /// saturation and IDF — see the section-12 header comment for why
+        /// this differs from scoring each field with a separate
+        /// `Bm25Index` and summing.
+        pub fn rank(&self, query: &str, n: usize) -> Vec<SearchResult> {
+            let query_tokens = InMemoryVectorStore::tokenize(query);
+            let n_docs = self.docs.len() as f64;
+            if n_docs == 0.0 {
+                return vec![];
+            }
+
+            let mut scored = Vec::with_capacity(self.docs.len());
+            for doc in &self.docs {
+                let mut score = 0.0;
+                for term in &query_tokens {
+                    let df = *self.doc_freq.get(term).unwrap_or(&0) as f64;
+                    if df == 0.0 {
+                        continue;
+                    }
+                    let idf = ((n_docs - df + 0.5) / (df + 0.5) + 1.0).ln();
+
+                    let mut tf_tilde = 0.0;
+                    for (field, (term_freq, len)) in &doc.fields {
+                        let tf = *term_freq.get(term).unwrap_or(&0) as f64;
+                        if tf == 0.0 {
+                            continue;
+                        }
+                        let weight = self.field_weight(field);
+                        let avg_dl = self.field_stats.get(field).map(|s| s.avg_dl).unwrap_or(0.0).max(1.0);
+                        let norm_tf = tf / (1.0 - weight.b + weight.b * (*len as f64) / avg_dl);
+                        tf_tilde += weight.boost * norm_tf;
+                    }
+                    if tf_tilde <= 0.0 {
+                        continue;
+                    }
+                    score += idf * (tf_tilde * (BM25F_K1 + 1.0) / (BM25F_K1 + tf_tilde));
+                }
+                if score <= 0.0 {
+                    continue;
+                }
+                scored.push(SearchResult {
+                    id: doc.id.clone(),
+                    text: doc.text.clone(),
+                    score,
+                    metadata: doc.metadata.clone(),
+                    snippet: None,
+                    highlighted: None,
+                });
+            }
+
+            scored.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+            scored.truncate(n);
+            scored
+        }
+
+        pub fn len(&self) -> usize {
+            self.docs.len()
+        }
+    }
+
+    // ── 13. METADATA PREDICATES (faceted filtering over SearchResults) ──────
+    //
+    // Mirrors `query_parser::Filter`'s field:value model, but as an
+    // in-memory predicate tree applied to already-ranked `SearchResult`s
+    // rather than compiled to a SQL `WHERE` clause — lets a caller scope a
+    // `Bm25Index`/`InMemoryVectorStore`/`Bm25fIndex` ranking to, say,
+    // `industries = "ai-ml" AND size_signal != "startup"` without
+    // round-tripping through D1. Paired with `facets`, which turns the
+    // same filtered set into the value-count distributions a faceted-search
+    // UI needs (e.g. how many hits fall under each `tech_signals` value).
+
+    /// Split a metadata value on the `", "` convention this module already
+    /// uses for multi-valued fields (see `corrections.join(", ")` in
+    /// `Bm25Index::rank`/`InMemoryVectorStore::top_n`) into its individual
+    /// facet values. A single-valued field (e.g. `size_signal`) is just a
+    /// one-element list under this scheme, so the same helper backs both
+    /// `MetadataPredicate::Eq` and `facets`.
+    fn metadata_values<'a>(metadata: &'a HashMap<String, String>, key: &str) -> Vec<&'a str> {
+        metadata.get(key)
+            .map(|v| v.split(", ").filter(|s| !s.is_empty()).collect())
+            .unwrap_or_default()
+    }
+
+    fn metadata_numeric(metadata: &HashMap<String, String>, key: &str, cmp: impl Fn(f64) -> bool) -> bool {
+        metadata.get(key).and_then(|v| v.parse::<f64>().ok()).map(cmp).unwrap_or(false)
+    }
+
+    /// A predicate over `SearchResult::metadata`, composable via
+    /// `And`/`Or`/`Not`. `Eq`/`Ne`/`In` test set membership against a
+    /// `metadata_values`-split field (exact string equality for a
+    /// single-valued field, "is one of the comma-split values" for a
+    /// multi-valued one like `industries`); `Gt`/`Gte`/`Lt`/`Lte` parse the
+    /// raw (unsplit) value as `f64` and compare numerically, failing the
+    /// predicate if the key is missing or not numeric.
+    #[derive(Clone, Debug)]
+    pub enum MetadataPredicate {
+        Eq(String, String),
+        Ne(String, String),
+        In(String, Vec<String>),
+        Gt(String, f64),
+        Gte(String, f64),
+        Lt(String, f64),
+        Lte(String, f64),
+        And(Vec<MetadataPredicate>),
+        Or(Vec<MetadataPredicate>),
+        Not(Box<MetadataPredicate>),
+    }
+
+    impl MetadataPredicate {
+        pub fn matches(&self, metadata: &HashMap<String, String>) -> bool {
+            match self {
+                MetadataPredicate::Eq(key, value) => metadata_values(metadata, key).contains(&value.as_str()),
+                MetadataPredicate::Ne(key, value) => !metadata_values(metadata, key).contains(&value.as_str()),
+                MetadataPredicate::In(key, values) => {
+                    let have = metadata_values(metadata, key);
+                    values.iter().any(|v| have.contains(&v.as_str()))
+                }
+                MetadataPredicate::Gt(key, n) => metadata_numeric(metadata, key, |x| x > *n),
+                MetadataPredicate::Gte(key, n) => metadata_numeric(metadata, key, |x| x >= *n),
+                MetadataPredicate::Lt(key, n) => metadata_numeric(metadata, key, |x| x < *n),
+                MetadataPredicate::Lte(key, n) => metadata_numeric(metadata, key, |x| x <= *n),
+                MetadataPredicate::And(preds) => preds.iter().all(|p| p.matches(metadata)),
+                MetadataPredicate::Or(preds) => preds.iter().any(|p| p.matches(metadata)),
+                MetadataPredicate::Not(p) => !p.matches(metadata),
+            }
+        }
+    }
+
+    /// Keep only results whose `metadata` satisfies `predicate` — applied
+    /// after ranking (cheap relative to the scoring pass), so a caller asks
+    /// the index for its full corpus ranking, filters, then truncates to
+    /// the page size, rather than trying to push the predicate into the
+    /// scoring loop itself.
+    pub fn filter_results(results: Vec<SearchResult>, predicate: &MetadataPredicate) -> Vec<SearchResult> {
+        results.into_iter().filter(|r| predicate.matches(&r.metadata)).collect()
+    }
+
+    /// Value-count distribution of `metadata[key]` across `results`, for
+    /// each `key` in `keys`, computed in one pass over `results` —
+    /// e.g. `facets(&results, &["tech_signals", "size_signal"])` to drive a
+    /// faceted-search sidebar. A multi-valued field contributes once per
+    /// `metadata_values`-split value; a result missing a key contributes
+    /// nothing to that key's distribution.
+    pub fn facets(results: &[SearchResult], keys: &[&str]) -> HashMap<String, HashMap<String, usize>> {
+        let mut out: HashMap<String, HashMap<String, usize>> = HashMap::new();
+        for result in results {
+            for key in keys {
+                for value in metadata_values(&result.metadata, key) {
+                    *out.entry((*key).to_string()).or_default().entry(value.to_string()).or_default() += 1;
+                }
+            }
+        }
+        out
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn result(id: &str, metadata: &[(&str, &str)]) -> SearchResult {
+            SearchResult {
+                id: id.to_string(),
+                text: String::new(),
+                score: 0.0,
+                metadata: metadata.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+                snippet: None,
+                highlighted: None,
+            }
+        }
+
+        #[test]
+        fn facets_counts_each_value_once_per_result() {
+            let results = vec![
+                result("a", &[("size_signal", "startup")]),
+                result("b", &[("size_signal", "enterprise")]),
+                result("c", &[("size_signal", "startup")]),
+            ];
+            let out = facets(&results, &["size_signal"]);
+            let sizes = &out["size_signal"];
+            assert_eq!(sizes["startup"], 2);
+            assert_eq!(sizes["enterprise"], 1);
+        }
+
+        #[test]
+        fn facets_splits_multi_valued_fields() {
+            let results = vec![result("a", &[("tech_signals", "rust, go")])];
+            let out = facets(&results, &["tech_signals"]);
+            assert_eq!(out["tech_signals"]["rust"], 1);
+            assert_eq!(out["tech_signals"]["go"], 1);
+        }
+
+        #[test]
+        fn facets_skips_results_missing_the_key() {
+            let results = vec![result("a", &[])];
+            let out = facets(&results, &["size_signal"]);
+            assert!(out.get("size_signal").is_none());
+        }
+
+        #[test]
+        fn metadata_predicate_and_requires_every_clause() {
+            let metadata: HashMap<String, String> =
+                [("size_signal".to_string(), "enterprise".to_string())].into_iter().collect();
+            let pred = MetadataPredicate::And(vec![
+                MetadataPredicate::Eq("size_signal".into(), "enterprise".into()),
+                MetadataPredicate::Ne("size_signal".into(), "startup".into()),
+            ]);
+            assert!(pred.matches(&metadata));
+        }
+
+        #[test]
+        fn metadata_predicate_in_matches_any_listed_value() {
+            let metadata: HashMap<String, String> =
+                [("tech_signals".to_string(), "rust, go".to_string())].into_iter().collect();
+            let pred = MetadataPredicate::In("tech_signals".into(), vec!["python".into(), "go".into()]);
+            assert!(pred.matches(&metadata));
+        }
+
+        #[test]
+        fn metadata_predicate_numeric_comparison_fails_on_missing_key() {
+            let metadata: HashMap<String, String> = HashMap::new();
+            assert!(!MetadataPredicate::Gt("headcount".into(), 10.0).matches(&metadata));
+        }
+
+        #[test]
+        fn levenshtein_identical_strings_is_zero() {
+            assert_eq!(levenshtein("kubernetes", "kubernetes"), 0);
+        }
+
+        #[test]
+        fn levenshtein_single_substitution() {
+            assert_eq!(levenshtein("postgres", "postgrex"), 1);
+        }
+
+        #[test]
+        fn levenshtein_adjacent_transposition_costs_one() {
+            // Optimal-string-alignment variant: a transposed pair is 1 edit,
+            // not the 2 plain Levenshtein would charge.
+            assert_eq!(levenshtein("kubenretes", "kubernetes"), 1);
+        }
+
+        #[test]
+        fn typo_distance_budget_bands_by_length() {
+            assert_eq!(typo_distance_budget(3, None), 0);
+            assert_eq!(typo_distance_budget(5, None), 1);
+            assert_eq!(typo_distance_budget(12, None), 2);
+        }
+
+        #[test]
+        fn typo_distance_budget_capped_by_max_typos() {
+            assert_eq!(typo_distance_budget(12, Some(0)), 0);
+            assert_eq!(typo_distance_budget(12, Some(1)), 1);
+            // A cap higher than the band's own budget doesn't raise it.
+            assert_eq!(typo_distance_budget(5, Some(5)), 1);
+        }
+
+        #[test]
+        fn fuzzy_variants_finds_one_edit_match() {
+            let buckets = bucket_vocab(["kubernetes".to_string(), "postgres".to_string()].iter());
+            let variants = fuzzy_variants("kubernets", &buckets, None);
+            assert!(variants.iter().any(|(term, dist)| term == "kubernetes" && *dist == 1));
+        }
+
+        #[test]
+        fn fuzzy_variants_respects_zero_budget() {
+            let buckets = bucket_vocab(["kubernetes".to_string()].iter());
+            assert!(fuzzy_variants("kubernets", &buckets, Some(0)).is_empty());
+        }
+
+        #[test]
+        fn prefix_variants_completes_in_progress_word() {
+            let buckets = bucket_vocab(["stripe".to_string(), "strict".to_string()].iter());
+            let variants = prefix_variants("strip", &buckets);
+            assert_eq!(variants, vec!["stripe".to_string()]);
+        }
+
+        #[test]
+        fn hybrid_search_rrf_fuses_both_rankers() {
+            let mut index = HybridIndex::new();
+            index.add_document("a".into(), "rust backend engineer".into(), HashMap::new());
+            index.add_document("b".into(), "frontend designer".into(), HashMap::new());
+            index.rebuild_index();
+
+            let results = index.search("rust", 10);
+            assert!(!results.is_empty());
+            assert_eq!(results[0].id, "a");
+        }
+
+        #[test]
+        fn bm25_rank_with_max_typos_zero_disables_fuzzy_match() {
+            let mut index = Bm25Index::new();
+            index.add_document("a".into(), "kubernetes cluster autoscaling".into(), HashMap::new());
+            index.rebuild_index();
+
+            assert!(!index.rank_with_max_typos("kubernets", 10, None).is_empty());
+            assert!(index.rank_with_max_typos("kubernets", 10, Some(0)).is_empty());
+        }
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// CORE TYPES
+// ═══════════════════════════════════════════════════════════════════════════
+
+#[derive(Deserialize, Debug, Clone)]
+struct CdxRecord {
+    url: String,
+    timestamp: String,
+    #[serde(default)]
+    status: Option<String>,
+    #[serde(default)]
+    mime: Option<String>,
+    #[serde(default, rename = "mime-detected")]
+    mime_detected: Option<String>,
+    #[serde(default)]
+    filename: Option<String>,
+    #[serde(default)]
+    offset: Option<String>,
+    #[serde(default)]
     length: Option<String>,
 }
 
@@ -612,14 +2165,14 @@ struct AshbyBoard {
 
 // ── Ashby Posting API types ──────────────────────────────────────────────────
 
-#[derive(Deserialize, Serialize, Clone)]
+#[derive(Deserialize, Serialize, Clone, Debug)]
 #[serde(rename_all = "camelCase")]
 struct AshbyApiAddress {
     #[serde(default)]
     postal_address: Option<serde_json::Value>,
 }
 
-#[derive(Deserialize, Serialize, Clone)]
+#[derive(Deserialize, Serialize, Clone, Debug)]
 #[serde(rename_all = "camelCase")]
 struct AshbyApiSecondaryLocation {
     #[serde(default)]
@@ -628,7 +2181,7 @@ struct AshbyApiSecondaryLocation {
     address: Option<AshbyApiAddress>,
 }
 
-#[derive(Deserialize, Clone)]
+#[derive(Deserialize, Serialize, Clone, Debug)]
 #[serde(rename_all = "camelCase")]
 struct AshbyJobPosting {
     id: String,
@@ -674,6 +2227,42 @@ struct AshbyJobBoardResponse {
     jobs: Vec<AshbyJobPosting>,
 }
 
+// Ashby intentionally does not implement `ats::AtsSource`. Its hand-written
+// `upsert_jobs_to_d1` below carries a content-hash change-detection pass
+// (skip rewriting a posting whose title/description/location/
+// workplace_type/compensation/listed status hasn't changed since the last
+// sync) and a content-addressed `job_bodies` table for description dedup,
+// neither of which `ats::upsert_jobs_to_d1` implements. Folding those into
+// the shared pipeline would change write behavior for Lever/Greenhouse/
+// Workable too, so Ashby keeps its own optimized path rather than being
+// forced onto the generic one just to have a third `AtsSource` impl.
+
+/// Stable, machine-readable failure category — same taxonomy pattern as
+/// pict-rs/Garage's `ErrorCode`: a client (or the `define_tools` Rig agent)
+/// can branch on `code` instead of pattern-matching `error`'s free text,
+/// e.g. retrying `RateLimited`/`UpstreamUnavailable` but not `InvalidInput`.
+/// Serializes to the kebab-case form of each variant's name (`rename_all`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+enum ErrorCode {
+    /// A required request parameter was missing, empty, or malformed.
+    InvalidInput,
+    /// The requested board/task/crawl/tool doesn't exist.
+    NotFound,
+    /// An upstream HTTP dependency (Ashby, Greenhouse, Common Crawl, …) is
+    /// down, timed out, or returned an unexpected/unparseable response.
+    UpstreamUnavailable,
+    /// An upstream HTTP dependency returned 429 — distinct from
+    /// `UpstreamUnavailable` since a caller should back off and retry here,
+    /// whereas other upstream failures may not be worth retrying at all.
+    RateLimited,
+    /// Deserializing or interpreting a document (CDX line, job posting,
+    /// pipeline step input) failed.
+    ParseError,
+    /// A D1 read, write, or migration failed.
+    DatabaseError,
+}
+
 #[derive(Serialize)]
 struct ApiResponse<T: Serialize> {
     ok: bool,
@@ -681,22 +2270,309 @@ struct ApiResponse<T: Serialize> {
     data: Option<T>,
     #[serde(skip_serializing_if = "Option::is_none")]
     error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    code: Option<ErrorCode>,
 }
 
 impl<T: Serialize> ApiResponse<T> {
     fn success(data: T) -> Self {
-        Self { ok: true, data: Some(data), error: None }
+        Self { ok: true, data: Some(data), error: None, code: None }
     }
 }
 
-fn error_response(msg: &str) -> Result<Response> {
+fn error_response(code: ErrorCode, msg: &str) -> Result<Response> {
     Response::from_json(&ApiResponse::<()> {
         ok: false,
         data: None,
         error: Some(msg.to_string()),
+        code: Some(code),
     })
 }
 
+/// Best-effort `ErrorCode` for a `worker::Error` that reached an HTTP
+/// handler through several layers of `?` — too far from where it actually
+/// originated to attach a code at the source, so this pattern-matches the
+/// message `fetch_with_retry`/D1 calls leave behind. Defaults to
+/// `DatabaseError` since most of what's left after ruling out HTTP/parse
+/// failures is a D1 read or write.
+fn classify_error(e: &Error) -> ErrorCode {
+    let msg = format!("{e:?}").to_lowercase();
+    if msg.contains("last status 429") {
+        ErrorCode::RateLimited
+    } else if msg.contains("request failed after") || msg.contains("api returned") {
+        ErrorCode::UpstreamUnavailable
+    } else if msg.contains("parse error") {
+        ErrorCode::ParseError
+    } else {
+        ErrorCode::DatabaseError
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// RESILIENT FETCH — retry with backoff + jitter
+// ═══════════════════════════════════════════════════════════════════════════
+//
+// The Common Crawl index servers and the Ashby API both rate-limit under
+// load; without this, one 429 mid-crawl aborts the whole job. `fetch_with_retry`
+// centralizes the retry policy so every call site gets the same behavior.
+
+const RETRY_MAX_ATTEMPTS: u32 = 5;
+const RETRY_BASE_DELAY_MS: u64 = 500;
+const RETRY_MAX_DELAY_MS: u64 = 30_000;
+
+/// Number of CDX pages in one `run_crawl_batch` run that, once they have
+/// exhausted `fetch_with_retry`, abort the whole batch; fewer retry-exhausted
+/// pages than this are tolerated. Isolated hiccups on a
+/// single CC node shouldn't kill a run that's otherwise making progress.
+const PAGE_ERROR_BUDGET: usize = 3;
+
+fn is_retryable_status(status: u16) -> bool {
+    matches!(status, 429 | 500 | 502 | 503 | 504)
+}
+
+/// Equal-jitter exponential backoff: half the capped exponential delay, plus
+/// a random amount up to the other half, so concurrent callers don't all
+/// retry in lockstep.
+fn backoff_delay_ms(attempt: u32) -> u64 {
+    let exp = RETRY_BASE_DELAY_MS
+        .saturating_mul(1u64 << attempt.min(16))
+        .min(RETRY_MAX_DELAY_MS);
+    let jitter = (js_sys::Math::random() * exp as f64) as u64;
+    exp / 2 + jitter / 2
+}
+
+/// Run `make_request` (a closure, since a sent `Request` can't be reused) and
+/// retry on 429/500/502/503/504 or transport errors, honoring `Retry-After`
+/// (seconds form) when the server sends one. 404 and other non-retryable
+/// statuses are returned as-is — 404 in particular is a terminal,
+/// non-retryable result so "404 means empty board" keeps working. Once
+/// `max_attempts` is exhausted on a retryable status or a transport error,
+/// returns `Err` with the attempt count baked into the message instead of
+/// silently handing back the last bad response. Most call sites pass the
+/// shared `RETRY_MAX_ATTEMPTS` default; `fetch_cdx_page` takes its own value
+/// so `/crawl?max_retries=` can tune it per run.
+async fn fetch_with_retry<F>(mut make_request: F, max_attempts: u32) -> Result<Response>
+where
+    F: FnMut() -> Result<Request>,
+{
+    let max_attempts = max_attempts.max(1);
+    let mut attempt = 0u32;
+    loop {
+        let result = Fetch::Request(make_request()?).send().await;
+        match result {
+            Ok(resp) => {
+                let status = resp.status_code();
+                if status == 404 || !is_retryable_status(status) {
+                    return Ok(resp);
+                }
+                attempt += 1;
+                if attempt >= max_attempts {
+                    console_log!("[retry] giving up after {} attempts, last status={}", attempt, status);
+                    return Err(Error::RustError(format!(
+                        "request failed after {attempt} attempts: last status {status}"
+                    )));
+                }
+                let delay_ms = resp.headers().get("retry-after").ok().flatten()
+                    .and_then(|v| v.parse::<u64>().ok())
+                    .map(|secs| secs * 1000)
+                    .unwrap_or_else(|| backoff_delay_ms(attempt));
+                console_log!("[retry] status={} attempt={} retrying in {}ms", status, attempt, delay_ms);
+                Delay::from(std::time::Duration::from_millis(delay_ms)).await;
+            }
+            Err(e) => {
+                attempt += 1;
+                if attempt >= max_attempts {
+                    return Err(Error::RustError(format!(
+                        "request failed after {attempt} attempts: {e:?}"
+                    )));
+                }
+                let delay_ms = backoff_delay_ms(attempt);
+                console_log!("[retry] transport error={:?} attempt={} retrying in {}ms", e, attempt, delay_ms);
+                Delay::from(std::time::Duration::from_millis(delay_ms)).await;
+            }
+        }
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// RESILIENT D1 BATCH — per-statement error isolation + retry
+// ═══════════════════════════════════════════════════════════════════════════
+//
+// A D1 `batch()` call is all-or-nothing: one malformed statement (or a
+// transient error) fails the whole chunk, so `let _ = db.batch(chunk).await`
+// can silently drop up to `BATCH_SIZE` good rows alongside the one bad one.
+// `run_batch_resilient` retries a failing chunk a few times, and if it still
+// won't commit, bisects it in half and retries each half — down to single
+// statements — so only the actually-offending statement(s) end up reported
+// as failed instead of the whole chunk.
+
+const D1_BATCH_RETRY_MAX_ATTEMPTS: u32 = 3;
+
+/// Outcome of a resilient batch run: how many statements committed, and a
+/// `(label, reason)` per statement that didn't, after retry + bisection gave
+/// up on it. `label` is caller-chosen (e.g. a job's `external_id`) so a
+/// caller can report exactly which rows were lost instead of a bare count.
+#[derive(Debug, Default)]
+struct BatchOutcome {
+    committed: usize,
+    failed: Vec<(String, String)>,
+}
+
+impl BatchOutcome {
+    fn merge(&mut self, other: BatchOutcome) {
+        self.committed += other.committed;
+        self.failed.extend(other.failed);
+    }
+}
+
+/// Run one D1 statement with bounded retry, sending a fresh clone of it on
+/// each attempt (a sent statement can't be reused, same constraint as
+/// `fetch_with_retry`'s `Request`).
+async fn run_single_with_retry(stmt: &D1PreparedStatement) -> std::result::Result<(), String> {
+    let mut attempt = 0u32;
+    loop {
+        match stmt.clone().run().await {
+            Ok(_) => return Ok(()),
+            Err(e) => {
+                attempt += 1;
+                if attempt >= D1_BATCH_RETRY_MAX_ATTEMPTS {
+                    return Err(format!("{e:?}"));
+                }
+                let delay_ms = backoff_delay_ms(attempt);
+                console_log!("[batch] single-statement retry attempt={} in {}ms: {:?}", attempt, delay_ms, e);
+                Delay::from(std::time::Duration::from_millis(delay_ms)).await;
+            }
+        }
+    }
+}
+
+/// Run a chunk of statements as one D1 `batch()` with bounded retry.
+async fn run_chunk_with_retry(db: &D1Database, stmts: &[D1PreparedStatement]) -> std::result::Result<(), String> {
+    let mut attempt = 0u32;
+    loop {
+        match db.batch(stmts.to_vec()).await {
+            Ok(_) => return Ok(()),
+            Err(e) => {
+                attempt += 1;
+                if attempt >= D1_BATCH_RETRY_MAX_ATTEMPTS {
+                    return Err(format!("{e:?}"));
+                }
+                let delay_ms = backoff_delay_ms(attempt);
+                console_log!("[batch] chunk-of-{} retry attempt={} in {}ms: {:?}", stmts.len(), attempt, delay_ms, e);
+                Delay::from(std::time::Duration::from_millis(delay_ms)).await;
+            }
+        }
+    }
+}
+
+/// Run `items` (each a caller-labeled statement) through D1, isolating
+/// failures: a chunk that won't commit after retry is split in half and each
+/// half is tried independently, recursing down to single statements, so one
+/// bad row never takes the rest of the chunk down with it.
+async fn run_batch_resilient(db: &D1Database, items: Vec<(String, D1PreparedStatement)>) -> BatchOutcome {
+    let mut outcome = BatchOutcome::default();
+    let mut groups = vec![items];
+
+    while let Some(mut group) = groups.pop() {
+        if group.is_empty() {
+            continue;
+        }
+        if group.len() == 1 {
+            let (label, stmt) = group.pop().unwrap();
+            match run_single_with_retry(&stmt).await {
+                Ok(()) => outcome.committed += 1,
+                Err(reason) => outcome.failed.push((label, reason)),
+            }
+            continue;
+        }
+
+        let stmts: Vec<D1PreparedStatement> = group.iter().map(|(_, s)| s.clone()).collect();
+        match run_chunk_with_retry(db, &stmts).await {
+            Ok(()) => outcome.committed += group.len(),
+            Err(reason) => {
+                console_log!("[batch] chunk of {} still failing after retry ({}), bisecting", group.len(), reason);
+                let half = group.split_off(group.len() / 2);
+                groups.push(group);
+                groups.push(half);
+            }
+        }
+    }
+
+    outcome
+}
+
+/// Default ceilings for [`pack_batches`]: a bind-parameter count per batch and
+/// an estimated serialized-byte budget per batch. Both stay comfortably under
+/// D1's request-size limits even when every statement in a batch is
+/// maximally large — they're tunables rather than baked into `pack_batches`
+/// itself so a caller with a different statement shape can pick its own.
+const D1_BATCH_MAX_BINDS: usize = 900;
+const D1_BATCH_MAX_BYTES: usize = 900_000;
+
+/// Greedily pack `items` into batches bounded by both a bind-parameter count
+/// and an estimated payload-byte budget, instead of a fixed statement count —
+/// a board with huge HTML job descriptions needs far fewer statements per
+/// batch than one with short plaintext postings to stay under the same
+/// request-size ceiling. `bind_count`/`byte_estimate` are supplied per item by
+/// the caller (computed from the actual lengths of what it bound) rather than
+/// inferred here, since a `D1PreparedStatement` doesn't expose its bound
+/// values once built. A batch always gets at least one item — a single
+/// oversized statement exceeds both ceilings on its own, but still runs in
+/// its own one-item batch rather than being dropped or stalling forever.
+fn pack_batches<T>(items: Vec<(T, usize, usize)>, max_binds: usize, max_bytes: usize) -> Vec<Vec<T>> {
+    let mut batches = Vec::new();
+    let mut current = Vec::new();
+    let mut binds_used = 0usize;
+    let mut bytes_used = 0usize;
+
+    for (item, binds, bytes) in items {
+        if !current.is_empty() && (binds_used + binds > max_binds || bytes_used + bytes > max_bytes) {
+            batches.push(std::mem::take(&mut current));
+            binds_used = 0;
+            bytes_used = 0;
+        }
+        current.push(item);
+        binds_used += binds;
+        bytes_used += bytes;
+    }
+    if !current.is_empty() {
+        batches.push(current);
+    }
+    batches
+}
+
+#[cfg(test)]
+mod pack_batches_tests {
+    use super::*;
+
+    #[test]
+    fn single_item_always_gets_its_own_batch_even_over_budget() {
+        let items = vec![("only", 10_000, 10_000_000)];
+        let batches = pack_batches(items, 900, 900_000);
+        assert_eq!(batches, vec![vec!["only"]]);
+    }
+
+    #[test]
+    fn packs_items_until_bind_budget_is_exceeded() {
+        let items = vec![("a", 400, 10), ("b", 400, 10), ("c", 400, 10)];
+        let batches = pack_batches(items, 900, 900_000);
+        assert_eq!(batches, vec![vec!["a", "b"], vec!["c"]]);
+    }
+
+    #[test]
+    fn packs_items_until_byte_budget_is_exceeded() {
+        let items = vec![("a", 1, 500_000), ("b", 1, 500_000), ("c", 1, 100)];
+        let batches = pack_batches(items, 900, 900_000);
+        assert_eq!(batches, vec![vec!["a"], vec!["b", "c"]]);
+    }
+
+    #[test]
+    fn empty_input_yields_no_batches() {
+        let items: Vec<(&str, usize, usize)> = Vec::new();
+        assert!(pack_batches(items, 900, 900_000).is_empty());
+    }
+}
+
 // ═══════════════════════════════════════════════════════════════════════════
 // COMMON CRAWL HELPERS
 // ═══════════════════════════════════════════════════════════════════════════
@@ -718,8 +2594,9 @@ fn extract_slug(url: &str) -> Option<String> {
 }
 
 async fn list_cc_indexes() -> Result<Vec<String>> {
-    let req = Request::new("https://index.commoncrawl.org/collinfo.json", Method::Get)?;
-    let mut resp = Fetch::Request(req).send().await?;
+    let mut resp = fetch_with_retry(|| {
+        Request::new("https://index.commoncrawl.org/collinfo.json", Method::Get)
+    }, RETRY_MAX_ATTEMPTS).await?;
     let text = resp.text().await?;
     #[derive(Deserialize)]
     struct C { id: String }
@@ -733,7 +2610,7 @@ async fn get_num_pages(crawl_id: &str) -> Result<u32> {
         "https://index.commoncrawl.org/{crawl_id}-index?\
          url=jobs.ashbyhq.com%2F*&output=json&showNumPages=true"
     );
-    let mut resp = Fetch::Request(Request::new(&url, Method::Get)?).send().await?;
+    let mut resp = fetch_with_retry(|| Request::new(&url, Method::Get), RETRY_MAX_ATTEMPTS).await?;
     let text = resp.text().await?;
     #[derive(Deserialize)]
     struct P { pages: u32 }
@@ -742,33 +2619,30 @@ async fn get_num_pages(crawl_id: &str) -> Result<u32> {
     Ok(info.pages)
 }
 
-async fn fetch_cdx_page(crawl_id: &str, page: u32) -> Result<Vec<AshbyBoard>> {
+async fn fetch_cdx_page(db: &D1Database, crawl_id: &str, page: u32, max_retries: u32) -> Result<(Vec<AshbyBoard>, u32)> {
     let url = format!(
         "https://index.commoncrawl.org/{crawl_id}-index?\
          url=jobs.ashbyhq.com%2F*&output=json&filter=statuscode:200&pageSize=100&page={page}"
     );
-    let mut resp = Fetch::Request(Request::new(&url, Method::Get)?).send().await?;
+    let mut resp = fetch_with_retry(|| Request::new(&url, Method::Get), max_retries).await?;
     let status = resp.status_code();
     let text = resp.text().await?;
     console_log!("[cdx] page {} status={} body_len={} first_100={}", page, status, text.len(), &text[..text.len().min(100)]);
 
     let mut parse_errors = 0u32;
-    let records: Vec<CdxRecord> = text
-        .lines()
-        .filter(|l| !l.trim().is_empty())
-        .filter_map(|l| {
-            match serde_json::from_str::<CdxRecord>(l) {
-                Ok(r) => Some(r),
-                Err(e) => {
-                    if parse_errors < 3 {
-                        console_log!("[cdx] parse error: {} on line: {}", e, &l[..l.len().min(200)]);
-                    }
-                    parse_errors += 1;
-                    None
+    let mut records: Vec<CdxRecord> = Vec::new();
+    for line in text.lines().filter(|l| !l.trim().is_empty()) {
+        match serde_json::from_str::<CdxRecord>(line) {
+            Ok(r) => records.push(r),
+            Err(e) => {
+                if parse_errors < 3 {
+                    console_log!("[cdx] parse error: {} on line: {}", e, &line[..line.len().min(200)]);
                 }
+                parse_errors += 1;
+                record_invalid(db, "cdx", crawl_id, &line[..line.len().min(2000)], &e.to_string()).await;
             }
-        })
-        .collect();
+        }
+    }
 
     console_log!("[cdx] parsed {} records, {} errors from {} lines", records.len(), parse_errors, text.lines().count());
 
@@ -795,7 +2669,7 @@ async fn fetch_cdx_page(crawl_id: &str, page: u32) -> Result<Vec<AshbyBoard>> {
                 .or_insert(board);
         }
     }
-    Ok(map.into_values().collect())
+    Ok((map.into_values().collect(), parse_errors))
 }
 
 // ── Ashby Posting API fetch ───────────────────────────────────────────────
@@ -807,7 +2681,7 @@ async fn fetch_ashby_board_jobs(slug: &str) -> Result<AshbyJobBoardResponse> {
         "https://api.ashbyhq.com/posting-api/job-board/{}?includeCompensation=true",
         slug
     );
-    let mut resp = Fetch::Request(Request::new(&url, Method::Get)?).send().await?;
+    let mut resp = fetch_with_retry(|| Request::new(&url, Method::Get), RETRY_MAX_ATTEMPTS).await?;
     let status = resp.status_code();
     if status == 404 {
         console_log!("[job-sync] board '{}' returned 404 — skipping", slug);
@@ -823,90 +2697,313 @@ async fn fetch_ashby_board_jobs(slug: &str) -> Result<AshbyJobBoardResponse> {
         .map_err(|e| Error::RustError(format!("ashby board parse error for '{}': {}", slug, e)))
 }
 
+
 // ═══════════════════════════════════════════════════════════════════════════
-// MIGRATIONS — applied automatically on first request after deploy
+// D1 POLL TIMING — warn when a batch/run stalls
 // ═══════════════════════════════════════════════════════════════════════════
+//
+// A `db.batch()`/`.run()` round-trip gives no visibility into how long it
+// took — a 100-row `BATCH_SIZE` chunk silently stalling against D1 looks
+// identical to a fast one until someone times it by hand. `with_poll_timer`
+// wraps an awaited D1 future, stamps either side with `js_sys::Date::now()`
+// (same wall-clock primitive `record_sync_run`'s `duration_ms` uses), and
+// emits a `console_warn!` tagged with the step name, row count, and elapsed
+// ms if it ran past `D1_SLOW_QUERY_THRESHOLD_MS` — enough to tell operators
+// which operation/batch size needs tuning.
+
+const D1_SLOW_QUERY_THRESHOLD_MS: f64 = 500.0;
+
+trait WithPollTimer: std::future::Future + Sized {
+    /// Await `self`, logging a `console_warn!` if it took longer than
+    /// `D1_SLOW_QUERY_THRESHOLD_MS`. `step` names the operation and `rows`
+    /// is the batch size being awaited, so a slow poll can be tied back to
+    /// both which query it was and how large a chunk it was carrying.
+    async fn with_poll_timer(self, step: &'static str, rows: usize) -> Self::Output {
+        let started_at = js_sys::Date::now();
+        let out = self.await;
+        let elapsed_ms = js_sys::Date::now() - started_at;
+        if elapsed_ms > D1_SLOW_QUERY_THRESHOLD_MS {
+            console_warn!("[d1] slow '{}' took {:.0}ms for {} row(s)", step, elapsed_ms, rows);
+        }
+        out
+    }
+}
 
-/// Ordered list of migrations. Each entry is (name, sql).
-/// D1 does not support multi-statement batches in `prepare`, so statements
-/// within a migration are split on `;` and executed individually.
-/// ALTER TABLE errors (column already exists) are ignored so re-runs are safe.
-const MIGRATIONS: &[(&str, &str)] = &[
-    ("0002_enrichment", "
-        ALTER TABLE ashby_boards ADD COLUMN company_name  TEXT;
-        ALTER TABLE ashby_boards ADD COLUMN industry_tags TEXT;
-        ALTER TABLE ashby_boards ADD COLUMN tech_signals  TEXT;
-        ALTER TABLE ashby_boards ADD COLUMN enriched_at   TEXT;
-        CREATE INDEX IF NOT EXISTS idx_boards_company  ON ashby_boards(company_name);
-        CREATE INDEX IF NOT EXISTS idx_boards_industry ON ashby_boards(industry_tags);
-    "),
-    ("0005_companies_ashby_enrichment", "
-        ALTER TABLE companies ADD COLUMN ashby_industry_tags TEXT;
-        ALTER TABLE companies ADD COLUMN ashby_tech_signals  TEXT;
-        ALTER TABLE companies ADD COLUMN ashby_size_signal   TEXT;
-        ALTER TABLE companies ADD COLUMN ashby_enriched_at   TEXT;
-    "),
-    ("0003_jobs_external_id_unique", "
-        CREATE UNIQUE INDEX IF NOT EXISTS idx_jobs_external_id ON jobs(external_id);
-    "),
-    ("0004_ashby_boards_sync", "
-        ALTER TABLE ashby_boards ADD COLUMN last_synced_at TEXT;
-        ALTER TABLE ashby_boards ADD COLUMN job_count      INTEGER;
-        ALTER TABLE ashby_boards ADD COLUMN is_active      INTEGER DEFAULT 1;
-    "),
-    ("0006_dedup_and_unique_external_id", "
-        DELETE FROM jobs WHERE id NOT IN (SELECT MIN(id) FROM jobs GROUP BY external_id);
-        CREATE UNIQUE INDEX IF NOT EXISTS idx_jobs_external_id ON jobs(external_id);
-    "),
-];
-
-async fn apply_pending_migrations(db: &D1Database) -> Result<()> {
-    // Ensure the migrations tracking table exists
-    db.prepare(
-        "CREATE TABLE IF NOT EXISTS _migrations (
-            name       TEXT PRIMARY KEY,
-            applied_at TEXT NOT NULL DEFAULT (datetime('now'))
-        )"
-    )
-    .bind(&[])?
-    .run()
-    .await?;
+impl<F: std::future::Future> WithPollTimer for F {}
 
-    for (name, sql) in MIGRATIONS {
-        let already_applied = db
-            .prepare("SELECT 1 FROM _migrations WHERE name=?1")
-            .bind(&[(*name).into()])?
-            .first::<serde_json::Value>(None)
-            .await?
-            .is_some();
+// ═══════════════════════════════════════════════════════════════════════════
+// PHASE TIMING — aggregate elapsed time per crawl phase, not just per poll
+// ═══════════════════════════════════════════════════════════════════════════
+//
+// `with_poll_timer` warns on an individual slow D1 round-trip, but gives a
+// caller no way to add that time up across a whole phase (all CDX pages in a
+// batch, every Ashby board fetch, …) to see where a crawl run actually spent
+// its CPU/subrequest budget. `timed` wraps any future — not just D1 ones —
+// and hands the elapsed ms back to the caller to aggregate, in addition to
+// still warning past a threshold. Its default threshold is far coarser than
+// `D1_SLOW_QUERY_THRESHOLD_MS` since it times whole subrequests (an Ashby
+// board fetch, a full CDX page fetch) rather than a single D1 statement.
+const SLOW_OP_THRESHOLD_MS: f64 = 2000.0;
+
+async fn timed<F: std::future::Future>(step: &'static str, fut: F) -> (F::Output, f64) {
+    let started_at = js_sys::Date::now();
+    let out = fut.await;
+    let elapsed_ms = js_sys::Date::now() - started_at;
+    if elapsed_ms > SLOW_OP_THRESHOLD_MS {
+        console_warn!("[timing] slow '{}' took {:.0}ms", step, elapsed_ms);
+    }
+    (out, elapsed_ms)
+}
 
-        if already_applied {
-            continue;
-        }
+// ═══════════════════════════════════════════════════════════════════════════
+// ADAPTIVE BATCH SIZING — scale chunk/page counts to the CPU budget, not a
+// hand-tuned constant
+// ═══════════════════════════════════════════════════════════════════════════
+//
+// `auto_enrich_boards`'s `BATCH_SIZE`, `PAGES_PER_CRON_RUN`, and
+// `BOARDS_PER_JOB_SYNC_RUN` were all fixed constants picked by hand — too
+// small and a run leaves CPU budget on the table every invocation, too large
+// and a slow batch risks tripping the Worker's 30s CPU-time ceiling. This
+// estimates a per-item cost from whatever's been measured so far (via
+// `timed`/manual `js_sys::Date::now()` deltas) and scales the next chunk/page
+// count to spend close to a budget instead.
+
+/// Lower/upper bounds so a wildly noisy first sample (a cold D1 connection,
+/// a single huge chunk) can't collapse the next chunk to 0 or blow it up
+/// past what a single batch should ever be.
+const ADAPTIVE_BATCH_MIN: usize = 10;
+const ADAPTIVE_BATCH_MAX: usize = 400;
+
+/// CPU time to spend on `auto_enrich_boards`'s D1-batch-commit loop — well
+/// under the Worker's 30s wall-clock ceiling, leaving room for the CDX/ATS
+/// fetches sharing the same cron invocation.
+const ADAPTIVE_ENRICH_BATCH_BUDGET_MS: f64 = 8_000.0;
+
+/// Bounds and budget for `cron_handler_inner`'s CDX page fan-out
+/// (`pages_per_run`, replacing the old fixed `PAGES_PER_CRON_RUN`).
+const ADAPTIVE_PAGES_MIN: usize = 2;
+const ADAPTIVE_PAGES_MAX: usize = 60;
+const ADAPTIVE_PAGE_BUDGET_MS: f64 = 10_000.0;
+
+/// Bounds and budget for `cron_handler_inner`'s job-sync board fan-out
+/// (`boards_per_run`, replacing the old fixed `BOARDS_PER_JOB_SYNC_RUN`).
+const ADAPTIVE_BOARDS_MIN: usize = 5;
+const ADAPTIVE_BOARDS_MAX: usize = 200;
+const ADAPTIVE_BOARDS_BUDGET_MS: f64 = 10_000.0;
+
+/// Given how many items have been processed so far and how long that took,
+/// estimates a per-item cost and returns the batch/page count that would
+/// spend close to `budget_ms` without overrunning it, clamped to
+/// `[min, max]`. Call again after each chunk with the *cumulative*
+/// `completed`/`elapsed_ms` so a noisy single sample gets smoothed out over
+/// the run instead of permanently mis-sizing every chunk after the first.
+fn next_batch_size(completed: usize, elapsed_ms: f64, budget_ms: f64, min: usize, max: usize) -> usize {
+    let min = min.max(1);
+    let max = max.max(min);
+    if completed == 0 || elapsed_ms <= 0.0 {
+        return min;
+    }
+    let per_item_ms = elapsed_ms / completed as f64;
+    if per_item_ms <= 0.0 {
+        return max;
+    }
+    ((budget_ms / per_item_ms).floor() as usize).clamp(min, max)
+}
 
-        // Run each statement individually (D1 limitation)
-        for stmt in sql.split(';').map(str::trim).filter(|s| !s.is_empty()) {
-            // Ignore errors — ALTER TABLE fails harmlessly if column already exists
-            let _ = db.prepare(stmt).bind(&[])?.run().await;
-        }
+#[cfg(test)]
+mod next_batch_size_tests {
+    use super::*;
+
+    #[test]
+    fn no_prior_measurement_returns_the_minimum() {
+        assert_eq!(next_batch_size(0, 0.0, 8_000.0, 10, 400), 10);
+        assert_eq!(next_batch_size(5, 0.0, 8_000.0, 10, 400), 10);
+    }
+
+    #[test]
+    fn scales_to_spend_close_to_the_budget() {
+        // 100 items took 2000ms => 20ms/item; an 8000ms budget affords 400 items.
+        assert_eq!(next_batch_size(100, 2_000.0, 8_000.0, 10, 400), 400);
+    }
 
-        db.prepare("INSERT OR IGNORE INTO _migrations (name) VALUES (?1)")
-            .bind(&[(*name).into()])?
-            .run()
-            .await?;
+    #[test]
+    fn clamps_to_the_configured_max() {
+        // 100 items took 100ms => 1ms/item; the 8000ms budget would afford 8000 items.
+        assert_eq!(next_batch_size(100, 100.0, 8_000.0, 10, 400), 400);
+    }
 
-        console_log!("[migrations] Applied: {}", name);
+    #[test]
+    fn clamps_to_the_configured_min() {
+        // 10 items took 100_000ms => 10_000ms/item; the budget barely affords one.
+        assert_eq!(next_batch_size(10, 100_000.0, 8_000.0, 10, 400), 10);
     }
+}
+
+/// Reads a previously-tuned adaptive size for `key` out of a
+/// `crawl_progress.timings`-shaped JSON blob (see `save_progress`), falling
+/// back to `default` the first time a crawl runs (no prior measurement yet).
+fn read_adaptive_size(timings: Option<&serde_json::Value>, key: &str, default: usize) -> usize {
+    timings
+        .and_then(|t| t.get(key))
+        .and_then(|v| v.as_u64())
+        .map(|v| v as usize)
+        .unwrap_or(default)
+}
 
+/// Reads back the `timings` JSON blob `save_progress` persisted for
+/// `crawl_id`, so the next cron invocation's adaptive sizing can start from
+/// the last one's measurements instead of re-guessing from scratch.
+async fn get_progress_timings(db: &D1Database, crawl_id: &str) -> Result<Option<serde_json::Value>> {
+    let r = db
+        .prepare("SELECT timings FROM crawl_progress WHERE crawl_id=?1")
+        .bind(&[crawl_id.into()])?
+        .first::<serde_json::Value>(None).await?;
+    Ok(r.and_then(|row| row["timings"].as_str().map(str::to_string))
+        .and_then(|s| serde_json::from_str(&s).ok()))
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// SUBREQUEST-BUDGET PLANNER — cap each cron run against Cloudflare's
+// subrequest ceiling, a resource axis `next_batch_size` above doesn't watch
+// ═══════════════════════════════════════════════════════════════════════════
+//
+// `next_batch_size` sizes a run to a CPU-time budget, but a run can be well
+// under its time budget and still trip Cloudflare's per-invocation
+// subrequest limit — board-sparse CDX pages make the time-budget size too
+// small, while a board that starts paginating could make it too large. This
+// reserves a fixed subrequest budget `B`, splits it between Phase 1 (CDX
+// pages) and Phase 2 (board syncs) by a fixed ratio, and sizes each phase
+// off a rolling average of subrequests-per-item tracked in `run_stats` —
+// analogous to sizing a chunk from input size and worker count. The two
+// planners are combined with `min()` at each call site, so this only ever
+// tightens the time-budget estimate, never loosens it.
+
+/// Reserved subrequest budget per cron invocation. Cloudflare Workers caps
+/// a single invocation at 1000 subrequests on the plans this runs under;
+/// this reserves headroom for the D1 batch writes (`upsert_boards`,
+/// `auto_enrich_boards`, `sync_provider_jobs`) that ride along in the same
+/// invocation but aren't counted against either phase's share below.
+const SUBREQUEST_BUDGET: f64 = 900.0;
+
+/// Share of `SUBREQUEST_BUDGET` reserved for Phase 1 (CDX pages); the
+/// remainder goes to Phase 2 (board syncs). Even split — neither phase is
+/// known to be reliably cheaper than the other across the fleet.
+const SUBREQUEST_SPLIT_RATIO: f64 = 0.5;
+
+/// `run_stats.avg_value`'s own smoothing factor, mirroring the EMA used for
+/// per-run adaptive sizing: `new = 0.8*old + 0.2*observed`.
+const RUN_STAT_EMA_ALPHA: f64 = 0.2;
+
+/// Reads a rolling average from `run_stats`, falling back to `default` when
+/// the metric hasn't been observed yet (fresh install, or a migration that
+/// hasn't run).
+async fn get_run_stat(db: &D1Database, metric: &str, default: f64) -> Result<f64> {
+    let row = db.prepare("SELECT avg_value FROM run_stats WHERE metric=?1")
+        .bind(&[metric.into()])?
+        .first::<serde_json::Value>(None).await?;
+    Ok(row.and_then(|r| r["avg_value"].as_f64()).unwrap_or(default))
+}
+
+/// Folds one run's observed subrequests-per-item into `run_stats` via EMA
+/// (`new = 0.8*old + 0.2*observed`), so the planner self-tunes as board
+/// density changes instead of staying pinned to its initial estimate.
+async fn update_run_stat_ema(db: &D1Database, metric: &str, observed: f64) -> Result<()> {
+    let prev = get_run_stat(db, metric, observed).await?;
+    let next = (1.0 - RUN_STAT_EMA_ALPHA) * prev + RUN_STAT_EMA_ALPHA * observed;
+    db.prepare(
+        "INSERT INTO run_stats (metric, avg_value, sample_count, updated_at)
+         VALUES (?1, ?2, 1, datetime('now'))
+         ON CONFLICT(metric) DO UPDATE SET
+            avg_value=?2, sample_count=sample_count+1, updated_at=datetime('now')"
+    ).bind(&[metric.into(), next.into()])?.run().await?;
     Ok(())
 }
 
+/// Plans this cron tick's CDX-page and job-sync-board caps from
+/// `SUBREQUEST_BUDGET`: `pages = floor(r*B / avg_subreq_per_cdx_page)`,
+/// `boards = floor((1-r)*B / avg_subreq_per_board)`, each clamped to the
+/// same `ADAPTIVE_PAGES_*`/`ADAPTIVE_BOARDS_*` bounds the time-budget
+/// planner uses. `get_run_stat` already falls back to an assumed ratio of
+/// 1.0 subrequest/item when `run_stats` has no samples yet (fresh install),
+/// so this degrades to the plain `ADAPTIVE_*` bounds rather than the old
+/// fixed `PAGES_PER_CRON_RUN`/`BOARDS_PER_JOB_SYNC_RUN` constants —
+/// `avg_subreq_per_board` is additionally clamped to at least 1.0 since a
+/// board fetch can't cost less than one subrequest.
+async fn plan_subrequest_caps(db: &D1Database) -> (usize, usize) {
+    let avg_page = get_run_stat(db, "avg_subreq_per_cdx_page", 1.0).await.unwrap_or(1.0).max(0.01);
+    let avg_board = get_run_stat(db, "avg_subreq_per_board", 1.0).await.unwrap_or(1.0).max(1.0);
+    let pages_cap = ((SUBREQUEST_SPLIT_RATIO * SUBREQUEST_BUDGET) / avg_page).floor() as usize;
+    let boards_cap = (((1.0 - SUBREQUEST_SPLIT_RATIO) * SUBREQUEST_BUDGET) / avg_board).floor() as usize;
+    (
+        pages_cap.clamp(ADAPTIVE_PAGES_MIN, ADAPTIVE_PAGES_MAX),
+        boards_cap.clamp(ADAPTIVE_BOARDS_MIN, ADAPTIVE_BOARDS_MAX),
+    )
+}
+
 // ═══════════════════════════════════════════════════════════════════════════
 // D1 OPERATIONS
 // ═══════════════════════════════════════════════════════════════════════════
 
-async fn upsert_boards(db: &D1Database, boards: &[AshbyBoard]) -> Result<usize> {
-    if boards.is_empty() { return Ok(0); }
+/// Outcome of a board-upsert or enrichment batch: `written` is a statement
+/// whose `INSERT`/`UPDATE` guard actually matched a row, `skipped` is one
+/// that ran but touched nothing (a stale crawl's `last_seen_capture_timestamp`
+/// guard, or an enrichment `UPDATE` targeting a slug with no `companies` row
+/// yet), and `failed` is a `(slug, error)` pair for a statement that never
+/// committed even after [`run_batch_resilient`]'s retry + bisection gave up
+/// on it. `written`/`skipped` are derived from an existence check taken
+/// before the write rather than D1's `changes()`/affected-row reporting, same as
+/// `ats.rs`'s `existing_external_ids` — that metadata isn't reliable across
+/// a batched statement.
+#[derive(Debug, Default)]
+struct BoardBatchOutcome {
+    written: usize,
+    skipped: usize,
+    failed: Vec<(String, String)>,
+}
+
+/// Snapshot each key's current `last_seen_capture_timestamp`, so
+/// `upsert_boards` can tell which of its upserts the
+/// `WHERE excluded.last_seen_capture_timestamp >= ...` guard actually let
+/// through. Chunked the same way as `ats.rs`'s `existing_external_ids`.
+async fn existing_company_timestamps(db: &D1Database, keys: &[String]) -> Result<HashMap<String, String>> {
+    let mut out = HashMap::with_capacity(keys.len());
+    const CHUNK_SIZE: usize = 100;
+    for chunk in keys.chunks(CHUNK_SIZE) {
+        let placeholders: Vec<String> = (1..=chunk.len()).map(|i| format!("?{i}")).collect();
+        let sql = format!("SELECT key, last_seen_capture_timestamp FROM companies WHERE key IN ({})", placeholders.join(", "));
+        let binds: Vec<JsValue> = chunk.iter().map(|k| k.clone().into()).collect();
+        let rows = db.prepare(&sql).bind(&binds)?.all().await?.results::<serde_json::Value>()?;
+        for row in rows {
+            if let Some(key) = row["key"].as_str() {
+                out.insert(key.to_string(), row["last_seen_capture_timestamp"].as_str().unwrap_or_default().to_string());
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// Which of `keys` already have a `companies` row, so `auto_enrich_boards`
+/// can tell an enrichment `UPDATE` that matched a row from one that silently
+/// touched zero. Chunked the same way as `ats.rs`'s `existing_external_ids`.
+async fn existing_company_keys(db: &D1Database, keys: &[String]) -> Result<HashSet<String>> {
+    let mut out = HashSet::with_capacity(keys.len());
+    const CHUNK_SIZE: usize = 100;
+    for chunk in keys.chunks(CHUNK_SIZE) {
+        let placeholders: Vec<String> = (1..=chunk.len()).map(|i| format!("?{i}")).collect();
+        let sql = format!("SELECT key FROM companies WHERE key IN ({})", placeholders.join(", "));
+        let binds: Vec<JsValue> = chunk.iter().map(|k| k.clone().into()).collect();
+        let rows = db.prepare(&sql).bind(&binds)?.all().await?.results::<serde_json::Value>()?;
+        for row in rows {
+            if let Some(key) = row["key"].as_str() {
+                out.insert(key.to_string());
+            }
+        }
+    }
+    Ok(out)
+}
+
+async fn upsert_boards(db: &D1Database, boards: &[AshbyBoard]) -> Result<BoardBatchOutcome> {
+    if boards.is_empty() { return Ok(BoardBatchOutcome::default()); }
 
     const SQL: &str = "INSERT INTO companies (key, name, website, category, score, last_seen_crawl_id, last_seen_capture_timestamp, last_seen_source_url)
          VALUES (?1, ?2, ?3, 'PRODUCT', 0.5, ?4, ?5, ?6)
@@ -919,7 +3016,10 @@ async fn upsert_boards(db: &D1Database, boards: &[AshbyBoard]) -> Result<usize>
            updated_at=datetime('now')
          WHERE excluded.last_seen_capture_timestamp >= COALESCE(companies.last_seen_capture_timestamp, '')";
 
-    let mut stmts = Vec::with_capacity(boards.len());
+    let keys: Vec<String> = boards.iter().map(|b| b.slug.clone()).collect();
+    let existing = existing_company_timestamps(db, &keys).await?;
+
+    let mut items = Vec::with_capacity(boards.len());
     for board in boards {
         let name: String = board.slug
             .split(|c: char| c == '-' || c == '_')
@@ -933,36 +3033,63 @@ async fn upsert_boards(db: &D1Database, boards: &[AshbyBoard]) -> Result<usize>
             .collect::<Vec<_>>()
             .join(" ");
         let website = format!("https://jobs.ashbyhq.com/{}", board.slug);
-        stmts.push(db.prepare(SQL).bind(&[
+        let stmt = db.prepare(SQL).bind(&[
             board.slug.clone().into(),
             name.into(),
             website.into(),
             board.crawl_id.clone().into(),
             board.timestamp.clone().into(),
             board.url.clone().into(),
-        ])?);
+        ])?;
+        items.push((board.slug.clone(), stmt));
     }
 
-    // D1 batch: chunk to stay within CF subrequest limits (100 per batch)
+    // D1 batch: chunk to stay within CF subrequest limits (100 per batch),
+    // isolating any statement that still won't commit after retry+bisection.
     const BATCH_SIZE: usize = 100;
-    let mut saved = 0usize;
-    for chunk in stmts.chunks(BATCH_SIZE) {
-        if let Ok(results) = db.batch(chunk.to_vec()).await {
-            saved += results.len();
+    let mut batch_outcome = BatchOutcome::default();
+    for chunk in items.chunks(BATCH_SIZE) {
+        batch_outcome.merge(
+            run_batch_resilient(db, chunk.to_vec())
+                .with_poll_timer("upsert_boards:batch", chunk.len())
+                .await
+        );
+    }
+    if !batch_outcome.failed.is_empty() {
+        console_log!("[upsert_boards] {} board(s) failed to commit: {:?}", batch_outcome.failed.len(), batch_outcome.failed);
+    }
+
+    let mut written = 0usize;
+    let mut skipped = 0usize;
+    for board in boards {
+        if batch_outcome.failed.iter().any(|(slug, _)| slug == &board.slug) {
+            continue;
         }
+        let passed_guard = existing.get(&board.slug)
+            .map(|prev| board.timestamp.as_str() >= prev.as_str())
+            .unwrap_or(true);
+        if passed_guard { written += 1 } else { skipped += 1 }
     }
-    Ok(saved)
+
+    Ok(BoardBatchOutcome { written, skipped, failed: batch_outcome.failed })
 }
 
+/// `timings`, when given, is a JSON object of per-phase elapsed ms (e.g.
+/// `{"cdx_fetch_ms":…, "ashby_fetch_ms":…, "d1_write_ms":…}`) from [`timed`]
+/// calls around this run's subrequests — `COALESCE`d against the existing
+/// value so an intermediate "running" save (taken before this batch's
+/// timings are known) doesn't blank out the last batch's numbers.
 async fn save_progress(
     db: &D1Database, crawl_id: &str, total: u32, current: u32, status: &str, found: u32,
+    timings: Option<&serde_json::Value>,
 ) -> Result<()> {
     db.prepare(
-        "INSERT INTO crawl_progress (crawl_id,total_pages,current_page,status,boards_found,started_at,updated_at)
-         VALUES (?1,?2,?3,?4,?5,datetime('now'),datetime('now'))
+        "INSERT INTO crawl_progress (crawl_id,total_pages,current_page,status,boards_found,timings,started_at,updated_at)
+         VALUES (?1,?2,?3,?4,?5,?6,datetime('now'),datetime('now'))
          ON CONFLICT(crawl_id) DO UPDATE SET
            total_pages=excluded.total_pages, current_page=excluded.current_page,
            status=excluded.status, boards_found=excluded.boards_found,
+           timings=COALESCE(excluded.timings, timings),
            finished_at=CASE WHEN excluded.status='done' THEN datetime('now') ELSE finished_at END,
            updated_at=datetime('now')"
     )
@@ -972,8 +3099,9 @@ async fn save_progress(
         (current as f64).into(),
         status.into(),
         (found as f64).into(),
+        timings.map(|t| t.to_string()).map(JsValue::from).unwrap_or(JsValue::NULL),
     ])?
-    .run().await?;
+    .run().with_poll_timer("save_progress:run", 1).await?;
     Ok(())
 }
 
@@ -981,7 +3109,7 @@ async fn get_progress(db: &D1Database, crawl_id: &str) -> Result<Option<(u32,u32
     let r = db
         .prepare("SELECT total_pages,current_page,status,boards_found FROM crawl_progress WHERE crawl_id=?1")
         .bind(&[crawl_id.into()])?
-        .first::<serde_json::Value>(None).await?;
+        .first::<serde_json::Value>(None).with_poll_timer("get_progress:first", 1).await?;
     Ok(r.map(|row| (
         row["total_pages"].as_f64().unwrap_or(0.0) as u32,
         row["current_page"].as_f64().unwrap_or(0.0) as u32,
@@ -992,21 +3120,65 @@ async fn get_progress(db: &D1Database, crawl_id: &str) -> Result<Option<(u32,u32
 
 // ── Job-sync D1 helpers ───────────────────────────────────────────────────
 
-/// Fetch the next batch of company slugs that have never been synced
-/// (last_synced_at IS NULL in ashby_boards), ordered alphabetically.
-/// When all companies have been synced once, falls back to the oldest-synced ones
-/// so the cycle repeats.
+/// Fetch the next batch of company slugs to sync, spending the request budget on
+/// live boards first: ordered by lifecycle state (`active` > `validated` >
+/// `discovered` > `stale` > `dead`), then by retry backoff, then oldest-synced
+/// first within a tier — so healthy boards get re-checked often while boards that
+/// keep failing sink to the back of the queue instead of crowding out live ones.
+/// Boards whose `sync_state` has flipped to `'dead'` (see [`record_sync_failure`])
+/// or whose `next_retry_at` backoff hasn't elapsed yet are excluded outright.
 async fn get_company_slugs(db: &D1Database, limit: usize) -> Result<Vec<String>> {
     let rows = db
         .prepare(
             "SELECT c.key FROM companies c
              LEFT JOIN ashby_boards ab ON ab.slug = c.key
-             WHERE ab.last_synced_at IS NULL
-             ORDER BY c.key
+             WHERE COALESCE(ab.sync_state, 'pending') != 'dead'
+               AND (ab.next_retry_at IS NULL OR ab.next_retry_at <= datetime('now'))
+             ORDER BY
+               CASE COALESCE(ab.lifecycle_state, 'discovered')
+                 WHEN 'active'     THEN 0
+                 WHEN 'validated'  THEN 1
+                 WHEN 'discovered' THEN 2
+                 WHEN 'stale'      THEN 3
+                 WHEN 'dead'       THEN 4
+                 ELSE 2
+               END,
+               ab.next_retry_at IS NOT NULL,
+               ab.next_retry_at ASC,
+               ab.last_synced_at IS NOT NULL,
+               ab.last_synced_at ASC
+             LIMIT ?1"
+        )
+        .bind(&[(limit as f64).into()])?
+        .all()
+        .with_poll_timer("get_company_slugs:all", limit)
+        .await?
+        .results::<serde_json::Value>()?;
+    Ok(rows.iter()
+        .filter_map(|r| r["key"].as_str().map(String::from))
+        .collect())
+}
+
+/// Greenhouse counterpart of [`get_company_slugs`] — same dead/backoff
+/// exclusion and never-synced-first, oldest-synced-next ordering, joined
+/// against `greenhouse_boards` instead.
+async fn get_greenhouse_company_slugs(db: &D1Database, limit: usize) -> Result<Vec<String>> {
+    let rows = db
+        .prepare(
+            "SELECT c.key FROM companies c
+             LEFT JOIN greenhouse_boards gb ON gb.token = c.key
+             WHERE COALESCE(gb.sync_state, 'pending') != 'dead'
+               AND (gb.next_retry_at IS NULL OR gb.next_retry_at <= datetime('now'))
+             ORDER BY
+               gb.last_synced_at IS NOT NULL,
+               gb.next_retry_at IS NOT NULL,
+               gb.next_retry_at ASC,
+               gb.last_synced_at ASC
              LIMIT ?1"
         )
         .bind(&[(limit as f64).into()])?
         .all()
+        .with_poll_timer("get_greenhouse_company_slugs:all", limit)
         .await?
         .results::<serde_json::Value>()?;
     Ok(rows.iter()
@@ -1014,15 +3186,335 @@ async fn get_company_slugs(db: &D1Database, limit: usize) -> Result<Vec<String>>
         .collect())
 }
 
+/// `(board_table, key_column)` for each provider's board-tracking table, so
+/// [`record_sync_failure`] can target the right row by a plain provider
+/// name rather than a generic trait bound — Ashby's board pipeline predates
+/// `AtsSource` and isn't part of it.
+fn board_sync_table(provider: &str) -> Option<(&'static str, &'static str)> {
+    match provider {
+        "ashby" => Some(("ashby_boards", "slug")),
+        "greenhouse" => Some(("greenhouse_boards", "token")),
+        "workable" => Some(("workable_boards", "shortcode")),
+        "lever" => Some(("lever_boards", "site")),
+        _ => None,
+    }
+}
+
+/// Retry attempts after which a board's `sync_state` flips to `'dead'` — a
+/// board that's still failing this many times in a row is treated as a
+/// permanently invalid job rather than retried forever.
+const MAX_SYNC_RETRIES: i64 = 6;
+
+/// Ceiling on the exponential backoff `record_sync_failure` schedules, so a
+/// long-dead-but-not-yet-`'dead'` board is still re-tried once a day rather
+/// than the doubling interval growing unbounded.
+const MAX_SYNC_RETRY_BACKOFF_MINUTES: i64 = 24 * 60;
+
+/// Record a failed sync attempt for `slug` under `provider` (one of
+/// `"ashby"`/`"greenhouse"`/`"workable"`/`"lever"`): increments `retry_count`, schedules
+/// `next_retry_at` with backoff doubling each attempt (capped at
+/// [`MAX_SYNC_RETRY_BACKOFF_MINUTES`]), records `last_error`, and flips
+/// `sync_state` to `'dead'` once `retry_count` reaches [`MAX_SYNC_RETRIES`] so
+/// [`get_company_slugs`]/[`get_greenhouse_company_slugs`] stop selecting it.
+/// Best-effort — a lost retry-state update never blocks the caller's error
+/// from propagating.
+async fn record_sync_failure(db: &D1Database, provider: &str, slug: &str, err: &str) -> Result<()> {
+    let Some((table, key_column)) = board_sync_table(provider) else {
+        return Err(Error::RustError(format!("record_sync_failure: unknown provider '{provider}'")));
+    };
+
+    let retry_count = db.prepare(&format!("SELECT retry_count FROM {table} WHERE {key_column}=?1"))
+        .bind(&[slug.into()])?
+        .first::<serde_json::Value>(None)
+        .with_poll_timer("record_sync_failure:select", 1)
+        .await?
+        .and_then(|row| row["retry_count"].as_i64())
+        .unwrap_or(0) + 1;
+
+    let backoff_minutes = (60 * 2i64.pow(retry_count.clamp(0, 20) as u32)).min(MAX_SYNC_RETRY_BACKOFF_MINUTES);
+    let sync_state = if retry_count >= MAX_SYNC_RETRIES { "dead" } else { "pending" };
+
+    db.prepare(&format!(
+        "UPDATE {table} SET
+           retry_count=?1,
+           next_retry_at=datetime('now', '+' || ?2 || ' minutes'),
+           last_error=?3,
+           sync_state=?4,
+           updated_at=datetime('now')
+         WHERE {key_column}=?5"
+    ))
+    .bind(&[
+        (retry_count as f64).into(),
+        (backoff_minutes as f64).into(),
+        err.into(),
+        sync_state.into(),
+        slug.into(),
+    ])?
+    .run()
+    .with_poll_timer("record_sync_failure:update", 1)
+    .await?;
+
+    console_log!(
+        "[sync-retry] {} '{}' failed (attempt {}, {}): {} — next retry in {}m",
+        provider, slug, retry_count, sync_state, err, backoff_minutes
+    );
+    Ok(())
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// CRON PROVIDER SYNC (CronSyncProvider) — uniform fetch-and-upsert, cron-side
+// ═══════════════════════════════════════════════════════════════════════════
+//
+// Ashby has always had a full pipeline here; Greenhouse only fed `build_bm25_index`
+// from rows someone else wrote. `CronSyncProvider` gives both providers the same
+// shape (fetch a board's jobs, then upsert them) so the cron job-sync phase can
+// drive them through one generic helper instead of hand-duplicating the Ashby
+// loop. This is distinct from `ats::AtsSource` (see the note above
+// `ErrorCode`): that trait normalizes a posting onto the shared
+// `JobRow`/`upsert_jobs_to_d1` pipeline, while this one just needs "fetch a
+// board, upsert its jobs, whatever that upsert does under the hood" —
+// Ashby's `upsert_jobs` below calls its own hand-optimized function, not
+// `ats::upsert_jobs_to_d1`, so folding this into `AtsSource` would force
+// that optimization out — the reason Ashby doesn't implement `AtsSource` at
+// all. Named `*CronSync` (not `*Sync`) to avoid colliding with
+// `greenhouse::GreenhouseSource` / `tasks::TaskKind::GreenhouseSync`, which are
+// different things entirely.
+
+trait CronSyncProvider {
+    type Job;
+    type BoardJobs;
+
+    /// Provider name as stored in `companies.ats_provider` / recognized by
+    /// [`board_sync_table`] — used to tag [`record_sync_failure`] calls.
+    fn provider_name() -> &'static str;
+
+    /// Fetch all jobs from a single board/account by its provider-specific slug/token.
+    async fn fetch_board(slug: &str) -> Result<Self::BoardJobs>;
+
+    /// Split a fetched board into its postings and board title, for upserting.
+    fn jobs_and_title(board: &Self::BoardJobs) -> (&[Self::Job], &str);
+
+    /// Upsert a board's jobs into D1.
+    async fn upsert_jobs(db: &D1Database, jobs: &[Self::Job], slug: &str, board_title: &str) -> Result<usize>;
+}
+
+struct AshbyCronSync;
+
+impl CronSyncProvider for AshbyCronSync {
+    type Job = AshbyJobPosting;
+    type BoardJobs = AshbyJobBoardResponse;
+
+    fn provider_name() -> &'static str { "ashby" }
+
+    async fn fetch_board(slug: &str) -> Result<Self::BoardJobs> {
+        fetch_ashby_board_jobs(slug).await
+    }
+
+    fn jobs_and_title(board: &Self::BoardJobs) -> (&[Self::Job], &str) {
+        (&board.jobs, board.title.as_deref().unwrap_or(""))
+    }
+
+    async fn upsert_jobs(db: &D1Database, jobs: &[Self::Job], slug: &str, board_title: &str) -> Result<usize> {
+        upsert_jobs_to_d1(db, jobs, slug, board_title).await.map(|o| o.committed)
+    }
+}
+
+struct GreenhouseCronSync;
+
+impl CronSyncProvider for GreenhouseCronSync {
+    type Job = greenhouse::GreenhouseJob;
+    type BoardJobs = greenhouse::GreenhouseBoardResponse;
+
+    fn provider_name() -> &'static str { "greenhouse" }
+
+    async fn fetch_board(slug: &str) -> Result<Self::BoardJobs> {
+        greenhouse::fetch_greenhouse_board_jobs(slug).await
+    }
+
+    fn jobs_and_title(board: &Self::BoardJobs) -> (&[Self::Job], &str) {
+        (&board.jobs, board.name.as_deref().unwrap_or(""))
+    }
+
+    async fn upsert_jobs(db: &D1Database, jobs: &[Self::Job], slug: &str, board_title: &str) -> Result<usize> {
+        greenhouse::upsert_greenhouse_jobs_to_d1(db, jobs, slug, board_title).await.map(|o| o.committed)
+    }
+}
+
+/// Fetch + upsert a batch of boards for one provider. Mirrors the original
+/// Ashby-only job-sync loop in `cron_handler_inner`, parameterized over
+/// `CronSyncProvider` so adding a provider means adding an impl, not another loop.
+/// Returns `(jobs_synced, total_fetch_ms)` — the latter is the summed
+/// per-board fetch time (each timed individually via [`timed`], so a single
+/// slow board also gets its own `console_warn!`) for the caller to fold into
+/// a `crawl_progress.timings` entry.
+async fn sync_provider_jobs<P: CronSyncProvider>(db: &D1Database, slugs: Vec<String>) -> (usize, f64) {
+    if slugs.is_empty() {
+        return (0, 0.0);
+    }
+    let runner = rig_compat::ConcurrentRunner::new();
+    let (boards_ok, boards_err) = runner
+        .run_all(slugs, |slug| async move {
+            let slug_on_err = slug.clone();
+            let (result, elapsed_ms) = timed(P::provider_name(), P::fetch_board(&slug)).await;
+            result
+                .map(|board| (slug, board, elapsed_ms))
+                .map_err(|e| (slug_on_err, e))
+        })
+        .await;
+
+    for (slug, e) in &boards_err {
+        console_log!("[job-sync] board fetch error for '{}': {:?}", slug, e);
+        if let Err(record_err) = record_sync_failure(db, P::provider_name(), slug, &format!("{e:?}")).await {
+            console_log!("[job-sync] record_sync_failure({}, '{}') failed: {:?}", P::provider_name(), slug, record_err);
+        }
+    }
+
+    let mut total = 0usize;
+    let mut fetch_ms = 0.0f64;
+    for (slug, board, elapsed_ms) in boards_ok {
+        fetch_ms += elapsed_ms;
+        let (jobs, title) = P::jobs_and_title(&board);
+        total += P::upsert_jobs(db, jobs, &slug, title).await.unwrap_or(0);
+    }
+    (total, fetch_ms)
+}
+
+/// Counts for one sync invocation (one `sync_runs` row). Distinct from the
+/// mutable `job_count`/`last_synced_at` columns on `*_boards` tables: those
+/// only ever hold the latest snapshot, while a `sync_runs` row is immutable
+/// history — enough to chart jobs-added-per-day or an error rate per source
+/// without re-deriving it from board state that's already been overwritten.
+struct SyncRunMetrics {
+    fetched: usize,
+    inserted: usize,
+    updated: usize,
+    skipped: usize,
+    errors: usize,
+    duration_ms: i64,
+}
+
+/// Append one immutable `sync_runs` row for a completed (or failed) sync
+/// invocation. Best-effort — a lost metrics row never blocks the sync it's
+/// describing.
+async fn record_sync_run(db: &D1Database, source_kind: &str, site: &str, m: &SyncRunMetrics) {
+    let result = db.prepare(
+        "INSERT INTO sync_runs (source_kind, site, fetched, inserted, updated, skipped, errors, duration_ms)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)"
+    ).bind(&[
+        source_kind.into(), site.into(),
+        (m.fetched as f64).into(), (m.inserted as f64).into(), (m.updated as f64).into(),
+        (m.skipped as f64).into(), (m.errors as f64).into(), (m.duration_ms as f64).into(),
+    ]);
+    let run = match result { Ok(stmt) => stmt.run().await, Err(e) => Err(e) };
+    if let Err(e) = run {
+        console_log!("[sync-runs] failed to record {} run for '{}': {:?}", source_kind, site, e);
+    }
+}
+
+/// Hex-encode a SHA-256 digest. Used to content-address job description
+/// bodies so identical postings (e.g. the same role reposted across boards)
+/// share a single row in `job_bodies` instead of duplicating the HTML, and
+/// by `migrations::apply_pending_migrations` to checksum each migration's
+/// SQL.
+pub(crate) fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes).iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Quarantine a record that would otherwise be silently dropped (a CDX line
+/// that didn't parse, a posting with no usable external id/URL) into
+/// `_invalid_records` — see migration `0015_invalid_records`. Imports
+/// pict-rs's `InvalidJob` idea: keep the raw payload plus the error that
+/// rejected it, so it's inspectable via `GET /invalid` and retriable via
+/// `reprocess_invalid` instead of just vanishing into a log line.
+/// Best-effort — a lost quarantine row never blocks the caller's main path.
+async fn record_invalid(db: &D1Database, kind: &str, ref_id: &str, raw_payload: &str, error: &str) {
+    let id = sha256_hex(format!(
+        "{kind}-{ref_id}-{raw_payload}-{}-{}", js_sys::Date::now(), js_sys::Math::random()
+    ).as_bytes())[..20].to_string();
+    let stmt = db.prepare(
+        "INSERT INTO _invalid_records (id, kind, ref_id, raw_payload, error) VALUES (?1, ?2, ?3, ?4, ?5)"
+    ).bind(&[id.into(), kind.into(), ref_id.into(), raw_payload.into(), error.into()]);
+    let result = match stmt {
+        Ok(stmt) => run_single_with_retry(&stmt).await,
+        Err(e) => Err(format!("{e:?}")),
+    };
+    if let Err(e) = result {
+        console_log!("[invalid] failed to record {} '{}': {}", kind, ref_id, e);
+    }
+}
+
+/// Fetch job description bodies by hash in batch, for callers that need the
+/// full text (e.g. rendering a job detail page). Missing hashes are simply
+/// absent from the returned map rather than erroring.
+async fn get_job_bodies(db: &D1Database, hashes: &[String]) -> Result<HashMap<String, String>> {
+    let mut out = HashMap::with_capacity(hashes.len());
+    if hashes.is_empty() {
+        return Ok(out);
+    }
+
+    // D1 has no array-bind support, so batch lookups go through a chunked
+    // `IN (?1, ?2, ...)` clause rather than one query per hash.
+    const CHUNK_SIZE: usize = 100;
+    for chunk in hashes.chunks(CHUNK_SIZE) {
+        let placeholders: Vec<String> = (1..=chunk.len()).map(|i| format!("?{i}")).collect();
+        let sql = format!(
+            "SELECT hash, body FROM job_bodies WHERE hash IN ({})",
+            placeholders.join(", ")
+        );
+        let binds: Vec<JsValue> = chunk.iter().map(|h| h.clone().into()).collect();
+        let rows = db.prepare(&sql).bind(&binds)?.all().await?.results::<serde_json::Value>()?;
+        for row in rows {
+            if let (Some(hash), Some(body)) = (row["hash"].as_str(), row["body"].as_str()) {
+                out.insert(hash.to_string(), body.to_string());
+            }
+        }
+    }
+    Ok(out)
+}
+
+const STALE_AFTER_FAILURES: i64 = 3;
+const DEAD_AFTER_FAILURES: i64 = 10;
+
+/// Board lifecycle: `discovered → validated → active → stale → dead`.
+/// A successful fetch (board returned jobs) resets the failure counter and
+/// promotes — to `active` if it was already `validated` or `active`,
+/// otherwise to `validated` so a recovering `stale`/`dead` board re-earns
+/// trust before counting as fully `active` again. Repeated empty/404
+/// responses demote, first to `stale` then to `dead` past the
+/// consecutive-failure thresholds.
+fn next_lifecycle_state(current: &str, consecutive_failures: i64, success: bool) -> (&'static str, i64) {
+    if success {
+        let next = if current == "active" || current == "validated" { "active" } else { "validated" };
+        return (next, 0);
+    }
+
+    let failures = consecutive_failures + 1;
+    let next: &'static str = if failures >= DEAD_AFTER_FAILURES {
+        "dead"
+    } else if failures >= STALE_AFTER_FAILURES {
+        "stale"
+    } else {
+        match current {
+            "validated" => "validated",
+            "active" => "active",
+            "stale" => "stale",
+            "dead" => "dead",
+            _ => "discovered",
+        }
+    };
+    (next, failures)
+}
+
 /// Upsert a batch of Ashby job postings into the D1 `jobs` table.
-/// Mirrors the TypeScript `saveAshbyJobData` mapping.
-/// Returns the number of successfully upserted rows.
+/// Mirrors the TypeScript `saveAshbyJobData` mapping. Returns a
+/// [`BatchOutcome`] rather than an optimistic row count, so a caller can tell
+/// exactly how many rows committed vs which ones failed even after retry.
 async fn upsert_jobs_to_d1(
     db: &D1Database,
     jobs: &[AshbyJobPosting],
     slug: &str,
     board_title: &str,
-) -> Result<usize> {
+) -> Result<BatchOutcome> {
     let company_name = if board_title.is_empty() {
         // Derive readable name from slug: "hello-world" → "Hello World"
         slug.split(|c: char| c == '-' || c == '_')
@@ -1039,16 +3531,18 @@ async fn upsert_jobs_to_d1(
         board_title.to_string()
     };
 
+    const JOB_BODY_SQL: &str = "INSERT OR IGNORE INTO job_bodies (hash, body) VALUES (?1, ?2)";
+
     const JOB_SQL: &str = "INSERT INTO jobs (
                 external_id, source_kind, source_id, company_key, company_name,
-                title, url, description, location,
+                title, url, description_hash, location,
                 posted_at,
                 workplace_type,
                 ashby_department, ashby_team, ashby_employment_type,
                 ashby_is_remote, ashby_is_listed, ashby_published_at,
                 ashby_job_url, ashby_apply_url,
                 ashby_secondary_locations, ashby_compensation, ashby_address,
-                categories, ats_created_at, updated_at
+                categories, content_hash, ats_created_at, updated_at
             ) VALUES (
                 ?1, 'ashby', ?2, ?3, ?4,
                 ?5, ?6, NULLIF(?7,''), NULLIF(?8,''),
@@ -1058,7 +3552,7 @@ async fn upsert_jobs_to_d1(
                 ?14, ?15, NULLIF(?9,''),
                 NULLIF(?16,''), NULLIF(?17,''),
                 NULLIF(?18,''), NULLIF(?19,''), NULLIF(?20,''),
-                NULLIF(?21,''), NULLIF(?9,''), datetime('now')
+                NULLIF(?21,''), ?22, NULLIF(?9,''), datetime('now')
             )
             ON CONFLICT(external_id) DO UPDATE SET
                 source_id=excluded.source_id,
@@ -1066,7 +3560,7 @@ async fn upsert_jobs_to_d1(
                 company_name=COALESCE(excluded.company_name, company_name),
                 title=excluded.title,
                 url=excluded.url,
-                description=COALESCE(excluded.description, description),
+                description_hash=COALESCE(excluded.description_hash, description_hash),
                 location=COALESCE(excluded.location, location),
                 posted_at=COALESCE(excluded.posted_at, posted_at),
                 workplace_type=COALESCE(excluded.workplace_type, workplace_type),
@@ -1082,18 +3576,53 @@ async fn upsert_jobs_to_d1(
                 ashby_compensation=excluded.ashby_compensation,
                 ashby_address=excluded.ashby_address,
                 categories=excluded.categories,
+                content_hash=excluded.content_hash,
                 ats_created_at=excluded.ats_created_at,
+                status='open',
+                closed_at=NULL,
                 updated_at=datetime('now')";
 
-    let mut stmts = Vec::with_capacity(jobs.len() + 2);
-    let mut count = 0usize;
+    // Change detection: only rewrite postings whose content actually changed
+    // since the last sync, keyed by external_id → content_hash.
+    let existing_hashes: HashMap<String, String> = db
+        .prepare("SELECT external_id, content_hash FROM jobs WHERE source_id=?1")
+        .bind(&[slug.into()])?
+        .all().await?
+        .results::<serde_json::Value>()?
+        .iter()
+        .filter_map(|row| {
+            let id = row["external_id"].as_str()?;
+            let hash = row["content_hash"].as_str()?;
+            Some((id.to_string(), hash.to_string()))
+        })
+        .collect();
+
+    let (current_lifecycle, consecutive_failures) = db
+        .prepare("SELECT lifecycle_state, consecutive_failures FROM ashby_boards WHERE slug=?1")
+        .bind(&[slug.into()])?
+        .first::<serde_json::Value>(None).await?
+        .map(|row| (
+            row["lifecycle_state"].as_str().unwrap_or("discovered").to_string(),
+            row["consecutive_failures"].as_i64().unwrap_or(0),
+        ))
+        .unwrap_or_else(|| ("discovered".to_string(), 0));
+
+    let mut stmts: Vec<(String, D1PreparedStatement)> = Vec::with_capacity(jobs.len() + 2);
+    // Every posting this fetch actually returned (even ones whose content
+    // hash is unchanged below and so never reaches `stmts`) — the janitor
+    // pass diffs this against what's already in D1 to close postings the
+    // board stopped returning, so it has to include the unchanged ones too.
+    let mut fetched_ids: Vec<String> = Vec::with_capacity(jobs.len());
 
     for job in jobs {
         let url = job.job_url.as_deref().or(job.apply_url.as_deref()).unwrap_or("");
         if url.is_empty() {
             console_log!("[job-sync] skipping job {} (no url) from board {}", job.id, slug);
+            let raw = serde_json::to_string(job).unwrap_or_default();
+            record_invalid(db, "posting", slug, &raw, "missing job_url/apply_url").await;
             continue; // url is NOT NULL in schema — skip malformed postings
         }
+        fetched_ids.push(job.id.clone());
 
         let description = job.description_html.as_deref()
             .or(job.description_plain.as_deref())
@@ -1107,6 +3636,32 @@ async fn upsert_jobs_to_d1(
             Some(false) => "office",
             None        => "",
         };
+        let compensation_json = job.compensation.as_ref()
+            .map(|c| serde_json::to_string(c).unwrap_or_default())
+            .unwrap_or_default();
+
+        // Change detection: skip the write entirely if nothing that matters changed.
+        let content_hash = sha256_hex(
+            format!(
+                "{}\u{1}{}\u{1}{}\u{1}{}\u{1}{}\u{1}{}",
+                job.title, description, location, workplace_type, compensation_json,
+                job.is_listed.unwrap_or(true),
+            ).as_bytes()
+        );
+        if existing_hashes.get(&job.id) == Some(&content_hash) {
+            continue;
+        }
+
+        let description_hash = if description.is_empty() {
+            String::new()
+        } else {
+            let hash = sha256_hex(description.as_bytes());
+            stmts.push((format!("job_body:{}", job.id), db.prepare(JOB_BODY_SQL).bind(&[
+                hash.clone().into(),
+                description.into(),
+            ])?));
+            hash
+        };
         let department = job.department.as_deref().unwrap_or("");
         let team = job.team.as_deref().unwrap_or("");
         let employment_type = job.employment_type.as_deref().unwrap_or("");
@@ -1122,10 +3677,6 @@ async fn upsert_jobs_to_d1(
             })
             .unwrap_or_default();
 
-        let compensation_json = job.compensation.as_ref()
-            .map(|c| serde_json::to_string(c).unwrap_or_default())
-            .unwrap_or_default();
-
         let address_json = job.address.as_ref()
             .map(|a| serde_json::to_string(a).unwrap_or_default())
             .unwrap_or_default();
@@ -1156,14 +3707,14 @@ async fn upsert_jobs_to_d1(
             .map(|v| JsValue::from_f64(if v { 1.0 } else { 0.0 }))
             .unwrap_or(JsValue::NULL);
 
-        stmts.push(db.prepare(JOB_SQL).bind(&[
+        stmts.push((job.id.clone(), db.prepare(JOB_SQL).bind(&[
             job.id.clone().into(),        // ?1  external_id
             slug.into(),                   // ?2  source_id
             slug.into(),                   // ?3  company_key
             company_name.clone().into(),   // ?4  company_name
             job.title.clone().into(),      // ?5  title
             url.into(),                    // ?6  url
-            description.into(),            // ?7  description
+            description_hash.into(),       // ?7  description_hash
             location.into(),               // ?8  location
             published_at.into(),           // ?9  published_at (used for posted_at, ashby_published_at, ats_created_at)
             workplace_type.into(),         // ?10 workplace_type
@@ -1178,35 +3729,81 @@ async fn upsert_jobs_to_d1(
             compensation_json.into(),      // ?19 ashby_compensation
             address_json.into(),           // ?20 ashby_address
             categories_json.into(),        // ?21 categories
-        ])?);
-        count += 1;
-    }
-
-    // Append board + company tracking updates to the same batch
-    stmts.push(db.prepare(
-        "INSERT INTO ashby_boards (slug, url, first_seen, last_seen, crawl_id, last_synced_at, job_count, is_active)
-         VALUES (?1, ?2, datetime('now'), datetime('now'), 'job-sync', datetime('now'), ?3, 1)
-         ON CONFLICT(slug) DO UPDATE SET
-           last_synced_at=datetime('now'),
-           job_count=?3,
-           is_active=1,
-           updated_at=datetime('now')"
-    ).bind(&[
-        slug.into(),
-        format!("https://jobs.ashbyhq.com/{}", slug).into(),
-        (count as f64).into(),
-    ])?);
-
-    stmts.push(db.prepare("UPDATE companies SET updated_at=datetime('now') WHERE key=?1")
-        .bind(&[slug.into()])?);
+            content_hash.into(),           // ?22 content_hash
+        ])?));
+    }
 
-    // D1 batch: chunk to stay within CF subrequest limits (100 per batch)
+    // D1 batch: chunk to stay within CF subrequest limits (100 per batch),
+    // isolating any statement that still won't commit after retry+bisection
+    // instead of swallowing the whole chunk's result — same pattern as
+    // `upsert_boards`/`ats::upsert_jobs_to_d1`.
     const BATCH_SIZE: usize = 100;
+    let mut outcome = BatchOutcome::default();
     for chunk in stmts.chunks(BATCH_SIZE) {
-        let _ = db.batch(chunk.to_vec()).await;
+        outcome.merge(
+            run_batch_resilient(db, chunk.to_vec())
+                .with_poll_timer("upsert_jobs_to_d1:batch", chunk.len())
+                .await
+        );
+    }
+    if !outcome.failed.is_empty() {
+        console_log!(
+            "[job-sync:ashby] board '{}': {} job row(s) failed to commit: {:?}",
+            slug, outcome.failed.len(), outcome.failed
+        );
+    }
+
+    // Janitor pass: close whatever `jobs` rows this board used to have that
+    // didn't come back in this fetch. Only runs on a non-empty fetch — an
+    // empty/404 response already demotes the board via `next_lifecycle_state`
+    // below rather than being treated as "the board has zero postings now".
+    if !jobs.is_empty() {
+        match ats::reconcile_closed_jobs(db, "ashby", slug, &fetched_ids).await {
+            Ok(o) if !o.failed.is_empty() => console_log!(
+                "[job-sync:ashby] board '{}': {} stale-job close(s) failed: {:?}",
+                slug, o.failed.len(), o.failed
+            ),
+            Ok(_) => {}
+            Err(e) => console_log!("[job-sync:ashby] board '{}': janitor reconciliation failed: {:?}", slug, e),
+        }
+    }
+
+    // Board + company tracking updates — small, best-effort bookkeeping
+    // rather than job data, so failures are logged rather than folded into
+    // the job `BatchOutcome` returned to the caller.
+    let (lifecycle_state, consecutive_failures) =
+        next_lifecycle_state(&current_lifecycle, consecutive_failures, !jobs.is_empty());
+    let tracking_stmts = vec![
+        (format!("ashby_boards:{slug}"), db.prepare(
+            "INSERT INTO ashby_boards (slug, url, first_seen, last_seen, crawl_id, last_synced_at, job_count, is_active, lifecycle_state, consecutive_failures)
+             VALUES (?1, ?2, datetime('now'), datetime('now'), 'job-sync', datetime('now'), ?3, 1, ?4, ?5)
+             ON CONFLICT(slug) DO UPDATE SET
+               last_synced_at=datetime('now'),
+               job_count=?3,
+               is_active=1,
+               lifecycle_state=?4,
+               consecutive_failures=?5,
+               retry_count=0,
+               next_retry_at=NULL,
+               last_error=NULL,
+               sync_state='done',
+               updated_at=datetime('now')"
+        ).bind(&[
+            slug.into(),
+            format!("https://jobs.ashbyhq.com/{}", slug).into(),
+            (outcome.committed as f64).into(),
+            lifecycle_state.into(),
+            (consecutive_failures as f64).into(),
+        ])?),
+        (format!("companies:{slug}"), db.prepare("UPDATE companies SET updated_at=datetime('now') WHERE key=?1")
+            .bind(&[slug.into()])?),
+    ];
+    let tracking_outcome = run_batch_resilient(db, tracking_stmts).await;
+    for (label, reason) in &tracking_outcome.failed {
+        console_log!("[job-sync:ashby] tracking write failed for {}: {}", label, reason);
     }
 
-    Ok(count)
+    Ok(outcome)
 }
 
 // ═══════════════════════════════════════════════════════════════════════════
@@ -1266,6 +3863,42 @@ fn define_tools() -> Vec<rig_compat::ToolDefinition> {
                 },
             ],
         },
+        rig_compat::ToolDefinition {
+            name: "sync_lever_board".into(),
+            description: "On-demand sync of a single Lever board's postings, outside the regular cron cycle".into(),
+            parameters: vec![
+                rig_compat::ToolParam {
+                    name: "site".into(),
+                    description: "Lever site identifier, e.g. the `site` in jobs.lever.co/<site>".into(),
+                    r#type: "string".into(),
+                    required: true,
+                },
+            ],
+        },
+        rig_compat::ToolDefinition {
+            name: "sync_workable_board".into(),
+            description: "On-demand sync of a single Workable board's postings, outside the regular cron cycle".into(),
+            parameters: vec![
+                rig_compat::ToolParam {
+                    name: "shortcode".into(),
+                    description: "Workable account shortcode, e.g. the `shortcode` in apply.workable.com/<shortcode>".into(),
+                    r#type: "string".into(),
+                    required: true,
+                },
+            ],
+        },
+        rig_compat::ToolDefinition {
+            name: "sync_greenhouse_board".into(),
+            description: "On-demand sync of a single Greenhouse board's postings, outside the regular cron cycle".into(),
+            parameters: vec![
+                rig_compat::ToolParam {
+                    name: "token".into(),
+                    description: "Greenhouse board token, e.g. the `token` in boards.greenhouse.io/<token>".into(),
+                    r#type: "string".into(),
+                    required: true,
+                },
+            ],
+        },
     ]
 }
 
@@ -1313,75 +3946,530 @@ fn build_enrichment_pipeline() -> rig_compat::ResultPipeline {
         })
 }
 
+/// Build the RAG context/prompt pipeline (Rig ResultPipeline pattern), same
+/// shape as [`build_enrichment_pipeline`]: each named step propagates errors,
+/// step names appear in `handle_rag`'s error response. Input is
+/// `{"query": ..., "documents": [{slug, company_name, industry_tags,
+/// tech_signals, url, last_seen, score}, ...]}`; output adds `"context"` (the
+/// rendered document list) and `"prompt"` (context + query, ready to hand to
+/// an LLM or inspect directly).
+fn build_rag_pipeline() -> rig_compat::ResultPipeline {
+    rig_compat::ResultPipeline::new()
+        // Step 1: Require at least one ranked document — an empty context
+        // would otherwise silently produce a prompt with nothing to ground it.
+        .then("validate_documents", |val| {
+            match val.get("documents").and_then(|d| d.as_array()) {
+                Some(docs) if !docs.is_empty() => Ok(val),
+                _ => Err("no ranked boards matched the query".to_string()),
+            }
+        })
+        // Step 2: Render each document into a numbered context block.
+        .then("render_context", |mut val| {
+            let docs = val["documents"].as_array().cloned().unwrap_or_default();
+            let blocks: Vec<String> = docs.iter().enumerate().map(|(i, doc)| {
+                format!(
+                    "[{}] {} (score={:.3})\nCompany: {} | Industries: {} | Tech: {} | URL: {} | Last seen: {}",
+                    i + 1,
+                    doc["slug"].as_str().unwrap_or(""),
+                    doc["score"].as_f64().unwrap_or(0.0),
+                    non_empty_or(doc["company_name"].as_str(), "unknown"),
+                    non_empty_or(doc["industry_tags"].as_str(), "none"),
+                    non_empty_or(doc["tech_signals"].as_str(), "none"),
+                    doc["url"].as_str().unwrap_or(""),
+                    non_empty_or(doc["last_seen"].as_str(), "unknown"),
+                )
+            }).collect();
+            val["context"] = serde_json::json!(blocks.join("\n\n"));
+            Ok(val)
+        })
+        // Step 3: Combine context + query into the final prompt.
+        .then("render_prompt", |mut val| {
+            let context = val["context"].as_str().unwrap_or("").to_string();
+            let query = val["query"].as_str().unwrap_or("").to_string();
+            val["prompt"] = serde_json::json!(format!(
+                "Answer the question using only the job boards listed below. \
+                 If the context doesn't contain an answer, say so.\n\n\
+                 Context:\n{context}\n\nQuestion: {query}\nAnswer:"
+            ));
+            Ok(val)
+        })
+}
+
+fn non_empty_or<'a>(value: Option<&'a str>, fallback: &'a str) -> &'a str {
+    match value {
+        Some(v) if !v.is_empty() => v,
+        _ => fallback,
+    }
+}
+
+/// Sends `prompt` to an OpenAI-compatible chat completions endpoint when the
+/// `LLM_API_KEY` secret is configured — `LLM_API_BASE` (secret or var,
+/// default `https://api.openai.com/v1`) and `LLM_MODEL` (secret or var,
+/// default `gpt-4o-mini`) are optional overrides. Returns `Ok(None)` rather
+/// than erroring when no key is configured, so `/rag` stays usable offline.
+async fn run_rag_completion(env: &Env, prompt: &str) -> Result<Option<String>> {
+    let api_key = match env.secret("LLM_API_KEY") {
+        Ok(secret) => secret.to_string(),
+        Err(_) => return Ok(None),
+    };
+    let api_base = env.secret("LLM_API_BASE").map(|s| s.to_string())
+        .or_else(|_| env.var("LLM_API_BASE").map(|v| v.to_string()))
+        .unwrap_or_else(|_| "https://api.openai.com/v1".to_string());
+    let model = env.secret("LLM_MODEL").map(|s| s.to_string())
+        .or_else(|_| env.var("LLM_MODEL").map(|v| v.to_string()))
+        .unwrap_or_else(|_| "gpt-4o-mini".to_string());
+
+    let body = serde_json::to_string(&serde_json::json!({
+        "model": model,
+        "messages": [{"role": "user", "content": prompt}],
+    })).unwrap_or_default();
+
+    let mut headers = Headers::new();
+    headers.set("Content-Type", "application/json")?;
+    headers.set("Authorization", &format!("Bearer {api_key}"))?;
+    let mut init = RequestInit::new();
+    init.with_method(Method::Post);
+    init.with_headers(headers);
+    init.with_body(Some(JsValue::from_str(&body)));
+
+    let url = format!("{}/chat/completions", api_base.trim_end_matches('/'));
+    let req = Request::new_with_init(&url, &init)?;
+    let mut resp = Fetch::Request(req).send().await?;
+    if resp.status_code() != 200 {
+        return Err(Error::RustError(format!("LLM completion endpoint returned {}", resp.status_code())));
+    }
+    let parsed: serde_json::Value = resp.json().await?;
+    Ok(parsed["choices"][0]["message"]["content"].as_str().map(str::to_string))
+}
+
 // ═══════════════════════════════════════════════════════════════════════════
 // ROUTE HANDLERS
 // ═══════════════════════════════════════════════════════════════════════════
 
 /// GET /crawl — paginated CC crawl (unchanged from v1)
-async fn handle_crawl(req: Request, ctx: RouteContext<()>) -> Result<Response> {
-    let db = ctx.env.d1("DB")?;
-    let url = req.url()?;
-    let params: HashMap<String, String> = url.query_pairs().into_owned().collect();
-
-    let crawl_id = params.get("crawl_id").cloned().unwrap_or("CC-MAIN-2025-52".into());
-    let pages_per_run: u32 = params.get("pages_per_run").and_then(|p| p.parse().ok()).unwrap_or(3);
-
-    let (total_pages, start_page, _st, mut boards_found) = match get_progress(&db, &crawl_id).await? {
+/// Run one batch of a paginated Common Crawl CDX scan (discover → upsert →
+/// auto-enrich) and return the same summary shape `GET /crawl` responds with.
+/// Factored out of `handle_crawl` so the `tasks` queue can run a crawl batch
+/// without going through an HTTP round-trip.
+async fn run_crawl_batch(db: &D1Database, crawl_id: &str, pages_per_run: u32, max_retries: u32) -> Result<serde_json::Value> {
+    let (total_pages, start_page, _st, mut boards_found) = match get_progress(db, crawl_id).await? {
         Some((_t, _c, s, f)) if s == "done" => {
-            return Response::from_json(&ApiResponse::success(serde_json::json!({
+            return Ok(serde_json::json!({
                 "crawl_id": crawl_id, "status": "done", "boards_found": f,
                 "message": "Already done. DELETE /progress?crawl_id=… to re-run."
-            })));
+            }));
         }
         Some((t, c, _, f)) => (t, c, String::from("running"), f),
-        None => (get_num_pages(&crawl_id).await?, 0, "pending".into(), 0),
+        None => (get_num_pages(crawl_id).await?, 0, "pending".into(), 0),
     };
 
-    save_progress(&db, &crawl_id, total_pages, start_page, "running", boards_found).await?;
+    save_progress(db, crawl_id, total_pages, start_page, "running", boards_found, None).await?;
     let end_page = std::cmp::min(start_page + pages_per_run, total_pages);
 
     // Fan-out: fetch all pages in this batch concurrently
     let page_futures: Vec<_> = (start_page..end_page)
         .map(|page| {
-            let cid = crawl_id.clone();
-            async move { (page, fetch_cdx_page(&cid, page).await) }
+            let cid = crawl_id.to_string();
+            async move {
+                let (result, elapsed_ms) = timed("fetch_cdx_page", fetch_cdx_page(db, &cid, page, max_retries)).await;
+                (page, result, elapsed_ms)
+            }
         })
         .collect();
     let mut page_fetch_results = join_all(page_futures).await;
-    page_fetch_results.sort_by_key(|(page, _)| *page);
+    page_fetch_results.sort_by_key(|(page, _, _)| *page);
 
-    // Collect boards from all pages before writing — fail fast on any CDX error
+    // Collect boards from all pages before writing. A page whose fetch
+    // already exhausted `fetch_with_retry`'s attempts is tolerated — one
+    // flaky CC node shouldn't kill the whole batch — while fewer than
+    // `PAGE_ERROR_BUDGET` pages are retry-exhausted; at that many the batch
+    // aborts rather than silently under-crawling every run.
     let mut all_new_boards: Vec<AshbyBoard> = Vec::new();
     let mut page_results = Vec::new();
-    for (page, result) in page_fetch_results {
-        let boards = result?;
-        page_results.push(serde_json::json!({ "page": page, "discovered": boards.len() }));
-        all_new_boards.extend(boards);
+    let mut invalid_cdx_count = 0u32;
+    let mut cdx_fetch_ms = 0.0f64;
+    let mut page_errors: Vec<(u32, String)> = Vec::new();
+    for (page, result, elapsed_ms) in page_fetch_results {
+        cdx_fetch_ms += elapsed_ms;
+        match result {
+            Ok((boards, invalid)) => {
+                page_results.push(serde_json::json!({ "page": page, "discovered": boards.len(), "invalid": invalid }));
+                invalid_cdx_count += invalid;
+                all_new_boards.extend(boards);
+            }
+            Err(e) => {
+                console_log!("[cdx] page {} failed after retries, tolerating: {:?}", page, e);
+                page_results.push(serde_json::json!({ "page": page, "error": format!("{e:?}") }));
+                page_errors.push((page, format!("{e:?}")));
+            }
+        }
+    }
+    if page_errors.len() >= PAGE_ERROR_BUDGET {
+        return Err(Error::RustError(format!(
+            "{} of {} pages failed after exhausting retries, aborting batch: {:?}",
+            page_errors.len(), end_page - start_page, page_errors
+        )));
     }
 
     // Single combined upsert for all pages (fewer D1 round-trips than per-page)
-    let upserted = upsert_boards(&db, &all_new_boards).await?;
-    boards_found += upserted as u32;
+    let (upserted, upsert_ms) = timed("upsert_boards", upsert_boards(db, &all_new_boards)).await;
+    let upserted = upserted?;
+    boards_found += upserted.written as u32;
 
     // Auto-enrich: run SlugExtractor + ResultPipeline on this batch, persist to D1
-    let enriched = auto_enrich_boards(&db, &all_new_boards).await.unwrap_or(0);
+    let (enriched, enrich_ms) = timed("auto_enrich_boards", auto_enrich_boards(db, &all_new_boards)).await;
+    let enriched = enriched.unwrap_or_default();
 
     let status = if end_page >= total_pages { "done" } else { "running" };
-    save_progress(&db, &crawl_id, total_pages, end_page, status, boards_found).await?;
-
-    Response::from_json(&ApiResponse::success(serde_json::json!({
+    // Re-tune next run's page count off what this batch actually measured —
+    // see `next_batch_size`. Persisted regardless of caller (`/crawl`, a
+    // `TaskKind::Crawl` task, or `CdxCrawlWorker::step`) so all of them
+    // benefit from the same adaptive estimate.
+    let pages_fetched = (end_page - start_page) as usize;
+    let next_pages_per_run = next_batch_size(
+        pages_fetched, cdx_fetch_ms, ADAPTIVE_PAGE_BUDGET_MS, ADAPTIVE_PAGES_MIN, ADAPTIVE_PAGES_MAX,
+    );
+    // Feed this run's cost back into the subrequest-budget planner (see
+    // `plan_subrequest_caps`) — `fetch_cdx_page` issues exactly one HTTP
+    // request per page today, so the observed ratio is 1.0, but this keeps
+    // `avg_subreq_per_cdx_page` accurate if that ever changes.
+    if pages_fetched > 0 {
+        if let Err(e) = update_run_stat_ema(db, "avg_subreq_per_cdx_page", 1.0).await {
+            console_log!("[cdx-crawl] run_stats update failed (non-fatal): {:?}", e);
+        }
+    }
+    let timings = serde_json::json!({
+        "cdx_fetch_ms": cdx_fetch_ms.round(),
+        "d1_write_ms": (upsert_ms + enrich_ms).round(),
+        "pages_per_run": next_pages_per_run,
+        // Actuals for this run, read back by `cron_handler_inner` when it
+        // records `run_metrics` — separate from `pages_per_run` above, which
+        // is the *next* run's planned size, not what this one did.
+        "pages_fetched": pages_fetched,
+        "boards_enriched": enriched.written,
+    });
+    save_progress(db, crawl_id, total_pages, end_page, status, boards_found, Some(&timings)).await?;
+
+    Ok(serde_json::json!({
         "crawl_id": crawl_id, "status": status, "total_pages": total_pages,
         "pages_processed": format!("{start_page}-{}", end_page.saturating_sub(1)),
         "next_page": if status == "done" { None } else { Some(end_page) },
         "total_boards_found": boards_found,
-        "upserted_this_run": upserted,
-        "enriched_this_run": enriched,
+        "upserted_this_run": upserted.written,
+        "upsert_skipped_this_run": upserted.skipped,
+        "upsert_failed_this_run": upserted.failed.len(),
+        "enriched_this_run": enriched.written,
+        "enrich_skipped_this_run": enriched.skipped,
+        "enrich_failed_this_run": enriched.failed.len(),
+        "invalid_cdx_this_run": invalid_cdx_count,
+        "page_errors_this_run": page_errors.len(),
         "page_results": page_results,
+        "timings": timings,
+    }))
+}
+
+async fn handle_crawl(req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    let db = ctx.env.d1("DB")?;
+    let url = req.url()?;
+    let params: HashMap<String, String> = url.query_pairs().into_owned().collect();
+
+    let crawl_id = params.get("crawl_id").cloned().unwrap_or("CC-MAIN-2025-52".into());
+    let pages_per_run: u32 = params.get("pages_per_run").and_then(|p| p.parse().ok()).unwrap_or(3);
+    let max_retries: u32 = params.get("max_retries").and_then(|p| p.parse().ok()).unwrap_or(RETRY_MAX_ATTEMPTS);
+
+    match run_crawl_batch(&db, &crawl_id, pages_per_run, max_retries).await {
+        Ok(summary) => Response::from_json(&ApiResponse::success(summary)),
+        Err(e) => error_response(classify_error(&e), &format!("{e:?}")),
+    }
+}
+
+/// One batch of Phase 2 (Ashby + Greenhouse job-sync): pull up to
+/// `boards_per_run` pending slugs from each provider and sync them
+/// concurrently via `sync_provider_jobs`, then re-tune `boards_per_run`
+/// off what this batch measured — self-contained the same way
+/// `run_crawl_batch` is for Phase 1, so it's callable standalone or as one
+/// `JobSyncWorker::step()` tick.
+async fn run_job_sync_batch(db: &D1Database, boards_per_run: usize) -> Result<serde_json::Value> {
+    let (slugs_result, gh_slugs_result) = join(
+        get_company_slugs(db, boards_per_run),
+        get_greenhouse_company_slugs(db, boards_per_run),
+    ).await;
+    let slugs = slugs_result.unwrap_or_default();
+    let gh_slugs = gh_slugs_result.unwrap_or_default();
+
+    let ((ashby_synced, ashby_fetch_ms), (gh_synced, gh_fetch_ms)) = join(
+        sync_provider_jobs::<AshbyCronSync>(db, slugs.clone()),
+        sync_provider_jobs::<GreenhouseCronSync>(db, gh_slugs.clone()),
+    ).await;
+    let jobs_synced = ashby_synced + gh_synced;
+    let boards_processed = slugs.len() + gh_slugs.len();
+
+    let next_boards_per_run = next_batch_size(
+        boards_processed, ashby_fetch_ms + gh_fetch_ms, ADAPTIVE_BOARDS_BUDGET_MS, ADAPTIVE_BOARDS_MIN, ADAPTIVE_BOARDS_MAX,
+    );
+    // Feed this run's cost back into the subrequest-budget planner (see
+    // `plan_subrequest_caps`) — both providers' fetch functions issue one
+    // HTTP request per board today (neither paginates within a single
+    // fetch), so the observed ratio is 1.0, but `avg_subreq_per_board` will
+    // track it honestly if Ashby pagination is ever added.
+    if boards_processed > 0 {
+        if let Err(e) = update_run_stat_ema(db, "avg_subreq_per_board", 1.0).await {
+            console_log!("[job-sync] run_stats update failed (non-fatal): {:?}", e);
+        }
+    }
+    save_progress(db, "job-sync", 0, 0, "tuning", 0, Some(&serde_json::json!({
+        "ashby_fetch_ms": ashby_fetch_ms.round(),
+        "greenhouse_fetch_ms": gh_fetch_ms.round(),
+        "boards_per_run": next_boards_per_run,
+        // Actuals for this run — see the matching comment in
+        // `run_crawl_batch`'s `timings`.
+        "boards_processed": boards_processed,
+        "jobs_synced": jobs_synced,
+    }))).await?;
+
+    Ok(serde_json::json!({
+        "boards_processed": boards_processed,
+        "ashby_boards": slugs.len(),
+        "greenhouse_boards": gh_slugs.len(),
+        "jobs_synced": jobs_synced,
+        "next_boards_per_run": next_boards_per_run,
+    }))
+}
+
+/// Resolves the CDX crawl currently being worked — the newest Common Crawl
+/// index, falling back to a pinned one if the collinfo API is unreachable.
+/// Shared by `CdxCrawlWorker::step` and `cron_handler_inner`'s `run_metrics`
+/// recording so both agree on which `crawl_progress` row is "the" crawl.
+async fn current_crawl_id() -> String {
+    match list_cc_indexes().await {
+        Ok(indexes) if !indexes.is_empty() => indexes[0].clone(),
+        Ok(_) => "CC-MAIN-2025-52".to_string(),
+        Err(e) => {
+            console_log!("[cdx-crawl] CC index list failed: {:?}, using fallback", e);
+            "CC-MAIN-2025-52".to_string()
+        }
+    }
+}
+
+/// Phase 1 as a `workers::Worker` — re-resolves the latest CC index and
+/// its resume point from `crawl_progress` every tick (no state of its own
+/// to carry between ticks), then delegates one page-batch to
+/// `run_crawl_batch`.
+struct CdxCrawlWorker;
+
+impl workers::Worker for CdxCrawlWorker {
+    fn name(&self) -> &'static str { "cdx-crawl" }
+
+    async fn step(&mut self, db: &D1Database) -> Result<workers::WorkerState> {
+        let crawl_id = current_crawl_id().await;
+
+        if let Some((_, _, status, found)) = get_progress(db, &crawl_id).await? {
+            if status == "done" {
+                console_log!("[cdx-crawl] {} already done ({} boards)", crawl_id, found);
+                return Ok(workers::WorkerState::Done);
+            }
+        }
+
+        let prev_timings = get_progress_timings(db, &crawl_id).await.unwrap_or(None);
+        let (pages_cap, _) = plan_subrequest_caps(db).await;
+        let pages_per_run = read_adaptive_size(prev_timings.as_ref(), "pages_per_run", pages_cap).min(pages_cap) as u32;
+
+        let summary = run_crawl_batch(db, &crawl_id, pages_per_run, RETRY_MAX_ATTEMPTS).await?;
+        if summary["status"].as_str() == Some("done") {
+            Ok(workers::WorkerState::Done)
+        } else {
+            Ok(workers::WorkerState::Busy)
+        }
+    }
+}
+
+/// Phase 2 as a `workers::Worker` — the job-sync cycle never finishes (it
+/// keeps re-syncing the oldest-synced boards each tick), so it only ever
+/// reports `Busy`/`Idle`/`Errored`, never `Done`.
+struct JobSyncWorker;
+
+impl workers::Worker for JobSyncWorker {
+    fn name(&self) -> &'static str { "job-sync" }
+
+    async fn step(&mut self, db: &D1Database) -> Result<workers::WorkerState> {
+        let job_sync_timings = get_progress_timings(db, "job-sync").await.unwrap_or(None);
+        let (_, boards_cap) = plan_subrequest_caps(db).await;
+        let boards_per_run = read_adaptive_size(job_sync_timings.as_ref(), "boards_per_run", boards_cap).min(boards_cap);
+
+        let summary = run_job_sync_batch(db, boards_per_run).await?;
+        if summary["boards_processed"].as_u64() == Some(0) {
+            Ok(workers::WorkerState::Idle(60.0))
+        } else {
+            Ok(workers::WorkerState::Busy)
+        }
+    }
+}
+
+/// GET /workers — one row per background phase (`cdx-crawl`, `job-sync`):
+/// status (`active`/`idle`/`done`/`dead`), control (`run`/`paused`/
+/// `cancelled`), cumulative items processed, last error, and last-tick time.
+async fn handle_list_workers(_req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    let db = ctx.env.d1("DB")?;
+    let rows = workers::WorkerManager::list(&db).await?;
+    Response::from_json(&ApiResponse::success(serde_json::json!({
+        "count": rows.len(),
+        "workers": rows,
+    })))
+}
+
+/// POST /workers/:name/{pause,resume,cancel} — flips the named worker's
+/// persisted control flag; the next cron tick's `WorkerManager::tick` call
+/// honors it before stepping.
+async fn handle_worker_action(_req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    let db = ctx.env.d1("DB")?;
+    let name = match ctx.param("name") {
+        Some(n) => n.clone(),
+        None => return error_response(ErrorCode::InvalidInput, "worker name required"),
+    };
+    let action = match ctx.param("action") {
+        Some(a) => a.clone(),
+        None => return error_response(ErrorCode::InvalidInput, "action required"),
+    };
+    let control = match action.as_str() {
+        "pause" => workers::WorkerControl::Paused,
+        "resume" => workers::WorkerControl::Run,
+        "cancel" => workers::WorkerControl::Cancelled,
+        other => return error_response(ErrorCode::InvalidInput, &format!("unknown action '{other}' (expected pause/resume/cancel)")),
+    };
+    workers::WorkerManager::set_control(&db, &name, control).await?;
+    Response::from_json(&ApiResponse::success(serde_json::json!({
+        "worker": name,
+        "control": action,
     })))
 }
 
-/// GET /search?q=fintech&top_n=10 — Okapi BM25 ranking over the board corpus.
-/// Enriched company_name and industry_tags are included in the index when available.
+/// Resolve a `near:` query's place name to a point — gazetteer first, falling
+/// back to a best-effort external geocode. `None` means the place couldn't be
+/// resolved at all, in which case the caller drops the geo clause rather than
+/// erroring (consistent with the rest of this pipeline's best-effort stance on
+/// location enrichment — a bad `near:` shouldn't 400 a search).
+async fn resolve_geo_center(place: &str) -> Option<geo::GeoPoint> {
+    let normalized = geo::parse_location(place);
+    if normalized.point.is_some() {
+        return normalized.point;
+    }
+    geo::geocode_external(place).await
+}
+
+/// `external_id`s of jobs with at least one `job_locations` row inside the
+/// bounding box around `center`, refined to the exact `radius_km` via
+/// haversine in Rust (the bounding box is a cheap SQL pre-filter only — it can
+/// include corners up to ~1.4x the radius away).
+async fn jobs_within_radius(db: &D1Database, center: geo::GeoPoint, radius_km: f64) -> Result<HashSet<String>> {
+    let (min_lat, max_lat, min_lng, max_lng) = geo::bounding_box(center, radius_km);
+    let rows = db.prepare(
+        "SELECT external_id, lat, lng FROM job_locations
+         WHERE lat IS NOT NULL AND lng IS NOT NULL
+           AND lat BETWEEN ?1 AND ?2 AND lng BETWEEN ?3 AND ?4"
+    ).bind(&[min_lat.into(), max_lat.into(), min_lng.into(), max_lng.into()])?
+        .all().await?.results::<serde_json::Value>()?;
+
+    let mut matched = HashSet::new();
+    for row in rows {
+        let (Some(lat), Some(lng)) = (row["lat"].as_f64(), row["lng"].as_f64()) else { continue };
+        let point = geo::GeoPoint { lat, lng };
+        if geo::haversine_km(center, point) <= radius_km {
+            if let Some(id) = row["external_id"].as_str() {
+                matched.insert(id.to_string());
+            }
+        }
+    }
+    Ok(matched)
+}
+
+/// Run a field-filtered job search: `query_parser::compile_where` turns the
+/// typed filters into a parameterized `WHERE` clause over `jobs`, ANDed with
+/// a `LIKE` group per free-text term (title/company_name/location) and, when
+/// `parsed.geo` is present, an `external_id IN (...)` restriction to jobs with
+/// a `job_locations` row inside the requested radius (chunked the same way as
+/// `get_job_bodies` — D1 has no array-bind support). Closed postings (see
+/// `ats::reconcile_closed_jobs`) are excluded unless `include_closed` is set.
+async fn search_jobs_filtered(
+    db: &D1Database,
+    parsed: &query_parser::ParsedQuery,
+    top_n: usize,
+    include_closed: bool,
+) -> Result<Vec<serde_json::Value>> {
+    let (filter_sql, mut binds) = query_parser::compile_where(&parsed.filters);
+    let mut clauses = Vec::new();
+    if !include_closed {
+        clauses.push("status != 'closed'".to_string());
+    }
+    if !filter_sql.is_empty() {
+        clauses.push(filter_sql);
+    }
+    for term in &parsed.terms {
+        clauses.push("(title LIKE ? OR company_name LIKE ? OR location LIKE ?)".to_string());
+        let pattern: JsValue = format!("%{}%", term).into();
+        binds.push(pattern.clone());
+        binds.push(pattern.clone());
+        binds.push(pattern);
+    }
+
+    if let Some(geo_query) = &parsed.geo {
+        if let Some(center) = resolve_geo_center(&geo_query.place).await {
+            let matches = jobs_within_radius(db, center, geo_query.radius_km).await?;
+            if matches.is_empty() {
+                return Ok(Vec::new());
+            }
+            const CHUNK_SIZE: usize = 100;
+            let ids: Vec<&String> = matches.iter().collect();
+            let mut id_groups = Vec::new();
+            for chunk in ids.chunks(CHUNK_SIZE) {
+                let placeholders: Vec<String> = chunk.iter().map(|_| "?".to_string()).collect();
+                id_groups.push(format!("external_id IN ({})", placeholders.join(", ")));
+                for id in chunk {
+                    binds.push((*id).clone().into());
+                }
+            }
+            clauses.push(format!("({})", id_groups.join(" OR ")));
+        }
+        // Unresolvable place name: drop the geo clause, fall back to the
+        // filters/terms already gathered rather than erroring out.
+    }
+
+    let where_sql = if clauses.is_empty() { "1=1".to_string() } else { clauses.join(" AND ") };
+
+    let sql = format!(
+        "SELECT external_id, source_kind, title, company_key, company_name, url,
+                location, workplace_type, country, salary_min, salary_max, status
+         FROM jobs WHERE {where_sql} ORDER BY updated_at DESC LIMIT ?"
+    );
+    binds.push((top_n as f64).into());
+
+    db.prepare(&sql).bind(&binds)?.all().await?.results::<serde_json::Value>()
+}
+
+/// GET /search?q=fintech&top_n=10 — ranking over the board corpus (engine
+/// picked by `mode`, below), or a field-filtered `jobs` lookup when `q`
+/// carries `field:value` clauses
+/// (e.g. `rust workplace_type:remote location:"Berlin" salary_min:>100000`,
+/// see [`query_parser`]). A query with no filters takes the `mode` ranking
+/// path described below.
+/// Enriched company_name, industry_tags, tech_signals and size_signal are
+/// included in the index when available.
+/// GET /search?q=...&top_n=10&mode=hybrid|lexical|semantic&filter=...&facets=...&max_typos=...
+///
+/// `mode` picks the ranking engine: `lexical` is pure BM25, `semantic` is
+/// pure vector similarity, `hybrid` (the default) fuses both via Reciprocal
+/// Rank Fusion and annotates each result with both rankers' own rank/score
+/// in `metadata`. `filter` is a facet-filter grammar (see
+/// [`parse_facet_filter`]) over the `industry`/`tech`/`size` enrichment
+/// columns, applied to the full ranking before `top_n` truncation so a
+/// narrow filter doesn't just empty out a small unfiltered page. `facets`
+/// (comma-separated `industry`/`tech`/`size`) returns a `facetDistribution`
+/// computed over that same filtered set. `max_typos` caps the per-length
+/// fuzzy-match budget described on [`rig_compat::Bm25Index::rank_with_max_typos`],
+/// or disables fuzzy matching entirely with `max_typos=0`; omitted, the
+/// default per-length budget applies. Facet/geo queries (anything
+/// `query_parser` recognizes) bypass all of this and hit the `jobs` table
+/// directly, same as before — and exclude closed postings unless
+/// `?include_closed=1` is passed.
 async fn handle_search(req: Request, ctx: RouteContext<()>) -> Result<Response> {
     let db = ctx.env.d1("DB")?;
     let url = req.url()?;
@@ -1389,18 +4477,180 @@ async fn handle_search(req: Request, ctx: RouteContext<()>) -> Result<Response>
 
     let query = match params.get("q") {
         Some(q) if !q.is_empty() => q.clone(),
-        _ => return error_response("?q= query parameter required"),
+        _ => return error_response(ErrorCode::InvalidInput, "?q= query parameter required"),
     };
     let top_n: usize = params.get("top_n").and_then(|n| n.parse().ok()).unwrap_or(10);
+    let mode = params.get("mode").map(String::as_str).unwrap_or("hybrid");
+    let max_typos: Option<usize> = params.get("max_typos").and_then(|n| n.parse().ok());
+    let filter_predicate = params.get("filter").and_then(|f| parse_facet_filter(f));
+    let facet_fields: Vec<&str> = params.get("facets")
+        .map(|s| s.split(',').filter_map(|f| facet_field_key(f.trim())).collect())
+        .unwrap_or_default();
+    // Closed postings (see `ats::reconcile_closed_jobs`) are dropped from the
+    // `jobs`-table facet/geo path by default. The BM25/hybrid paths below
+    // rank the board corpus instead, where the same flag keeps boards with
+    // `lifecycle_state='dead'` out (see `fetch_search_corpus`).
+    let include_closed = params.get("include_closed").map(String::as_str) == Some("1");
+
+    let parsed = query_parser::parse_query(&query);
+    if !parsed.filters.is_empty() || parsed.geo.is_some() {
+        let results = search_jobs_filtered(&db, &parsed, top_n, include_closed).await?;
+        return Response::from_json(&ApiResponse::success(serde_json::json!({
+            "query": query,
+            "engine": "jobs table facet filter (query_parser) + free-text LIKE",
+            "filters_applied": parsed.filters.len(),
+            "geo_applied": parsed.geo.is_some(),
+            "result_count": results.len(),
+            "results": results,
+        })));
+    }
+
+    // A filter/facet request needs the full ranking, not just `top_n`, so
+    // narrowing by filter doesn't just shrink an already-truncated page.
+    let rank_n_for = |index_len: usize| {
+        if filter_predicate.is_some() || !facet_fields.is_empty() { index_len.max(1) } else { top_n }
+    };
+
+    if mode == "lexical" {
+        let index = build_bm25_index(&db, include_closed).await?;
+        let mut results = index.rank_with_snippets_and_max_typos(&query, rank_n_for(index.len()), rig_compat::HighlightTags::default(), max_typos);
+        if let Some(pred) = &filter_predicate {
+            results = rig_compat::filter_results(results, pred);
+        }
+        let facet_distribution = (!facet_fields.is_empty()).then(|| rig_compat::facets(&results, &facet_fields));
+        results.truncate(top_n);
+        return Response::from_json(&ApiResponse::success(serde_json::json!({
+            "query": query,
+            "mode": "lexical",
+            "engine": "rig_compat::Bm25Index (Okapi BM25, k1=1.5, b=0.75)",
+            "index_size": index.len(),
+            "results": results,
+            "facetDistribution": facet_distribution,
+        })));
+    }
+
+    // hybrid/semantic both run over the fused index so `semantic` can still
+    // report the BM25 sub-score a document would have gotten, for comparison.
+    let index = build_hybrid_index(&db, include_closed).await?;
+    let corpus_size = index.len().max(1);
+    let keyword_ranked = index.keyword_rank_with_max_typos(&query, corpus_size, max_typos);
+    let vector_ranked = index.vector_rank_with_max_typos(&query, corpus_size, max_typos);
+    let keyword_by_id: HashMap<&str, (usize, f64)> = keyword_ranked
+        .iter().enumerate()
+        .map(|(rank, r)| (r.id.as_str(), (rank + 1, r.score)))
+        .collect();
+    let vector_by_id: HashMap<&str, (usize, f64)> = vector_ranked
+        .iter().enumerate()
+        .map(|(rank, r)| (r.id.as_str(), (rank + 1, r.score)))
+        .collect();
+
+    let mut ranked = if mode == "semantic" {
+        index.vector_rank_with_max_typos(&query, rank_n_for(corpus_size), max_typos)
+    } else {
+        index.search_with_max_typos(&query, rank_n_for(corpus_size), max_typos)
+    };
+    if let Some(pred) = &filter_predicate {
+        ranked = rig_compat::filter_results(ranked, pred);
+    }
+    let facet_distribution = (!facet_fields.is_empty()).then(|| rig_compat::facets(&ranked, &facet_fields));
+    ranked.truncate(top_n);
+
+    let results: Vec<serde_json::Value> = ranked.into_iter().map(|mut r| {
+        if let Some((rank, score)) = keyword_by_id.get(r.id.as_str()) {
+            r.metadata.insert("bm25_rank".into(), rank.to_string());
+            r.metadata.insert("bm25_score".into(), score.to_string());
+        }
+        if let Some((rank, score)) = vector_by_id.get(r.id.as_str()) {
+            r.metadata.insert("vector_rank".into(), rank.to_string());
+            r.metadata.insert("vector_score".into(), score.to_string());
+        }
+        serde_json::to_value(&r).unwrap_or(serde_json::Value::Null)
+    }).collect();
 
-    let index = build_bm25_index(&db).await?;
-    let results = index.rank(&query, top_n);
+    let engine = if mode == "semantic" {
+        "rig_compat::InMemoryVectorStore (TF-IDF cosine similarity — no Workers AI binding in this Worker)"
+    } else {
+        "rig_compat::HybridIndex (BM25 + TF-IDF vector, fused via Reciprocal Rank Fusion, k=60)"
+    };
 
     Response::from_json(&ApiResponse::success(serde_json::json!({
         "query": query,
-        "engine": "rig_compat::Bm25Index (Okapi BM25, k1=1.5, b=0.75)",
+        "mode": mode,
+        "engine": engine,
         "index_size": index.len(),
         "results": results,
+        "facetDistribution": facet_distribution,
+    })))
+}
+
+/// GET /rag?q=...&top_n=5&mode=hybrid|lexical|semantic
+///
+/// Retrieval-augmented layer on top of `/search`: ranks boards the same way
+/// `handle_search` does, pulls each top-K board's enrichment metadata, and
+/// assembles a context document + prompt via [`build_rag_pipeline`] (named
+/// steps so a broken stage reports exactly where, same as
+/// `build_enrichment_pipeline`). When an `LLM_API_KEY` secret is configured
+/// (see [`run_rag_completion`]) the prompt is also sent to a chat-completions
+/// endpoint and `answer` is populated — otherwise `answer` is `null` and the
+/// endpoint still returns the raw context/prompt, so it stays usable offline.
+async fn handle_rag(req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    let db = ctx.env.d1("DB")?;
+    let url = req.url()?;
+    let params: HashMap<String, String> = url.query_pairs().into_owned().collect();
+
+    let query = match params.get("q") {
+        Some(q) if !q.is_empty() => q.clone(),
+        _ => return error_response(ErrorCode::InvalidInput, "?q= query parameter required"),
+    };
+    let top_n: usize = params.get("top_n").and_then(|n| n.parse().ok()).unwrap_or(5);
+    let mode = params.get("mode").map(String::as_str).unwrap_or("hybrid");
+    // Same board corpus as `handle_search` — ranks boards the same way, so it
+    // respects the same lifecycle gate and override.
+    let include_closed = params.get("include_closed").map(String::as_str) == Some("1");
+
+    let index = build_hybrid_index(&db, include_closed).await?;
+    let ranked = if mode == "lexical" {
+        index.keyword_rank(&query, top_n)
+    } else if mode == "semantic" {
+        index.vector_rank(&query, top_n)
+    } else {
+        index.search(&query, top_n)
+    };
+
+    let documents: Vec<serde_json::Value> = ranked.iter().map(|r| serde_json::json!({
+        "slug": r.id,
+        "score": r.score,
+        "company_name": r.metadata.get("company_name").cloned().unwrap_or_default(),
+        "industry_tags": r.metadata.get("industry_tags").cloned().unwrap_or_default(),
+        "tech_signals": r.metadata.get("tech_signals").cloned().unwrap_or_default(),
+        "url": r.metadata.get("url").cloned().unwrap_or_default(),
+        "last_seen": r.metadata.get("last_seen").cloned().unwrap_or_default(),
+    })).collect();
+
+    let pipeline = build_rag_pipeline();
+    let assembled = match pipeline.run(serde_json::json!({ "query": query, "documents": documents })) {
+        Ok(v) => v,
+        Err((step, msg)) => return error_response(ErrorCode::ParseError, &format!("RAG pipeline failed at '{step}': {msg}")),
+    };
+    let context = assembled["context"].as_str().unwrap_or("").to_string();
+    let prompt = assembled["prompt"].as_str().unwrap_or("").to_string();
+
+    let answer = match run_rag_completion(&ctx.env, &prompt).await {
+        Ok(answer) => answer,
+        Err(e) => {
+            console_log!("[rag] completion skipped: {:?}", e);
+            None
+        }
+    };
+
+    Response::from_json(&ApiResponse::success(serde_json::json!({
+        "query": query,
+        "mode": mode,
+        "document_count": documents.len(),
+        "documents": documents,
+        "context": context,
+        "prompt": prompt,
+        "answer": answer,
     })))
 }
 
@@ -1412,7 +4662,7 @@ async fn handle_enrich(req: Request, ctx: RouteContext<()>) -> Result<Response>
 
     let slug = match params.get("slug") {
         Some(s) if !s.is_empty() => s.clone(),
-        _ => return error_response("?slug= parameter required"),
+        _ => return error_response(ErrorCode::InvalidInput, "?slug= parameter required"),
     };
 
     let row = db
@@ -1423,13 +4673,13 @@ async fn handle_enrich(req: Request, ctx: RouteContext<()>) -> Result<Response>
 
     let row = match row {
         Some(r) => r,
-        None => return error_response("Board not found"),
+        None => return error_response(ErrorCode::NotFound, "Board not found"),
     };
 
     let pipeline = build_enrichment_pipeline();
     let enriched = match pipeline.run(row) {
         Ok(v) => v,
-        Err((step, msg)) => return error_response(&format!("Pipeline failed at '{step}': {msg}")),
+        Err((step, msg)) => return error_response(ErrorCode::ParseError, &format!("Pipeline failed at '{step}': {msg}")),
     };
 
     Response::from_json(&ApiResponse::success(serde_json::json!({
@@ -1487,6 +4737,139 @@ async fn handle_enrich_all(req: Request, ctx: RouteContext<()>) -> Result<Respon
     })))
 }
 
+/// Hard cap on `POST /batch`'s op array — mirrors `PAGE_ERROR_BUDGET`'s role
+/// of bounding an otherwise-unbounded fan-out: without it, a large `ops`
+/// array turns one request into that many concurrent `join_all` branches,
+/// each (pre-`BATCH_OPS_MAX`) rebuilding the full search/rank corpus from D1.
+const BATCH_OPS_MAX: usize = 20;
+
+/// POST /batch — body: JSON array of operations, e.g.
+/// `[{"op":"search","q":"rust","top_n":5},{"op":"rank","q":"staff engineer"},{"op":"enrich","slug":"figma"}]`.
+/// Capped at `BATCH_OPS_MAX` ops per request.
+///
+/// Runs every op concurrently via `join_all` (same pattern `handle_crawl`
+/// uses for its page fetches) and returns a parallel `results` array, each
+/// entry tagged with its own `ok`/`error` so one failing op doesn't abort
+/// the rest. Op names mirror `build_tool_registry`'s `search_boards`/
+/// `rank_boards`/`enrich_board` entries, but dispatch straight to the real
+/// search/rank/enrichment logic rather than the registry's HTTP-forwarding
+/// closures — a batch caller wants the actual result in this one
+/// round-trip, not a description of which GET to make next.
+///
+/// `search`/`rank` ops share one `build_hybrid_index`/`build_bm25_index`
+/// call for the whole request instead of each op rebuilding the corpus from
+/// D1 independently — those builds are a full `fetch_search_corpus` read
+/// plus a from-scratch index rebuild, so doing it once per op (as opposed
+/// to once per request) turned a batch of N search ops into N redundant
+/// full-corpus rebuilds fanned out concurrently. Because the index is
+/// shared, `include_closed` is too: if any op in the batch sets
+/// `"include_closed":true`, the whole request's shared index is built with
+/// closed boards included.
+async fn handle_batch(mut req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    let db = ctx.env.d1("DB")?;
+    let ops: Vec<serde_json::Value> = match req.json().await {
+        Ok(ops) => ops,
+        Err(_) => return error_response(ErrorCode::InvalidInput, "Body must be a JSON array of operations"),
+    };
+    if ops.len() > BATCH_OPS_MAX {
+        return error_response(
+            ErrorCode::InvalidInput,
+            &format!("Too many ops: {} (max {BATCH_OPS_MAX} per /batch request)", ops.len()),
+        );
+    }
+
+    let op_kind = |op: &serde_json::Value| op.get("op").and_then(|v| v.as_str()).unwrap_or("").to_string();
+    // Each op may pass its own `"include_closed":true`, but the index is
+    // built once for the whole request (see the doc comment above) — so if
+    // any op in the batch asks for closed boards, the one shared index is
+    // built with them included, same as the rest would see it too.
+    let op_include_closed = |op: &serde_json::Value| op.get("include_closed").and_then(|v| v.as_bool()).unwrap_or(false);
+    let include_closed = ops.iter().any(op_include_closed);
+    let hybrid_index = if ops.iter().any(|op| op_kind(op) == "search") {
+        Some(build_hybrid_index(&db, include_closed).await?)
+    } else {
+        None
+    };
+    let bm25_index = if ops.iter().any(|op| op_kind(op) == "rank") {
+        Some(build_bm25_index(&db, include_closed).await?)
+    } else {
+        None
+    };
+
+    let results = join_all(
+        ops.into_iter()
+            .map(|op| run_batch_op(&db, op, hybrid_index.as_ref(), bm25_index.as_ref())),
+    ).await;
+
+    Response::from_json(&ApiResponse::success(serde_json::json!({
+        "count": results.len(),
+        "results": results,
+    })))
+}
+
+/// Dispatch a single `/batch` operation by its `op` field. Never returns an
+/// `Err` itself — failures (unknown op, missing arg, lookup miss) are
+/// folded into the per-op `{"ok": false, "error": ...}` shape so `join_all`
+/// can run every op unconditionally and a caller still gets a result for
+/// each array entry it sent. `hybrid_index`/`bm25_index` are built once by
+/// `handle_batch` for the whole request, not per op.
+async fn run_batch_op(
+    db: &D1Database,
+    op: serde_json::Value,
+    hybrid_index: Option<&rig_compat::HybridIndex>,
+    bm25_index: Option<&rig_compat::Bm25Index>,
+) -> serde_json::Value {
+    let kind = op.get("op").and_then(|v| v.as_str()).unwrap_or("").to_string();
+    let result = match kind.as_str() {
+        "search" => run_batch_search(hybrid_index, &op).await,
+        "rank" => run_batch_rank(bm25_index, &op).await,
+        "enrich" => run_batch_enrich(db, &op).await,
+        other => Err(format!("Unknown op: '{other}'")),
+    };
+    match result {
+        Ok(value) => serde_json::json!({ "op": kind, "ok": true, "result": value }),
+        Err(e) => serde_json::json!({ "op": kind, "ok": false, "error": e }),
+    }
+}
+
+async fn run_batch_search(index: Option<&rig_compat::HybridIndex>, op: &serde_json::Value) -> std::result::Result<serde_json::Value, String> {
+    let query = op.get("q").and_then(|v| v.as_str()).ok_or("Missing required arg: q")?;
+    let top_n = op.get("top_n").and_then(|v| v.as_u64()).unwrap_or(10) as usize;
+    let max_typos = op.get("max_typos").and_then(|v| v.as_u64()).map(|n| n as usize);
+    let filter_predicate = op.get("filter").and_then(|v| v.as_str()).and_then(parse_facet_filter);
+    let index = index.ok_or("search index unavailable")?;
+
+    let mut ranked = index.search_with_max_typos(query, index.len().max(1), max_typos);
+    if let Some(pred) = &filter_predicate {
+        ranked = rig_compat::filter_results(ranked, pred);
+    }
+    ranked.truncate(top_n);
+    Ok(serde_json::json!({ "query": query, "index_size": index.len(), "results": ranked }))
+}
+
+async fn run_batch_rank(index: Option<&rig_compat::Bm25Index>, op: &serde_json::Value) -> std::result::Result<serde_json::Value, String> {
+    let query = op.get("q").and_then(|v| v.as_str()).ok_or("Missing required arg: q")?;
+    let top_n = op.get("top_n").and_then(|v| v.as_u64()).unwrap_or(10) as usize;
+    let max_typos = op.get("max_typos").and_then(|v| v.as_u64()).map(|n| n as usize);
+    let index = index.ok_or("rank index unavailable")?;
+    let results = index.rank_with_snippets_and_max_typos(query, top_n, rig_compat::HighlightTags::default(), max_typos);
+    Ok(serde_json::json!({ "query": query, "index_size": index.len(), "results": results }))
+}
+
+async fn run_batch_enrich(db: &D1Database, op: &serde_json::Value) -> std::result::Result<serde_json::Value, String> {
+    let slug = op.get("slug").and_then(|v| v.as_str()).ok_or("Missing required arg: slug")?;
+    let row = db
+        .prepare("SELECT key as slug, website as url, created_at as first_seen, last_seen_capture_timestamp as last_seen, last_seen_crawl_id as crawl_id, NULL as http_status FROM companies WHERE key = ?1")
+        .bind(&[slug.into()]).map_err(|e| e.to_string())?
+        .first::<serde_json::Value>(None).await.map_err(|e| e.to_string())?;
+    let row = row.ok_or_else(|| "Board not found".to_string())?;
+
+    let pipeline = build_enrichment_pipeline();
+    pipeline.run(row)
+        .map(|enriched| serde_json::json!({ "slug": slug, "enriched": enriched }))
+        .map_err(|(step, msg)| format!("pipeline failed at '{step}': {msg}"))
+}
+
 /// Build the ToolRegistry — mirrors rig's agent tool registration.
 /// Without an LLM, tools are dispatched explicitly; with one, swap in rig::agent.
 fn build_tool_registry() -> rig_compat::ToolRegistry {
@@ -1494,7 +4877,10 @@ fn build_tool_registry() -> rig_compat::ToolRegistry {
 
     registry.register(
         "search_boards",
-        "TF-IDF cosine-similarity search over Ashby job boards. Args: {query: string, top_n?: number}",
+        "Hybrid BM25 + TF-IDF search (fused via RRF) over Ashby job boards. Args: {query: string, top_n?: number}. \
+         `query` may mix free text with field:value filters over jobs \
+         (location, team, department, workplace_type, country, salary_min, salary_max) — \
+         quoted phrases, -field:value negation, and salary_min:>N range operators are supported.",
         |args| {
             let query = args.get("query").and_then(|v| v.as_str())
                 .ok_or_else(|| "Missing required arg: query".to_string())?;
@@ -1509,7 +4895,8 @@ fn build_tool_registry() -> rig_compat::ToolRegistry {
 
     registry.register(
         "rank_boards",
-        "Okapi BM25 probabilistic ranking over Ashby job boards. Args: {query: string, top_n?: number}",
+        "Okapi BM25 probabilistic ranking over Ashby job boards, with the same field:value \
+         filter syntax as search_boards. Args: {query: string, top_n?: number}",
         |args| {
             let query = args.get("query").and_then(|v| v.as_str())
                 .ok_or_else(|| "Missing required arg: query".to_string())?;
@@ -1531,16 +4918,87 @@ fn build_tool_registry() -> rig_compat::ToolRegistry {
         },
     );
 
+    // `crawl_index`/`enrich_board` enqueue onto the `tasks` table instead of
+    // describing an HTTP call for the caller to forward — the cron tick's
+    // `tasks::drain_tasks` runs them out-of-band. The registry's sync closures
+    // can't await, so these two are dispatched explicitly in `handle_tools`
+    // rather than through `ToolRegistry::call`; the registered entries exist
+    // so `/tools` still lists them with accurate, self-describing schemas.
+    registry.register(
+        "crawl_index",
+        "Enqueue a Common Crawl CDX crawl task. Args: {crawl_id: string, pages_per_run?: number}. \
+         Returns a task uid — poll GET /tasks/{uid} for status and a result summary.",
+        |args| {
+            let crawl_id = args.get("crawl_id").and_then(|v| v.as_str())
+                .ok_or_else(|| "Missing required arg: crawl_id".to_string())?;
+            let pages = args.get("pages_per_run").and_then(|v| v.as_u64()).unwrap_or(3);
+            Ok(serde_json::json!({
+                "action": "ENQUEUE crawl",
+                "params": { "crawl_id": crawl_id, "pages_per_run": pages },
+            }))
+        },
+    );
+
+    registry.register(
+        "enrich_board",
+        "Enqueue an enrichment task for a single board slug. Args: {slug: string}. \
+         Returns a task uid — poll GET /tasks/{uid} for status and a result summary.",
+        |args| {
+            let slug = args.get("slug").and_then(|v| v.as_str())
+                .ok_or_else(|| "Missing required arg: slug".to_string())?;
+            Ok(serde_json::json!({
+                "action": "ENQUEUE enrich",
+                "params": { "slug": slug },
+            }))
+        },
+    );
+
+    // `sync_lever_board`/`sync_workable_board`/`sync_greenhouse_board` enqueue
+    // onto the `tasks` table for the same reason `crawl_index`/`enrich_board`
+    // do (the registry's closures are sync, the enqueue is a D1 write) — see
+    // `handle_tools`'s explicit dispatch branch. Unlike Ashby/Greenhouse's
+    // cron-driven `CronSyncProvider` loop in `run_job_sync_batch`, which
+    // rediscovers and re-syncs every known board on a schedule, these let a
+    // caller sync one named board right now — e.g. right after a board is
+    // first discovered, without waiting for its turn in the cron queue.
+    registry.register(
+        "sync_lever_board",
+        "Enqueue an on-demand sync of a single Lever board. Args: {site: string}. \
+         Returns a task uid — poll GET /tasks/{uid} for status and a result summary.",
+        |args| {
+            let site = args.get("site").and_then(|v| v.as_str())
+                .ok_or_else(|| "Missing required arg: site".to_string())?;
+            Ok(serde_json::json!({
+                "action": "ENQUEUE lever_sync",
+                "params": { "site": site },
+            }))
+        },
+    );
+
+    registry.register(
+        "sync_workable_board",
+        "Enqueue an on-demand sync of a single Workable board. Args: {shortcode: string}. \
+         Returns a task uid — poll GET /tasks/{uid} for status and a result summary.",
+        |args| {
+            let shortcode = args.get("shortcode").and_then(|v| v.as_str())
+                .ok_or_else(|| "Missing required arg: shortcode".to_string())?;
+            Ok(serde_json::json!({
+                "action": "ENQUEUE workable_sync",
+                "params": { "shortcode": shortcode },
+            }))
+        },
+    );
+
     registry.register(
-        "crawl_index",
-        "Trigger a Common Crawl CDX crawl for Ashby boards. Args: {crawl_id: string, pages_per_run?: number}",
+        "sync_greenhouse_board",
+        "Enqueue an on-demand sync of a single Greenhouse board. Args: {token: string}. \
+         Returns a task uid — poll GET /tasks/{uid} for status and a result summary.",
         |args| {
-            let crawl_id = args.get("crawl_id").and_then(|v| v.as_str())
-                .ok_or_else(|| "Missing required arg: crawl_id".to_string())?;
-            let pages = args.get("pages_per_run").and_then(|v| v.as_u64()).unwrap_or(3);
+            let token = args.get("token").and_then(|v| v.as_str())
+                .ok_or_else(|| "Missing required arg: token".to_string())?;
             Ok(serde_json::json!({
-                "action": "GET /crawl",
-                "params": { "crawl_id": crawl_id, "pages_per_run": pages },
+                "action": "ENQUEUE greenhouse_sync",
+                "params": { "token": token },
             }))
         },
     );
@@ -1549,23 +5007,47 @@ fn build_tool_registry() -> rig_compat::ToolRegistry {
 }
 
 /// GET /tools — ToolRegistry listing + ToolDefinition function-calling schemas
-async fn handle_tools(req: Request, _ctx: RouteContext<()>) -> Result<Response> {
+async fn handle_tools(req: Request, ctx: RouteContext<()>) -> Result<Response> {
     let url = req.url()?;
     let params: HashMap<String, String> = url.query_pairs().into_owned().collect();
 
-    // ?call=<tool_name>&args=<json> — execute a tool inline (rig agent dispatch)
+    // ?call=<tool_name>&args=<json> — execute a tool inline (rig agent dispatch).
+    // `crawl_index`/`enrich_board` enqueue onto the `tasks` table and return a
+    // uid to poll rather than running inline — the registry's closures are
+    // sync, so the enqueue itself (a D1 write) happens here instead.
     if let Some(tool_name) = params.get("call") {
         let args: serde_json::Value = params.get("args")
             .and_then(|s| serde_json::from_str(s).ok())
             .unwrap_or(serde_json::json!({}));
 
+        let enqueue_kind_and_arg = match tool_name.as_str() {
+            "crawl_index" => Some((tasks::TaskKind::Crawl, "crawl_id")),
+            "enrich_board" => Some((tasks::TaskKind::Enrich, "slug")),
+            "sync_lever_board" => Some((tasks::TaskKind::LeverSync, "site")),
+            "sync_workable_board" => Some((tasks::TaskKind::WorkableSync, "shortcode")),
+            "sync_greenhouse_board" => Some((tasks::TaskKind::GreenhouseSync, "token")),
+            _ => None,
+        };
+        if let Some((kind, required_arg)) = enqueue_kind_and_arg {
+            if args.get(required_arg).and_then(|v| v.as_str()).is_none() {
+                return error_response(ErrorCode::InvalidInput, &format!("Missing required arg: {required_arg}"));
+            }
+            let db = ctx.env.d1("DB")?;
+            let uid = tasks::enqueue_task(&db, kind, args).await?;
+            return Response::from_json(&ApiResponse::success(serde_json::json!({
+                "tool": tool_name,
+                "task_uid": uid,
+                "note": "poll GET /tasks/{uid} for status and a result summary",
+            })));
+        }
+
         let registry = build_tool_registry();
         return match registry.call(tool_name, args) {
             Ok(result) => Response::from_json(&ApiResponse::success(serde_json::json!({
                 "tool": tool_name,
                 "result": result,
             }))),
-            Err(e) => error_response(&e),
+            Err(e) => error_response(ErrorCode::InvalidInput, &e),
         };
     }
 
@@ -1586,6 +5068,141 @@ async fn handle_tools(req: Request, _ctx: RouteContext<()>) -> Result<Response>
     })))
 }
 
+/// GET /tasks?type=&status=&from=&limit= — paginated list of queued/running/
+/// finished tasks, most recent first. `type` and `status` each accept one or
+/// more comma-separated values (e.g. `?type=crawl,enrich`); `from` is the
+/// `next` cursor returned by a previous page.
+async fn handle_list_tasks(req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    let db = ctx.env.d1("DB")?;
+    let url = req.url()?;
+    let params: HashMap<String, String> = url.query_pairs().into_owned().collect();
+    let limit: u32 = params.get("limit").and_then(|v| v.parse().ok()).unwrap_or(50).min(500);
+    let statuses: Vec<String> = params.get("status")
+        .map(|s| s.split(',').filter(|v| !v.is_empty()).map(str::to_string).collect())
+        .unwrap_or_default();
+    let kinds: Vec<String> = params.get("type")
+        .map(|s| s.split(',').filter(|v| !v.is_empty()).map(str::to_string).collect())
+        .unwrap_or_default();
+    let from: Option<i64> = params.get("from").and_then(|v| v.parse().ok());
+
+    let (rows, next) = tasks::list_tasks(&db, &statuses, &kinds, from, limit).await?;
+    Response::from_json(&ApiResponse::success(serde_json::json!({
+        "count": rows.len(),
+        "tasks": rows,
+        "next": next,
+    })))
+}
+
+/// GET /tasks/:uid — poll a single task's status and result/error.
+async fn handle_get_task(_req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    let db = ctx.env.d1("DB")?;
+    let uid = match ctx.param("uid") {
+        Some(u) => u.clone(),
+        None => return error_response(ErrorCode::InvalidInput, "task uid required"),
+    };
+    match tasks::get_task(&db, &uid).await? {
+        Some(task) => Response::from_json(&ApiResponse::success(task)),
+        None => error_response(ErrorCode::NotFound, &format!("no task found for uid '{uid}'")),
+    }
+}
+
+/// Re-attempt ingestion for up to `limit` not-yet-reprocessed `_invalid_records`
+/// rows, oldest first. `cdx` rows are re-parsed as `CdxRecord` and, if they
+/// now parse and still yield a discoverable slug, upserted as a board exactly
+/// like a normal crawl page would — covers the common case of a transient
+/// CDX glitch or an `extract_slug` fix landing after the row was quarantined.
+/// `posting` rows don't record which `AtsSource` produced them, so there's no
+/// generic re-ingestion path for those yet; reprocessing just marks them
+/// reviewed so `GET /invalid` stops listing them as outstanding.
+async fn reprocess_invalid(db: &D1Database, limit: u32) -> Result<serde_json::Value> {
+    let rows = db.prepare(
+        "SELECT id, kind, ref_id, raw_payload FROM _invalid_records
+         WHERE reprocessed_at IS NULL ORDER BY seen_at ASC LIMIT ?1"
+    ).bind(&[(limit as f64).into()])?.all().await?.results::<serde_json::Value>()?;
+
+    let mut recovered = 0usize;
+    let mut reviewed_only = 0usize;
+    let mut recovered_boards: Vec<AshbyBoard> = Vec::new();
+
+    for row in &rows {
+        let kind = row["kind"].as_str().unwrap_or_default();
+        let ref_id = row["ref_id"].as_str().unwrap_or_default();
+        let raw_payload = row["raw_payload"].as_str().unwrap_or_default();
+
+        if kind == "cdx" {
+            let reparsed = serde_json::from_str::<CdxRecord>(raw_payload).ok()
+                .and_then(|r| extract_slug(&r.url).map(|slug| (slug, r)));
+            match reparsed {
+                Some((slug, r)) => {
+                    recovered_boards.push(AshbyBoard {
+                        slug,
+                        url: r.url,
+                        timestamp: r.timestamp,
+                        crawl_id: ref_id.to_string(),
+                        status: r.status,
+                        mime: r.mime.or(r.mime_detected),
+                        warc_file: r.filename,
+                        warc_offset: r.offset.as_deref().and_then(|s| s.parse().ok()),
+                        warc_length: r.length.as_deref().and_then(|s| s.parse().ok()),
+                    });
+                    recovered += 1;
+                }
+                None => reviewed_only += 1,
+            }
+        } else {
+            reviewed_only += 1;
+        }
+    }
+
+    if !recovered_boards.is_empty() {
+        upsert_boards(db, &recovered_boards).await?;
+    }
+
+    let ids: Vec<String> = rows.iter().map(|r| r["id"].as_str().unwrap_or_default().to_string()).collect();
+    for id in &ids {
+        db.prepare("UPDATE _invalid_records SET reprocessed_at=datetime('now') WHERE id=?1")
+            .bind(&[id.clone().into()])?.run().await?;
+    }
+
+    Ok(serde_json::json!({
+        "examined": rows.len(),
+        "recovered": recovered,
+        "reviewed_only": reviewed_only,
+    }))
+}
+
+/// GET /invalid?kind=&limit= — list outstanding quarantined records (see
+/// `_invalid_records` in `MIGRATIONS`).
+async fn handle_list_invalid(req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    let db = ctx.env.d1("DB")?;
+    let url = req.url()?;
+    let params: HashMap<String, String> = url.query_pairs().into_owned().collect();
+    let limit: u32 = params.get("limit").and_then(|v| v.parse().ok()).unwrap_or(50);
+
+    let (sql, binds): (&str, Vec<JsValue>) = match params.get("kind") {
+        Some(kind) => (
+            "SELECT * FROM _invalid_records WHERE kind=?1 AND reprocessed_at IS NULL ORDER BY seen_at DESC LIMIT ?2",
+            vec![kind.clone().into(), (limit as f64).into()],
+        ),
+        None => (
+            "SELECT * FROM _invalid_records WHERE reprocessed_at IS NULL ORDER BY seen_at DESC LIMIT ?1",
+            vec![(limit as f64).into()],
+        ),
+    };
+    let rows = db.prepare(sql).bind(&binds)?.all().await?.results::<serde_json::Value>()?;
+    Response::from_json(&ApiResponse::success(serde_json::json!({ "count": rows.len(), "records": rows })))
+}
+
+/// GET /invalid/reprocess?limit= — re-attempt ingestion for quarantined rows.
+async fn handle_reprocess_invalid(req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    let db = ctx.env.d1("DB")?;
+    let url = req.url()?;
+    let params: HashMap<String, String> = url.query_pairs().into_owned().collect();
+    let limit: u32 = params.get("limit").and_then(|v| v.parse().ok()).unwrap_or(100);
+    let summary = reprocess_invalid(&db, limit).await?;
+    Response::from_json(&ApiResponse::success(summary))
+}
+
 /// GET /boards — list/search from D1
 async fn handle_list_boards(req: Request, ctx: RouteContext<()>) -> Result<Response> {
     let db = ctx.env.d1("DB")?;
@@ -1594,6 +5211,14 @@ async fn handle_list_boards(req: Request, ctx: RouteContext<()>) -> Result<Respo
     let limit: u32 = params.get("limit").and_then(|v| v.parse().ok()).unwrap_or(100);
     let offset: u32 = params.get("offset").and_then(|v| v.parse().ok()).unwrap_or(0);
     let search = params.get("search").cloned();
+    let filter_predicate = params.get("filter").and_then(|f| parse_facet_filter(f));
+    let facet_fields: Vec<&str> = params.get("facets")
+        .map(|s| s.split(',').filter_map(|f| facet_field_key(f.trim())).collect())
+        .unwrap_or_default();
+
+    if filter_predicate.is_some() || !facet_fields.is_empty() {
+        return handle_list_boards_filtered(&db, search.as_deref(), &filter_predicate, &facet_fields, limit, offset).await;
+    }
 
     let (q, binds): (String, Vec<JsValue>) = if let Some(ref term) = search {
         ("SELECT key as slug, website as url, created_at as first_seen, last_seen_capture_timestamp as last_seen, last_seen_crawl_id as crawl_id, NULL as http_status, created_at FROM companies WHERE key LIKE ?1 ORDER BY key LIMIT ?2 OFFSET ?3".into(),
@@ -1620,6 +5245,64 @@ async fn handle_list_boards(req: Request, ctx: RouteContext<()>) -> Result<Respo
     })))
 }
 
+/// `/boards` path taken when `?filter=`/`?facets=` is present. SQLite has no
+/// clean way to query `companies.ashby_industry_tags`/`ashby_tech_signals`
+/// (JSON-array-as-text) for containment, so this fetches every matching row,
+/// filters/facets in Rust via the same `rig_compat::MetadataPredicate` used
+/// by `/search`, then paginates and facets over that filtered set.
+async fn handle_list_boards_filtered(
+    db: &D1Database,
+    search: Option<&str>,
+    filter_predicate: &Option<rig_compat::MetadataPredicate>,
+    facet_fields: &[&str],
+    limit: u32,
+    offset: u32,
+) -> Result<Response> {
+    const SQL: &str = "SELECT key as slug, website as url, created_at as first_seen,
+         last_seen_capture_timestamp as last_seen, last_seen_crawl_id as crawl_id,
+         NULL as http_status, created_at, ashby_industry_tags, ashby_tech_signals, ashby_size_signal
+         FROM companies";
+
+    let (q, binds): (String, Vec<JsValue>) = match search {
+        Some(term) => (format!("{SQL} WHERE key LIKE ?1 ORDER BY key"), vec![format!("%{term}%").into()]),
+        None => (format!("{SQL} ORDER BY key"), vec![]),
+    };
+    let rows = db.prepare(&q).bind(&binds)?.all().await?.results::<serde_json::Value>()?;
+
+    let mut filtered = Vec::with_capacity(rows.len());
+    let mut metadatas = Vec::with_capacity(rows.len());
+    for row in rows {
+        let industries = parse_json_string_array(row["ashby_industry_tags"].as_str().unwrap_or(""));
+        let tech = parse_json_string_array(row["ashby_tech_signals"].as_str().unwrap_or(""));
+        let size = row["ashby_size_signal"].as_str().unwrap_or("");
+        let mut meta = HashMap::new();
+        if !industries.is_empty() { meta.insert("industry_tags".to_string(), industries.join(", ")); }
+        if !tech.is_empty() { meta.insert("tech_signals".to_string(), tech.join(", ")); }
+        if !size.is_empty() { meta.insert("size_signal".to_string(), size.to_string()); }
+
+        let keep = match filter_predicate { Some(p) => p.matches(&meta), None => true };
+        if keep {
+            metadatas.push(meta);
+            filtered.push(row);
+        }
+    }
+
+    let facet_distribution = (!facet_fields.is_empty()).then(|| {
+        let results: Vec<rig_compat::SearchResult> = metadatas.into_iter().map(|metadata| rig_compat::SearchResult {
+            id: String::new(), text: String::new(), score: 0.0, metadata, snippet: None, highlighted: None,
+        }).collect();
+        rig_compat::facets(&results, facet_fields)
+    });
+
+    let total = filtered.len() as u64;
+    let boards: Vec<serde_json::Value> = filtered.into_iter().skip(offset as usize).take(limit as usize).collect();
+
+    Response::from_json(&ApiResponse::success(serde_json::json!({
+        "boards": boards, "total": total, "limit": limit, "offset": offset,
+        "facetDistribution": facet_distribution,
+    })))
+}
+
 async fn handle_indexes(_req: Request, _ctx: RouteContext<()>) -> Result<Response> {
     let indexes = list_cc_indexes().await?;
     Response::from_json(&ApiResponse::success(serde_json::json!({ "indexes": indexes, "count": indexes.len() })))
@@ -1638,47 +5321,350 @@ async fn handle_reset_progress(req: Request, ctx: RouteContext<()>) -> Result<Re
     let params: HashMap<String, String> = url.query_pairs().into_owned().collect();
     let cid = match params.get("crawl_id") {
         Some(id) => id.clone(),
-        None => return error_response("crawl_id required"),
+        None => return error_response(ErrorCode::InvalidInput, "crawl_id required"),
     };
     db.prepare("DELETE FROM crawl_progress WHERE crawl_id=?1")
         .bind(&[cid.clone().into()])?.run().await?;
     Response::from_json(&ApiResponse::success(serde_json::json!({ "message": format!("Reset {cid}") })))
 }
 
-async fn handle_stats(_req: Request, ctx: RouteContext<()>) -> Result<Response> {
+/// GET /stats?include_closed=1 — summary board counts plus `sync_runs`
+/// rollups. By default, Ashby boards whose `lifecycle_state` has decayed to
+/// `'dead'` (see `next_lifecycle_state`) are excluded from `total_boards`/
+/// `newest_boards`, the same way `/search` drops closed jobs — `dead` is the
+/// only "board went inactive" signal this tree tracks today (ats.rs's
+/// `sync_state='dead'` covers repeated *error* responses for the other three
+/// providers, not repeated empty/404 ones), so that's the one honored here.
+async fn handle_stats(req: Request, ctx: RouteContext<()>) -> Result<Response> {
     let db = ctx.env.d1("DB")?;
-    let total = db.prepare("SELECT COUNT(*) as count FROM companies")
-        .bind(&[])?.first::<serde_json::Value>(None).await?
+    let url = req.url()?;
+    let params: HashMap<String, String> = url.query_pairs().into_owned().collect();
+    let include_closed = params.get("include_closed").map(String::as_str) == Some("1");
+    let active_filter = if include_closed { "1=1" } else { "COALESCE(ab.lifecycle_state, '') != 'dead'" };
+
+    let total = db.prepare(&format!(
+        "SELECT COUNT(*) as count FROM companies c LEFT JOIN ashby_boards ab ON ab.slug = c.key WHERE {active_filter}"
+    )).bind(&[])?.first::<serde_json::Value>(None).await?
         .and_then(|r| r["count"].as_f64()).unwrap_or(0.0) as u64;
     let by_crawl = db.prepare("SELECT last_seen_crawl_id as crawl_id, COUNT(*) as count FROM companies GROUP BY last_seen_crawl_id")
         .bind(&[])?.all().await?.results::<serde_json::Value>()?;
-    let newest = db.prepare("SELECT key as slug, website as url, last_seen_capture_timestamp as last_seen FROM companies ORDER BY last_seen_capture_timestamp DESC LIMIT 10")
-        .bind(&[])?.all().await?.results::<serde_json::Value>()?;
+    let newest = db.prepare(&format!(
+        "SELECT c.key as slug, c.website as url, c.last_seen_capture_timestamp as last_seen
+         FROM companies c LEFT JOIN ashby_boards ab ON ab.slug = c.key
+         WHERE {active_filter}
+         ORDER BY c.last_seen_capture_timestamp DESC LIMIT 10"
+    )).bind(&[])?.all().await?.results::<serde_json::Value>()?;
+    let sync = handle_stats_sync_rollups(&db).await?;
+    Response::from_json(&ApiResponse::success(serde_json::json!({
+        "total_boards": total, "by_crawl": by_crawl, "newest_boards": newest, "sync": sync,
+    })))
+}
+
+/// Ingestion-analytics rollups over `sync_runs` — jobs added per day, the
+/// most-active boards by jobs inserted/updated, and an error rate per source,
+/// so a fire-and-forget upsert loop is something you can chart and alarm on
+/// instead of only trusting its return value.
+async fn handle_stats_sync_rollups(db: &D1Database) -> Result<serde_json::Value> {
+    let jobs_added_per_day = db.prepare(
+        "SELECT date(ran_at) as day, SUM(inserted) as jobs_added
+         FROM sync_runs GROUP BY day ORDER BY day DESC LIMIT 30"
+    ).bind(&[])?.all().await?.results::<serde_json::Value>()?;
+
+    let most_active_boards = db.prepare(
+        "SELECT source_kind, site, SUM(inserted) as inserted, SUM(updated) as updated, COUNT(*) as runs
+         FROM sync_runs GROUP BY source_kind, site ORDER BY (inserted + updated) DESC LIMIT 20"
+    ).bind(&[])?.all().await?.results::<serde_json::Value>()?;
+
+    let error_rate_per_source = db.prepare(
+        "SELECT source_kind,
+                SUM(errors) as errors,
+                COUNT(*) as runs,
+                CAST(SUM(errors) AS REAL) / COUNT(*) as errors_per_run
+         FROM sync_runs GROUP BY source_kind ORDER BY source_kind"
+    ).bind(&[])?.all().await?.results::<serde_json::Value>()?;
+
+    Ok(serde_json::json!({
+        "jobs_added_per_day": jobs_added_per_day,
+        "most_active_boards": most_active_boards,
+        "error_rate_per_source": error_rate_per_source,
+    }))
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// METRICS — Prometheus exposition of pipeline health
+// ═══════════════════════════════════════════════════════════════════════════
+//
+// `console_log!` lines are grep fodder, not something you can chart or alarm
+// on. `GET /metrics` aggregates the tables this module writes to — one
+// `COUNT`/`SUM` query per table, so a scrape stays cheap — and renders them
+// as the Prometheus text exposition format.
+
+/// `boards_total`/`boards_unsynced`/`boards_dead` for one board-tracking
+/// table, read via a single aggregating query so per-provider metrics don't
+/// cost more than one round trip each.
+struct BoardCounts {
+    total: u64,
+    unsynced: u64,
+    dead: u64,
+}
+
+async fn board_counts(db: &D1Database, table: &str) -> Result<BoardCounts> {
+    let row = db.prepare(&format!(
+        "SELECT COUNT(*) as total,
+                SUM(CASE WHEN last_synced_at IS NULL THEN 1 ELSE 0 END) as unsynced,
+                SUM(CASE WHEN sync_state='dead' THEN 1 ELSE 0 END) as dead
+         FROM {table}"
+    )).bind(&[])?.first::<serde_json::Value>(None).with_poll_timer("metrics:board_counts", 1).await?
+        .map(|r| BoardCounts {
+            total: r["total"].as_f64().unwrap_or(0.0) as u64,
+            unsynced: r["unsynced"].as_f64().unwrap_or(0.0) as u64,
+            dead: r["dead"].as_f64().unwrap_or(0.0) as u64,
+        })
+        .unwrap_or(BoardCounts { total: 0, unsynced: 0, dead: 0 });
+    Ok(row)
+}
+
+/// Escape label-value-unsafe characters for Prometheus text exposition —
+/// `crawl_id`/`status` come from `crawl_progress`, not a fixed enum, so a
+/// stray `"` or newline must not break the line it's embedded in.
+fn prometheus_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+/// GET /metrics — Prometheus text exposition of crawl/migration/enrichment
+/// health. Queried fresh on every scrape; each block below is one cheap
+/// aggregating query, not a per-row scan.
+async fn handle_metrics(_req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    let db = ctx.env.d1("DB")?;
+    let mut out = String::new();
+
+    let providers = [
+        ("ashby", "ashby_boards"),
+        ("greenhouse", "greenhouse_boards"),
+        ("workable", "workable_boards"),
+        ("lever", "lever_boards"),
+    ];
+    let mut counts = Vec::with_capacity(providers.len());
+    for (provider, table) in providers {
+        counts.push((provider, board_counts(&db, table).await?));
+    }
+
+    out.push_str("# HELP boards_total Total tracked job boards per ATS provider.\n");
+    out.push_str("# TYPE boards_total gauge\n");
+    for (provider, c) in &counts {
+        out.push_str(&format!("boards_total{{provider=\"{provider}\"}} {}\n", c.total));
+    }
+    out.push('\n');
+
+    out.push_str("# HELP boards_unsynced Boards that have never completed a sync, per ATS provider.\n");
+    out.push_str("# TYPE boards_unsynced gauge\n");
+    for (provider, c) in &counts {
+        out.push_str(&format!("boards_unsynced{{provider=\"{provider}\"}} {}\n", c.unsynced));
+    }
+    out.push('\n');
+
+    out.push_str("# HELP boards_dead Boards whose sync_state flipped to 'dead' after exhausting retries, per ATS provider.\n");
+    out.push_str("# TYPE boards_dead gauge\n");
+    for (provider, c) in &counts {
+        out.push_str(&format!("boards_dead{{provider=\"{provider}\"}} {}\n", c.dead));
+    }
+    out.push('\n');
+
+    let jobs_total = db.prepare("SELECT COUNT(*) as count FROM jobs")
+        .bind(&[])?.first::<serde_json::Value>(None).with_poll_timer("metrics:jobs_total", 1).await?
+        .and_then(|r| r["count"].as_f64()).unwrap_or(0.0) as u64;
+    out.push_str("# HELP jobs_total Total job postings stored across all providers.\n");
+    out.push_str("# TYPE jobs_total gauge\n");
+    out.push_str(&format!("jobs_total {jobs_total}\n\n"));
+
+    let migrations_applied_total = db.prepare("SELECT COUNT(*) as count FROM _migrations")
+        .bind(&[])?.first::<serde_json::Value>(None).with_poll_timer("metrics:migrations_applied_total", 1).await?
+        .and_then(|r| r["count"].as_f64()).unwrap_or(0.0) as u64;
+    out.push_str("# HELP migrations_applied_total Migrations recorded as applied in the _migrations ledger.\n");
+    out.push_str("# TYPE migrations_applied_total counter\n");
+    out.push_str(&format!("migrations_applied_total {migrations_applied_total}\n\n"));
+
+    let enriched_companies_total = db.prepare("SELECT COUNT(*) as count FROM companies WHERE ashby_enriched_at IS NOT NULL")
+        .bind(&[])?.first::<serde_json::Value>(None).with_poll_timer("metrics:enriched_companies_total", 1).await?
+        .and_then(|r| r["count"].as_f64()).unwrap_or(0.0) as u64;
+    out.push_str("# HELP enriched_companies_total Companies with a completed Ashby enrichment pass.\n");
+    out.push_str("# TYPE enriched_companies_total gauge\n");
+    out.push_str(&format!("enriched_companies_total {enriched_companies_total}\n\n"));
+
+    let crawls = db.prepare("SELECT crawl_id, boards_found, current_page, total_pages, status FROM crawl_progress")
+        .bind(&[])?.all().with_poll_timer("metrics:crawl_progress", 1).await?.results::<serde_json::Value>()?;
+
+    out.push_str("# HELP crawl_progress_boards_found Boards discovered so far for a crawl_id.\n");
+    out.push_str("# TYPE crawl_progress_boards_found gauge\n");
+    for row in &crawls {
+        let crawl_id = prometheus_escape(row["crawl_id"].as_str().unwrap_or(""));
+        let boards_found = row["boards_found"].as_f64().unwrap_or(0.0);
+        out.push_str(&format!("crawl_progress_boards_found{{crawl_id=\"{crawl_id}\"}} {boards_found}\n"));
+    }
+    out.push('\n');
+
+    out.push_str("# HELP crawl_progress_current_page Current page cursor for a crawl_id.\n");
+    out.push_str("# TYPE crawl_progress_current_page gauge\n");
+    for row in &crawls {
+        let crawl_id = prometheus_escape(row["crawl_id"].as_str().unwrap_or(""));
+        let current_page = row["current_page"].as_f64().unwrap_or(0.0);
+        out.push_str(&format!("crawl_progress_current_page{{crawl_id=\"{crawl_id}\"}} {current_page}\n"));
+    }
+    out.push('\n');
+
+    out.push_str("# HELP crawl_progress_total_pages Total pages planned for a crawl_id.\n");
+    out.push_str("# TYPE crawl_progress_total_pages gauge\n");
+    for row in &crawls {
+        let crawl_id = prometheus_escape(row["crawl_id"].as_str().unwrap_or(""));
+        let total_pages = row["total_pages"].as_f64().unwrap_or(0.0);
+        out.push_str(&format!("crawl_progress_total_pages{{crawl_id=\"{crawl_id}\"}} {total_pages}\n"));
+    }
+    out.push('\n');
+
+    out.push_str("# HELP crawl_progress_status Current crawl status as a 1-valued timeseries labeled with the status string.\n");
+    out.push_str("# TYPE crawl_progress_status gauge\n");
+    for row in &crawls {
+        let crawl_id = prometheus_escape(row["crawl_id"].as_str().unwrap_or(""));
+        let status = prometheus_escape(row["status"].as_str().unwrap_or("unknown"));
+        out.push_str(&format!("crawl_progress_status{{crawl_id=\"{crawl_id}\",status=\"{status}\"}} 1\n"));
+    }
+    out.push('\n');
+
+    // Cron-cycle health from `run_metrics` (see `record_run_metrics`) — the
+    // latest run's numbers as gauges, same as everything above. A "last N
+    // runs" history view with p50/p95 rollups doesn't fit this single-sample
+    // scrape shape, so that lives at `GET /runs` instead of being crammed in
+    // here.
+    let last_run = db.prepare(
+        "SELECT duration_ms, occupancy, subrequests, error_count FROM run_metrics ORDER BY id DESC LIMIT 1"
+    ).bind(&[])?.first::<serde_json::Value>(None).with_poll_timer("metrics:last_run", 1).await?;
+    if let Some(r) = &last_run {
+        out.push_str("# HELP cron_run_duration_ms Wall-clock duration of the most recent cron cycle.\n");
+        out.push_str("# TYPE cron_run_duration_ms gauge\n");
+        out.push_str(&format!("cron_run_duration_ms {}\n\n", r["duration_ms"].as_f64().unwrap_or(0.0)));
+
+        out.push_str("# HELP cron_run_occupancy Fraction of the most recent cron cycle spent waiting on HTTP fetches.\n");
+        out.push_str("# TYPE cron_run_occupancy gauge\n");
+        out.push_str(&format!("cron_run_occupancy {}\n\n", r["occupancy"].as_f64().unwrap_or(0.0)));
+
+        out.push_str("# HELP cron_run_subrequests Subrequests issued by the most recent cron cycle.\n");
+        out.push_str("# TYPE cron_run_subrequests gauge\n");
+        out.push_str(&format!("cron_run_subrequests {}\n\n", r["subrequests"].as_f64().unwrap_or(0.0)));
+
+        out.push_str("# HELP cron_run_errors Errors recorded by the most recent cron cycle.\n");
+        out.push_str("# TYPE cron_run_errors gauge\n");
+        out.push_str(&format!("cron_run_errors {}\n", r["error_count"].as_f64().unwrap_or(0.0)));
+    }
+
+    let mut headers = Headers::new();
+    headers.set("Content-Type", "text/plain; version=0.0.4")?;
+    Ok(Response::ok(out)?.with_headers(headers))
+}
+
+/// GET /runs?limit=N — the latest `N` `run_metrics` rows (cron-cycle
+/// duration/occupancy/subrequests/error_count, newest first) plus rolling
+/// aggregates over the last 24h (p50/p95 duration, mean occupancy, error
+/// rate). `GET /metrics` already owns the single-sample Prometheus-gauge
+/// view of the same table (`cron_run_*`); this is the JSON "history" view a
+/// scrape format can't express, mirroring how `/stats` sits next to
+/// `/metrics` for the rest of the corpus.
+async fn handle_runs(req: Request, ctx: RouteContext<()>) -> Result<Response> {
+    let db = ctx.env.d1("DB")?;
+    let url = req.url()?;
+    let params: HashMap<String, String> = url.query_pairs().into_owned().collect();
+    let limit: u32 = params.get("limit").and_then(|l| l.parse().ok()).unwrap_or(20).clamp(1, 200);
+
+    let runs = db.prepare("SELECT * FROM run_metrics ORDER BY id DESC LIMIT ?1")
+        .bind(&[(limit as f64).into()])?
+        .all().await?
+        .results::<serde_json::Value>()?;
+
+    let mut durations_24h: Vec<f64> = db.prepare(
+        "SELECT duration_ms FROM run_metrics WHERE started_at >= datetime('now', '-24 hours')"
+    ).bind(&[])?.all().await?.results::<serde_json::Value>()?
+        .iter().filter_map(|r| r["duration_ms"].as_f64()).collect();
+    durations_24h.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+    let percentile = |sorted: &[f64], p: f64| -> f64 {
+        if sorted.is_empty() {
+            return 0.0;
+        }
+        let idx = (((sorted.len() - 1) as f64) * p).round() as usize;
+        sorted[idx.min(sorted.len() - 1)]
+    };
+
+    let rollup = db.prepare(
+        "SELECT AVG(occupancy) as mean_occupancy, COUNT(*) as total,
+                SUM(CASE WHEN error_count > 0 THEN 1 ELSE 0 END) as runs_with_errors
+         FROM run_metrics WHERE started_at >= datetime('now', '-24 hours')"
+    ).bind(&[])?.first::<serde_json::Value>(None).await?;
+    let (mean_occupancy, total, runs_with_errors) = rollup
+        .map(|r| (
+            r["mean_occupancy"].as_f64().unwrap_or(0.0),
+            r["total"].as_f64().unwrap_or(0.0),
+            r["runs_with_errors"].as_f64().unwrap_or(0.0),
+        ))
+        .unwrap_or((0.0, 0.0, 0.0));
+    let error_rate = if total > 0.0 { runs_with_errors / total } else { 0.0 };
+
     Response::from_json(&ApiResponse::success(serde_json::json!({
-        "total_boards": total, "by_crawl": by_crawl, "newest_boards": newest,
+        "runs": runs,
+        "aggregates_24h": {
+            "sample_count": total as u64,
+            "p50_duration_ms": percentile(&durations_24h, 0.50),
+            "p95_duration_ms": percentile(&durations_24h, 0.95),
+            "mean_occupancy": mean_occupancy,
+            "error_rate": error_rate,
+        },
     })))
 }
 
 /// Build the BM25 index from D1. Used by /search (replaces TF-IDF cosine similarity).
-async fn build_bm25_index(db: &D1Database) -> Result<rig_compat::Bm25Index> {
+/// Shared corpus for `/search`'s text indexes — one document per
+/// `ashby_boards` row, concatenating slug/company/industry/URL path segments
+/// into free text. Shared by [`build_bm25_index`] and [`build_hybrid_index`]
+/// so the two engines can't silently drift onto different corpora.
+async fn fetch_search_corpus(db: &D1Database, include_closed: bool) -> Result<Vec<(String, String, HashMap<String, String>)>> {
+    // `company_name`/`industry_tags` live on `ashby_boards` itself per an early
+    // migration, but nothing ever writes them — `auto_enrich_boards` actually
+    // enriches `companies.ashby_industry_tags`/`ashby_tech_signals`/
+    // `ashby_size_signal`, joined in here by slug/key the same way
+    // `get_company_slugs` does.
+    //
+    // Boards the janitor pass has tombstoned `lifecycle_state='dead'` (see
+    // `next_lifecycle_state`) are excluded by default, the same way
+    // `handle_stats` drops them from its counts — otherwise a board that's
+    // stopped resolving stays in `/search` results forever. `include_closed`
+    // overrides this, matching `/stats`'s `?include_closed=1`.
+    let active_filter = if include_closed { "1=1" } else { "COALESCE(ab.lifecycle_state, '') != 'dead'" };
     let rows = db
-        .prepare("SELECT slug, url, last_seen, crawl_id, company_name, industry_tags FROM ashby_boards")
+        .prepare(&format!(
+            "SELECT ab.slug AS slug, ab.url AS url, ab.last_seen AS last_seen, ab.crawl_id AS crawl_id,
+                    c.name AS company_name, c.ashby_industry_tags AS industry_tags,
+                    c.ashby_tech_signals AS tech_signals, c.ashby_size_signal AS size_signal
+             FROM ashby_boards ab
+             LEFT JOIN companies c ON c.key = ab.slug
+             WHERE {active_filter}"
+        ))
         .bind(&[])?
         .all().await?
         .results::<serde_json::Value>()?;
 
-    let mut index = rig_compat::Bm25Index::new();
+    let mut docs = Vec::with_capacity(rows.len());
     for row in &rows {
         let slug = row["slug"].as_str().unwrap_or("");
         let url  = row["url"].as_str().unwrap_or("");
-        // Include enriched company_name and industry_tags in the search corpus when available
         let company = row["company_name"].as_str().unwrap_or("");
-        let industries = row["industry_tags"].as_str().unwrap_or("");
+        let industries = parse_json_string_array(row["industry_tags"].as_str().unwrap_or(""));
+        let tech = parse_json_string_array(row["tech_signals"].as_str().unwrap_or(""));
+        let size = row["size_signal"].as_str().unwrap_or("");
         let search_text = format!(
-            "{} {} {} {}",
+            "{} {} {} {} {} {}",
             slug.replace('-', " "),
             company,
-            industries,
+            industries.join(" "),
+            tech.join(" "),
+            size,
             url.split('/').collect::<Vec<_>>().join(" "),
         );
         let mut meta = HashMap::new();
@@ -1686,18 +5672,251 @@ async fn build_bm25_index(db: &D1Database) -> Result<rig_compat::Bm25Index> {
         meta.insert("last_seen".into(), row["last_seen"].as_str().unwrap_or("").to_string());
         meta.insert("crawl_id".into(), row["crawl_id"].as_str().unwrap_or("").to_string());
         if !company.is_empty() { meta.insert("company_name".into(), company.to_string()); }
-        if !industries.is_empty() { meta.insert("industry_tags".into(), industries.to_string()); }
-        index.add_document(slug.to_string(), search_text, meta);
+        if !industries.is_empty() { meta.insert("industry_tags".into(), industries.join(", ")); }
+        if !tech.is_empty() { meta.insert("tech_signals".into(), tech.join(", ")); }
+        if !size.is_empty() { meta.insert("size_signal".into(), size.to_string()); }
+        docs.push((slug.to_string(), search_text, meta));
+    }
+    Ok(docs)
+}
+
+/// Parse an `auto_enrich_boards`-written JSON array column (e.g.
+/// `["fintech","ai-ml"]`) into its plain values. Empty/malformed input —
+/// including the `"[]"` default `auto_enrich_boards` writes when nothing was
+/// detected — yields an empty vec rather than an error.
+fn parse_json_string_array(raw: &str) -> Vec<String> {
+    if raw.is_empty() { return Vec::new(); }
+    serde_json::from_str::<Vec<String>>(raw).unwrap_or_default()
+}
+
+/// Maps a `?filter=`/`?facets=` field name onto the metadata key
+/// `fetch_search_corpus` populates for it.
+fn facet_field_key(field: &str) -> Option<&'static str> {
+    match field {
+        "industry" => Some("industry_tags"),
+        "tech" => Some("tech_signals"),
+        "size" => Some("size_signal"),
+        _ => None,
+    }
+}
+
+enum FacetClauseOp { Eq, Ne, In }
+
+struct FacetClause<'a> {
+    field: &'a str,
+    op: FacetClauseOp,
+    values: Vec<&'a str>,
+}
+
+/// Splits a `?filter=` string on `AND` into individual `field op value(s)`
+/// clauses. `!=` is checked before bare `=` so `size != enterprise` isn't
+/// misparsed as `size !` `= enterprise`; `IN [...]` is checked before `=` for
+/// the same reason. Unrecognized clause shapes are dropped silently.
+fn parse_facet_clauses(input: &str) -> Vec<FacetClause<'_>> {
+    input
+        .split(" AND ")
+        .map(str::trim)
+        .filter(|c| !c.is_empty())
+        .filter_map(|clause| {
+            if let Some((field, rest)) = clause.split_once("!=") {
+                return Some(FacetClause { field: field.trim(), op: FacetClauseOp::Ne, values: vec![rest.trim()] });
+            }
+            if let Some((field, rest)) = clause.split_once(" IN ") {
+                let values: Vec<&str> = rest
+                    .trim()
+                    .trim_start_matches('[')
+                    .trim_end_matches(']')
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|v| !v.is_empty())
+                    .collect();
+                if values.is_empty() { return None; }
+                return Some(FacetClause { field: field.trim(), op: FacetClauseOp::In, values });
+            }
+            if let Some((field, rest)) = clause.split_once('=') {
+                return Some(FacetClause { field: field.trim(), op: FacetClauseOp::Eq, values: vec![rest.trim()] });
+            }
+            None
+        })
+        .collect()
+}
+
+/// Parses a `?filter=` facet-filter grammar string (e.g. `industry = fintech
+/// AND tech IN [rust, go] AND size != enterprise`) into a
+/// `rig_compat::MetadataPredicate`, AND-combining every recognized clause.
+/// Unknown fields or malformed clauses are dropped rather than erroring —
+/// same never-400-on-a-bad-facet philosophy as `query_parser::parse_query`.
+fn parse_facet_filter(input: &str) -> Option<rig_compat::MetadataPredicate> {
+    use rig_compat::MetadataPredicate as P;
+    let preds: Vec<P> = parse_facet_clauses(input)
+        .into_iter()
+        .filter_map(|c| {
+            let key = facet_field_key(c.field)?.to_string();
+            Some(match c.op {
+                FacetClauseOp::Eq => P::Eq(key, c.values[0].to_string()),
+                FacetClauseOp::Ne => P::Ne(key, c.values[0].to_string()),
+                FacetClauseOp::In => P::In(key, c.values.into_iter().map(str::to_string).collect()),
+            })
+        })
+        .collect();
+    if preds.is_empty() { None } else { Some(P::And(preds)) }
+}
+
+#[cfg(test)]
+mod facet_filter_tests {
+    use super::*;
+
+    #[test]
+    fn facet_field_key_maps_known_fields_only() {
+        assert_eq!(facet_field_key("industry"), Some("industry_tags"));
+        assert_eq!(facet_field_key("tech"), Some("tech_signals"));
+        assert_eq!(facet_field_key("size"), Some("size_signal"));
+        assert_eq!(facet_field_key("nonsense"), None);
+    }
+
+    #[test]
+    fn ne_is_checked_before_bare_eq() {
+        let clauses = parse_facet_clauses("size != enterprise");
+        assert_eq!(clauses.len(), 1);
+        assert!(matches!(clauses[0].op, FacetClauseOp::Ne));
+        assert_eq!(clauses[0].values, vec!["enterprise"]);
+    }
+
+    #[test]
+    fn in_is_checked_before_bare_eq() {
+        let clauses = parse_facet_clauses("tech IN [rust, go]");
+        assert_eq!(clauses.len(), 1);
+        assert!(matches!(clauses[0].op, FacetClauseOp::In));
+        assert_eq!(clauses[0].values, vec!["rust", "go"]);
+    }
+
+    #[test]
+    fn and_joins_multiple_clauses() {
+        let clauses = parse_facet_clauses("industry = fintech AND size != enterprise");
+        assert_eq!(clauses.len(), 2);
+    }
+
+    #[test]
+    fn malformed_clause_is_dropped_silently() {
+        assert!(parse_facet_clauses("just some words").is_empty());
+    }
+
+    #[test]
+    fn parse_facet_filter_drops_unknown_fields_but_keeps_known_ones() {
+        let pred = parse_facet_filter("industry = fintech AND bogus = nope").unwrap();
+        match pred {
+            rig_compat::MetadataPredicate::And(preds) => assert_eq!(preds.len(), 1),
+            _ => panic!("expected an And predicate"),
+        }
+    }
+
+    #[test]
+    fn parse_facet_filter_returns_none_when_nothing_recognized() {
+        assert!(parse_facet_filter("bogus = nope").is_none());
+    }
+}
+
+/// Row shape for `search_index_snapshots` (see migration
+/// `0021_search_index_snapshots`) — one cached snapshot per index kind,
+/// looked up by `name` and checked against the live corpus by `corpus_hash`
+/// before reuse (see `build_bm25_index`/`build_hybrid_index`).
+async fn load_search_index_snapshot(db: &D1Database, name: &str) -> Result<Option<(String, String)>> {
+    let row = db.prepare("SELECT corpus_hash, payload FROM search_index_snapshots WHERE name = ?1")
+        .bind(&[name.into()])?
+        .first::<serde_json::Value>(None)
+        .await?;
+    Ok(row.map(|r| (
+        r["corpus_hash"].as_str().unwrap_or("").to_string(),
+        r["payload"].as_str().unwrap_or("").to_string(),
+    )))
+}
+
+/// Persist (or replace) the cached snapshot for `name`. Best-effort — a
+/// failed save just means the next cold start rebuilds from scratch instead
+/// of reusing a cache, not a request failure, so errors are logged and
+/// swallowed rather than propagated.
+async fn save_search_index_snapshot(db: &D1Database, name: &str, corpus_hash: &str, payload: &str) {
+    let stmt = db.prepare(
+        "INSERT INTO search_index_snapshots (name, corpus_hash, payload, updated_at)
+         VALUES (?1, ?2, ?3, datetime('now'))
+         ON CONFLICT(name) DO UPDATE SET corpus_hash = excluded.corpus_hash, payload = excluded.payload, updated_at = excluded.updated_at"
+    ).bind(&[name.into(), corpus_hash.into(), payload.into()]);
+    let result = match stmt {
+        Ok(stmt) => stmt.run().await,
+        Err(e) => Err(e),
+    };
+    if let Err(e) = result {
+        console_log!("[search-index-snapshot] failed to save '{}': {:?}", name, e);
+    }
+}
+
+/// Builds (or restores from a cached `search_index_snapshots` row) a
+/// [`rig_compat::Bm25Index`] over `fetch_search_corpus`. A cold Worker start
+/// pays `fetch_search_corpus`'s D1 read either way, but a cache hit skips
+/// re-tokenizing every document and recomputing `doc_freq`/`avg_dl` from
+/// scratch — `corpus_content_hash` is cheap to recompute and compare against
+/// the stored hash, so a stale cache (the corpus changed since the last
+/// save) is detected and rebuilt rather than served wrong.
+async fn build_bm25_index(db: &D1Database, include_closed: bool) -> Result<rig_compat::Bm25Index> {
+    let corpus = fetch_search_corpus(db, include_closed).await?;
+    let corpus_hash = rig_compat::corpus_content_hash(corpus.iter().map(|(id, text, _)| (id.as_str(), text.as_str())));
+
+    if let Ok(Some((cached_hash, payload))) = load_search_index_snapshot(db, "bm25").await {
+        if cached_hash == corpus_hash {
+            if let Ok(snapshot) = serde_json::from_str::<rig_compat::Bm25Snapshot>(&payload) {
+                if let Ok(index) = rig_compat::Bm25Index::from_snapshot(snapshot) {
+                    return Ok(index);
+                }
+            }
+        }
+    }
+
+    let mut index = rig_compat::Bm25Index::new();
+    for (id, text, meta) in corpus {
+        index.add_document(id, text, meta);
+    }
+    index.rebuild_index();
+    if let Ok(payload) = serde_json::to_string(&index.to_snapshot()) {
+        save_search_index_snapshot(db, "bm25", &corpus_hash, &payload).await;
+    }
+    Ok(index)
+}
+
+/// Same corpus and cold-start-snapshot strategy as [`build_bm25_index`], for
+/// [`rig_compat::HybridIndex`] used by the `mode=hybrid`/`mode=semantic`
+/// paths of `/search`. The "vector" side is `rig_compat::InMemoryVectorStore`'s
+/// TF-IDF embedding — this Worker has no Workers AI binding, so it's a
+/// lexical-adjacent semantic proxy rather than a true embedding model.
+async fn build_hybrid_index(db: &D1Database, include_closed: bool) -> Result<rig_compat::HybridIndex> {
+    let corpus = fetch_search_corpus(db, include_closed).await?;
+    let corpus_hash = rig_compat::corpus_content_hash(corpus.iter().map(|(id, text, _)| (id.as_str(), text.as_str())));
+
+    if let Ok(Some((cached_hash, payload))) = load_search_index_snapshot(db, "hybrid").await {
+        if cached_hash == corpus_hash {
+            if let Ok(snapshot) = serde_json::from_str::<rig_compat::HybridSnapshot>(&payload) {
+                if let Ok(index) = rig_compat::HybridIndex::from_snapshot(snapshot) {
+                    return Ok(index);
+                }
+            }
+        }
+    }
+
+    let mut index = rig_compat::HybridIndex::new();
+    for (id, text, meta) in corpus {
+        index.add_document(id, text, meta);
     }
     index.rebuild_index();
+    if let Ok(payload) = serde_json::to_string(&index.to_snapshot()) {
+        save_search_index_snapshot(db, "hybrid", &corpus_hash, &payload).await;
+    }
     Ok(index)
 }
 
 /// Run SlugExtractor + ResultPipeline on a batch of boards and persist enrichment
 /// columns (company_name, industry_tags, tech_signals, enriched_at) back to D1.
 /// Called automatically at the end of each crawl batch — no HTTP endpoint needed.
-async fn auto_enrich_boards(db: &D1Database, boards: &[AshbyBoard]) -> Result<usize> {
-    if boards.is_empty() { return Ok(0); }
+async fn auto_enrich_boards(db: &D1Database, boards: &[AshbyBoard]) -> Result<BoardBatchOutcome> {
+    if boards.is_empty() { return Ok(BoardBatchOutcome::default()); }
 
     const SQL: &str = "UPDATE companies
          SET ashby_industry_tags=?1, ashby_tech_signals=?2, ashby_size_signal=?3, ashby_enriched_at=datetime('now')
@@ -1705,7 +5924,7 @@ async fn auto_enrich_boards(db: &D1Database, boards: &[AshbyBoard]) -> Result<us
     const BATCH_SIZE: usize = 100;
 
     let pipeline = build_enrichment_pipeline();
-    let mut stmts = Vec::with_capacity(boards.len());
+    let mut items = Vec::with_capacity(boards.len());
 
     for board in boards {
         let row = serde_json::json!({
@@ -1734,20 +5953,54 @@ async fn auto_enrich_boards(db: &D1Database, boards: &[AshbyBoard]) -> Result<us
             .unwrap_or_else(|| "[]".to_string());
         let size_signal = extracted.get("size_signal").and_then(|v| v.as_str()).unwrap_or("startup");
 
-        stmts.push(db.prepare(SQL).bind(&[
+        let stmt = db.prepare(SQL).bind(&[
             industry_tags.into(),
             tech_signals.into(),
             size_signal.into(),
             board.slug.clone().into(),
-        ])?);
+        ])?;
+        items.push((board.slug.clone(), stmt));
     }
 
-    let saved = stmts.len();
-    for chunk in stmts.chunks(BATCH_SIZE) {
-        let _ = db.batch(chunk.to_vec()).await;
+    let keys: Vec<String> = items.iter().map(|(slug, _)| slug.clone()).collect();
+    let existing = existing_company_keys(db, &keys).await?;
+
+    // Adaptive chunk sizing: start at `BATCH_SIZE`, then re-tune off the
+    // cumulative measured cost so far — see `next_batch_size`.
+    let mut batch_outcome = BatchOutcome::default();
+    let mut offset = 0;
+    let mut chunk_size = BATCH_SIZE;
+    let mut processed = 0usize;
+    let mut elapsed_total_ms = 0.0f64;
+    while offset < items.len() {
+        let end = (offset + chunk_size).min(items.len());
+        let chunk = items[offset..end].to_vec();
+        let chunk_len = chunk.len();
+        let started_at = js_sys::Date::now();
+        batch_outcome.merge(
+            run_batch_resilient(db, chunk)
+                .with_poll_timer("auto_enrich_boards:batch", chunk_len)
+                .await
+        );
+        elapsed_total_ms += js_sys::Date::now() - started_at;
+        processed += chunk_len;
+        offset = end;
+        chunk_size = next_batch_size(processed, elapsed_total_ms, ADAPTIVE_ENRICH_BATCH_BUDGET_MS, ADAPTIVE_BATCH_MIN, ADAPTIVE_BATCH_MAX);
+    }
+    if !batch_outcome.failed.is_empty() {
+        console_log!("[auto_enrich_boards] {} board(s) failed to commit: {:?}", batch_outcome.failed.len(), batch_outcome.failed);
+    }
+
+    let mut written = 0usize;
+    let mut skipped = 0usize;
+    for slug in &keys {
+        if batch_outcome.failed.iter().any(|(label, _)| label == slug) {
+            continue;
+        }
+        if existing.contains(slug) { written += 1 } else { skipped += 1 }
     }
 
-    Ok(saved)
+    Ok(BoardBatchOutcome { written, skipped, failed: batch_outcome.failed })
 }
 
 // ═══════════════════════════════════════════════════════════════════════════
@@ -1759,154 +6012,192 @@ async fn auto_enrich_boards(db: &D1Database, boards: &[AshbyBoard]) -> Result<us
 /// Strategy:
 ///   - Runs daily at 02:00 UTC (configured in wrangler.toml [triggers])
 ///   - Detects the latest CC index automatically via the collinfo API
-///   - Processes PAGES_PER_RUN pages per invocation (resumable across days)
+///   - Processes a page count sized per run by `CdxCrawlWorker::step`
+///     (resumable across days)
 ///   - Skips if the current index is already fully crawled
 ///   - All progress persisted to D1 `crawl_progress` table
 ///
 /// Each CC index has ~100 pages × 100 records = ~10 000 Ashby board URLs.
-/// At 10 pages/day the full index is covered in ~10 days.
-const PAGES_PER_CRON_RUN: u32 = 10;
+/// Page count per run is sized by `plan_subrequest_caps`/`next_batch_size`,
+/// not a fixed constant — see `CdxCrawlWorker::step`.
 
-/// Number of company boards to fetch jobs for per cron run (Phase 2 job sync).
-/// Kept low to stay within the 30s CPU time budget.
-const BOARDS_PER_JOB_SYNC_RUN: usize = 20;
+/// Number of queued `tasks` rows to run per cron tick.
+const TASKS_PER_CRON_RUN: usize = 5;
 
 #[event(scheduled)]
+/// Brackets `cron_handler_inner` with a `tasks::TaskKind::CronCycle` row, so
+/// a cron-driven crawl/sync cycle — which otherwise only shows up in
+/// `crawl_progress` — is queryable via `GET /tasks`/`GET /tasks/{uid}` like
+/// any other task. Enqueue/mark-processing failures are logged and
+/// swallowed rather than aborting the cycle itself; the crawl running is
+/// more important than its own audit trail existing.
 async fn cron_handler(_event: ScheduledEvent, env: Env, _ctx: ScheduleContext) {
-    if let Err(e) = cron_handler_inner(env).await {
-        console_log!("[ashby-crawler cron] Error: {:?}", e);
+    let db = match env.d1("DB") {
+        Ok(db) => db,
+        Err(e) => {
+            console_log!("[ashby-crawler cron] Error: {:?}", e);
+            return;
+        }
+    };
+
+    let task_uid = match tasks::enqueue_task(&db, tasks::TaskKind::CronCycle, serde_json::json!({})).await {
+        Ok(uid) => {
+            if let Err(e) = tasks::mark_processing(&db, &uid).await {
+                console_log!("[ashby-crawler cron] mark_processing({}) failed: {:?}", uid, e);
+            }
+            Some(uid)
+        }
+        Err(e) => {
+            console_log!("[ashby-crawler cron] failed to enqueue cron_cycle task: {:?}", e);
+            None
+        }
+    };
+
+    match cron_handler_inner(env).await {
+        Ok(()) => {
+            if let Some(uid) = &task_uid {
+                if let Err(e) = tasks::mark_succeeded(&db, uid, &serde_json::json!({})).await {
+                    console_log!("[ashby-crawler cron] mark_succeeded({}) failed: {:?}", uid, e);
+                }
+            }
+        }
+        Err(e) => {
+            console_log!("[ashby-crawler cron] Error: {:?}", e);
+            if let Some(uid) = &task_uid {
+                if let Err(e2) = tasks::mark_failed(&db, uid, &format!("{e:?}")).await {
+                    console_log!("[ashby-crawler cron] mark_failed({}) failed: {:?}", uid, e2);
+                }
+            }
+        }
     }
 }
 
+/// Persists one cron cycle's health numbers to `run_metrics` for
+/// `GET /runs`/`GET /metrics` — wall-clock duration, subrequests issued,
+/// pages/boards/jobs processed, error count, and `occupancy` (the fraction
+/// of `duration_ms` actually spent waiting on HTTP fetches rather than
+/// idle/sequential D1 writes). Best-effort: a write failure here shouldn't
+/// fail a cron cycle that otherwise succeeded.
+#[allow(clippy::too_many_arguments)]
+async fn record_run_metrics(
+    db: &D1Database,
+    duration_ms: f64,
+    http_wait_ms: f64,
+    subrequests: u64,
+    pages_crawled: u64,
+    jobs_synced: u64,
+    boards_enriched: u64,
+    error_count: u32,
+) -> Result<()> {
+    let occupancy = if duration_ms > 0.0 { (http_wait_ms / duration_ms).clamp(0.0, 1.0) } else { 0.0 };
+    db.prepare(
+        "INSERT INTO run_metrics
+            (duration_ms, http_wait_ms, subrequests, pages_crawled, jobs_synced, boards_enriched, error_count, occupancy)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)"
+    ).bind(&[
+        duration_ms.into(), http_wait_ms.into(), (subrequests as f64).into(),
+        (pages_crawled as f64).into(), (jobs_synced as f64).into(), (boards_enriched as f64).into(),
+        (error_count as f64).into(), occupancy.into(),
+    ])?.run().await?;
+    Ok(())
+}
+
 async fn cron_handler_inner(env: Env) -> Result<()> {
+    let run_started_at = js_sys::Date::now();
     console_log!("[ashby-crawler cron] Starting scheduled crawl run...");
     let db = env.d1("DB")?;
 
-    if let Err(e) = apply_pending_migrations(&db).await {
-        console_log!("[migrations] Warning: {:?}", e);
+    // Refuse to run the crawl pipeline against a schema that failed to
+    // migrate — propagating the error aborts this cron tick entirely.
+    let migrations = migrations::apply_pending_migrations(&db).await?;
+    if !migrations.applied.is_empty() {
+        console_log!(
+            "[migrations] applied {} new migration(s): {} ({} already up to date)",
+            migrations.applied.len(), migrations.applied.join(", "), migrations.already_applied.len()
+        );
     }
 
-    // ── Step 1: concurrent reads — CC index list (HTTP) + Phase 2 slug queue (D1) ──
-    // These are fully independent: one hits Common Crawl, one hits D1.
-    // Mirrors rig_concurrent_demo: Arc<Model> shared across concurrent tasks.
-    let (cc_result, slugs_result) = join(
-        list_cc_indexes(),
-        get_company_slugs(&db, BOARDS_PER_JOB_SYNC_RUN),
+    // Phase 1 (CDX crawl) and Phase 2 (job-sync) are independent
+    // `workers::Worker`s now — each owns a `worker_state` row (status/
+    // control/last-error), so `POST /workers/:name/{pause,resume,cancel}`
+    // can steer one without touching the other's `crawl_progress` cursor.
+    // They still tick concurrently here since neither's D1/HTTP work
+    // depends on the other's — same fan-out this cron tick always did, just
+    // behind the `Worker` contract. A `step()` error surfaces as
+    // `WorkerState::Errored` (see `WorkerManager::tick`) rather than
+    // aborting this function, so a dead CDX crawl doesn't also stop
+    // job-sync from making progress.
+    let (cdx_tick, sync_tick) = join(
+        workers::WorkerManager::tick(&db, &mut CdxCrawlWorker),
+        workers::WorkerManager::tick(&db, &mut JobSyncWorker),
     ).await;
 
-    let crawl_id = match cc_result {
-        Ok(indexes) if !indexes.is_empty() => {
-            console_log!("[ashby-crawler cron] Latest CC index: {}", indexes[0]);
-            indexes[0].clone()
+    let mut error_count = 0u32;
+    match &cdx_tick {
+        Ok(state) => {
+            console_log!("[ashby-crawler cron] cdx-crawl tick: {:?}", state);
+            if matches!(state, workers::WorkerState::Errored(_)) {
+                error_count += 1;
+            }
         }
-        Ok(_) => { console_log!("[ashby-crawler cron] No CC indexes, using fallback"); "CC-MAIN-2025-52".to_string() }
-        Err(e) => { console_log!("[ashby-crawler cron] CC index list failed: {:?}, using fallback", e); "CC-MAIN-2025-52".to_string() }
-    };
-    let slugs = slugs_result.unwrap_or_default();
-
-    // ── Step 2: crawl progress check (needs crawl_id — sequential) ───────────
-    let (total_pages, start_page, mut boards_found) = match get_progress(&db, &crawl_id).await? {
-        Some((_, _, ref s, f)) if s == "done" => {
-            console_log!("[ashby-crawler cron] {} already done ({} boards). Phase 1 skipped.", crawl_id, f);
-            (0u32, 0u32, f)
+        Err(e) => {
+            console_log!("[ashby-crawler cron] cdx-crawl tick failed to record: {:?}", e);
+            error_count += 1;
         }
-        Some((t, c, ref s, f)) => {
-            console_log!("[ashby-crawler cron] Resuming {} page {}/{} (status={}, boards={})", crawl_id, c, t, s, f);
-            (t, c, f)
+    }
+    match &sync_tick {
+        Ok(state) => {
+            console_log!("[ashby-crawler cron] job-sync tick: {:?}", state);
+            if matches!(state, workers::WorkerState::Errored(_)) {
+                error_count += 1;
+            }
         }
-        None => {
-            let total = match get_num_pages(&crawl_id).await {
-                Ok(n) => n,
-                Err(e) => { console_log!("[ashby-crawler cron] get_num_pages failed: {:?}", e); return Err(e); }
-            };
-            console_log!("[ashby-crawler cron] New index {} — {} pages total", crawl_id, total);
-            (total, 0, 0)
+        Err(e) => {
+            console_log!("[ashby-crawler cron] job-sync tick failed to record: {:?}", e);
+            error_count += 1;
         }
-    };
-
-    let end_page = if total_pages > 0 {
-        save_progress(&db, &crawl_id, total_pages, start_page, "running", boards_found).await?;
-        (start_page + PAGES_PER_CRON_RUN).min(total_pages)
-    } else {
-        0
-    };
-
-    // ── Step 3: fan-out ALL HTTP concurrently — CDX pages ∥ Ashby board fetches ──
-    // Phase 1 CDX fetches and Phase 2 Ashby API fetches are fully independent HTTP calls.
-    // ConcurrentRunner mirrors rig_concurrent_demo's Arc<Model> + task::spawn pattern:
-    //   demo:  for i in 0..N { task::spawn(async { model.prompt(i) }) }
-    //   here:  runner.run_all(slugs, |slug| fetch_ashby_board_jobs(slug))
-    let cdx_futures: Vec<_> = (start_page..end_page)
-        .map(|page| { let cid = crawl_id.clone(); async move { (page, fetch_cdx_page(&cid, page).await) } })
-        .collect();
-
-    let runner = rig_compat::ConcurrentRunner::new();
-
-    // join_all(CDX) ∥ ConcurrentRunner(Ashby) — maximum HTTP concurrency
-    let (mut cdx_results, (ashby_ok, ashby_err)) = join(
-        join_all(cdx_futures),
-        runner.run_all(slugs.clone(), |slug| async move {
-            fetch_ashby_board_jobs(&slug).await.map(|board| (slug, board))
-        }),
-    ).await;
-
-    for e in &ashby_err {
-        console_log!("[job-sync] board fetch error: {:?}", e);
     }
 
-    // ── Step 4: process CDX results (in-memory, sync) ────────────────────────
-    cdx_results.sort_by_key(|(page, _)| *page);
-    let mut all_new_boards: Vec<AshbyBoard> = Vec::new();
-    let mut page_errors = 0u32;
-    for (page, result) in cdx_results {
-        match result {
-            Ok(boards) => {
-                console_log!("[ashby-crawler cron] Page {}/{}: {} boards", page + 1, total_pages, boards.len());
-                all_new_boards.extend(boards);
-            }
-            Err(e) => {
-                page_errors += 1;
-                console_log!("[ashby-crawler cron] Page {} error ({}): {:?}", page, page_errors, e);
-                if page_errors >= 3 {
-                    save_progress(&db, &crawl_id, total_pages, page, "error", boards_found).await?;
-                    return Err(Error::RustError(format!("Batch aborted after {} page errors", page_errors)));
-                }
-            }
+    // ── Drain the async task queue — crawl_index/enrich_board calls, plus
+    // any lever_sync/greenhouse_sync tasks enqueued outside the cron loop.
+    match tasks::drain_tasks(&db, TASKS_PER_CRON_RUN).await {
+        Ok(ran) if ran > 0 => console_log!("[ashby-crawler cron] Tasks: ran {} queued task(s)", ran),
+        Ok(_) => {}
+        Err(e) => {
+            console_log!("[ashby-crawler cron] Task drain error: {:?}", e);
+            error_count += 1;
         }
     }
 
-    // ── Step 5: concurrent D1 writes — Phase 1 (companies) ∥ Phase 2 (jobs) ──
-    // Phase 1 writes to `companies` table.
-    // Phase 2 writes to `jobs` + `ashby_boards` tables.
-    // Disjoint tables → safe to run concurrently under D1's WAL mode.
-    let all_boards_ref = &all_new_boards;
-    let ((upserted, enriched), phase2_synced) = join(
-        async {
-            let u = if total_pages > 0 { upsert_boards(&db, all_boards_ref).await.unwrap_or(0) } else { 0 };
-            let e = if total_pages > 0 { auto_enrich_boards(&db, all_boards_ref).await.unwrap_or(0) } else { 0 };
-            (u, e)
-        },
-        async {
-            let mut total = 0usize;
-            for (slug, board) in ashby_ok {
-                let title = board.title.clone().unwrap_or_default();
-                total += upsert_jobs_to_d1(&db, &board.jobs, &slug, &title).await.unwrap_or(0);
-            }
-            total
-        },
-    ).await;
-
-    // ── Step 6: save final progress ───────────────────────────────────────────
-    if total_pages > 0 {
-        boards_found += upserted as u32;
-        let status = if end_page >= total_pages { "done" } else { "running" };
-        save_progress(&db, &crawl_id, total_pages, end_page, status, boards_found).await?;
-        console_log!(
-            "[ashby-crawler cron] Phase 1: pages {}-{}/{}, {} upserted, {} enriched, status={}",
-            start_page, end_page.saturating_sub(1), total_pages, upserted, enriched, status
-        );
+    // ── Record this cycle's health numbers (see `record_run_metrics`).
+    // Re-reads the `timings` both phases just persisted (see the
+    // `pages_fetched`/`boards_enriched`/`boards_processed`/`jobs_synced`
+    // additions in `run_crawl_batch`/`run_job_sync_batch`) rather than
+    // threading return values through `workers::Worker::step`, which only
+    // reports `WorkerState`. If a phase was paused/cancelled this tick, its
+    // timings are simply last tick's — an acceptable approximation for a
+    // best-effort health sample.
+    let crawl_id = current_crawl_id().await;
+    let crawl_timings = get_progress_timings(&db, &crawl_id).await.unwrap_or(None);
+    let job_sync_timings = get_progress_timings(&db, "job-sync").await.unwrap_or(None);
+
+    let pages_crawled = crawl_timings.as_ref().and_then(|t| t["pages_fetched"].as_u64()).unwrap_or(0);
+    let boards_enriched = crawl_timings.as_ref().and_then(|t| t["boards_enriched"].as_u64()).unwrap_or(0);
+    let cdx_fetch_ms = crawl_timings.as_ref().and_then(|t| t["cdx_fetch_ms"].as_f64()).unwrap_or(0.0);
+    let jobs_synced = job_sync_timings.as_ref().and_then(|t| t["jobs_synced"].as_u64()).unwrap_or(0);
+    let boards_processed = job_sync_timings.as_ref().and_then(|t| t["boards_processed"].as_u64()).unwrap_or(0);
+    let ashby_fetch_ms = job_sync_timings.as_ref().and_then(|t| t["ashby_fetch_ms"].as_f64()).unwrap_or(0.0);
+    let gh_fetch_ms = job_sync_timings.as_ref().and_then(|t| t["greenhouse_fetch_ms"].as_f64()).unwrap_or(0.0);
+
+    let duration_ms = js_sys::Date::now() - run_started_at;
+    let http_wait_ms = cdx_fetch_ms + ashby_fetch_ms + gh_fetch_ms;
+    // One HTTP request per CDX page/board today — see `plan_subrequest_caps`.
+    let subrequests = pages_crawled + boards_processed;
+    if let Err(e) = record_run_metrics(
+        &db, duration_ms, http_wait_ms, subrequests, pages_crawled, jobs_synced, boards_enriched, error_count,
+    ).await {
+        console_log!("[ashby-crawler cron] run_metrics write failed (non-fatal): {:?}", e);
     }
-    console_log!("[ashby-crawler cron] Phase 2: {} jobs synced from {} boards", phase2_synced, slugs.len());
 
     Ok(())
 }
@@ -1917,10 +6208,13 @@ async fn cron_handler_inner(env: Env) -> Result<()> {
 
 #[event(fetch)]
 async fn main(req: Request, env: Env, _ctx: Context) -> Result<Response> {
-    // Apply any pending D1 migrations before handling the request
+    // Apply any pending D1 migrations before handling the request, and
+    // refuse to serve traffic against a schema that failed to migrate or
+    // whose already-applied ledger no longer matches the deployed SQL.
     if let Ok(db) = env.d1("DB") {
-        if let Err(e) = apply_pending_migrations(&db).await {
-            console_log!("[migrations] Warning: {:?}", e);
+        if let Err(e) = migrations::apply_pending_migrations(&db).await {
+            console_log!("[migrations] refusing to serve traffic: {:?}", e);
+            return error_response(ErrorCode::DatabaseError, &format!("database schema migration failed: {e:?}"));
         }
     }
 
@@ -1932,32 +6226,52 @@ async fn main(req: Request, env: Env, _ctx: Context) -> Result<Response> {
         .get_async("/progress", handle_progress)
         .delete_async("/progress", handle_reset_progress)
         .get_async("/stats", handle_stats)
+        .get_async("/metrics", handle_metrics)
+        .get_async("/runs", handle_runs)
         // Rig-powered endpoints
         .get_async("/search", handle_search)        // Okapi BM25 ranking over enriched corpus
+        .get_async("/rag", handle_rag)               // RAG context/prompt (+ answer if LLM_API_KEY is set) over top-ranked boards
         .get_async("/enrich", handle_enrich)        // ResultPipeline on single board (on-demand)
         .get_async("/enrich-all", handle_enrich_all)// ResultPipeline on batch (on-demand)
+        .post_async("/batch", handle_batch)         // Multi-op search/rank/enrich in one request
         .get_async("/tools", handle_tools)          // ToolRegistry + function-calling schemas
+        .get_async("/tasks", handle_list_tasks)     // Task queue listing. ?type=&status=&from=&limit=
+        .get_async("/tasks/:uid", handle_get_task)  // Poll a single task
+        .get_async("/invalid", handle_list_invalid) // Quarantined records. ?kind=&limit=
+        .get_async("/invalid/reprocess", handle_reprocess_invalid) // Re-attempt ingestion. ?limit=
+        .get_async("/workers", handle_list_workers)  // Background cron-phase status (cdx-crawl, job-sync)
+        .post_async("/workers/:name/:action", handle_worker_action) // pause/resume/cancel a phase
         // Root
         .get("/", |_, _| {
             Response::from_json(&serde_json::json!({
                 "service": "ashby-crawler v0.4 (job-sync)",
                 "core_endpoints": {
-                    "GET /crawl":       "Crawl CC index → D1 (auto-enriches each batch). ?crawl_id=&pages_per_run=",
-                    "GET /boards":      "List/search boards. ?limit=&offset=&search=",
+                    "GET /crawl":       "Crawl CC index → D1 (auto-enriches each batch). ?crawl_id=&pages_per_run=&max_retries=",
+                    "GET /boards":      "List/search boards. ?limit=&offset=&search=&filter=&facets=",
                     "GET /indexes":     "Available CC indexes",
                     "GET /progress":    "Crawl progress (includes job-sync cursor at crawl_id='job-sync')",
                     "DELETE /progress": "Reset a crawl. ?crawl_id=",
-                    "GET /stats":       "Summary stats",
+                    "GET /stats":       "Summary stats, plus sync_runs rollups (jobs/day, most-active boards, error rate per source). ?include_closed=1 to include dead boards",
+                    "GET /metrics":     "Prometheus text exposition of board/job/migration/enrichment/crawl-progress/cron-run health",
+                    "GET /runs":        "Latest cron cycles from run_metrics (duration/occupancy/subrequests/error_count) plus 24h p50/p95 duration, mean occupancy, error rate. ?limit=",
+                    "GET /invalid":     "Quarantined CDX lines/postings that couldn't be ingested. ?kind=&limit=",
+                    "GET /invalid/reprocess": "Re-attempt ingestion for quarantined rows. ?limit=",
+                    "GET /workers":     "Background cron-phase status: cdx-crawl, job-sync (status/control/items_processed/last_error/last_tick_at)",
+                    "POST /workers/:name/:action": "Control a phase: :action is pause, resume, or cancel",
                 },
                 "rig_endpoints": {
-                    "GET /search":      "Okapi BM25 search over enriched corpus. ?q=&top_n=",
+                    "GET /search":      "BM25 + TF-IDF vector search over enriched corpus, fused via RRF. Dead boards excluded unless ?include_closed=1. ?q=&top_n=&mode=lexical|semantic|hybrid&filter=&facets=&include_closed=1",
+                    "GET /rag":         "RAG context/prompt (+ answer if LLM_API_KEY is set) over top-ranked boards. Dead boards excluded unless ?include_closed=1. ?q=&top_n=&mode=lexical|semantic|hybrid&include_closed=1",
                     "GET /enrich":      "On-demand ResultPipeline for one board. ?slug=",
                     "GET /enrich-all":  "On-demand batch ResultPipeline. ?limit=",
-                    "GET /tools":       "ToolRegistry + function-calling schemas. ?call=&args=",
+                    "POST /batch":      "Run multiple search/rank/enrich ops concurrently in one request; any op's include_closed:true includes dead boards for the whole batch. Body: [{op:\"search\",q,top_n?,filter?,include_closed?}, {op:\"rank\",q,top_n?,include_closed?}, {op:\"enrich\",slug}]",
+                    "GET /tools":       "ToolRegistry + function-calling schemas. ?call=&args= (crawl_index/enrich_board enqueue a task)",
+                    "GET /tasks":       "Task queue listing. ?type=&status=&from=&limit=",
+                    "GET /tasks/:uid":  "Poll a single task's status and result/error",
                 },
                 "cron_phases": {
-                    "phase_1": "CC crawl → upsert companies (10 pages/run, resumable)",
-                    "phase_2": "Job sync → fetch Ashby jobs for 20 boards/run, paginated cursor in crawl_progress",
+                    "phase_1": "CC crawl → upsert companies (cdx-crawl worker, adaptive pages/run, resumable)",
+                    "phase_2": "Job sync → fetch Ashby+Greenhouse jobs (job-sync worker, adaptive boards/run, cursor in crawl_progress)",
                 },
                 "rig_patterns": ["Bm25Index", "ResultPipeline", "SlugExtractor", "ToolRegistry"],
             }))
@@ -0,0 +1,380 @@
+use worker::*;
+
+// ═══════════════════════════════════════════════════════════════════════════
+// Schema migrations — ordered, checksummed, applied on first request/cron
+// tick after deploy
+// ═══════════════════════════════════════════════════════════════════════════
+//
+// Split out of lib.rs to keep the ledger/runner next to its own migration
+// list instead of buried in the middle of the request-handling code — see
+// `apply_pending_migrations`'s call sites in `main`/`cron_handler_inner`.
+
+// ═══════════════════════════════════════════════════════════════════════════
+// MIGRATIONS — applied automatically on first request after deploy
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Ordered list of migrations. Each entry is (name, sql). Declaration order
+/// here is *not* authoritative — `apply_pending_migrations` sorts by each
+/// name's numeric prefix before applying, so e.g. `0003_*` always runs before
+/// `0005_*` no matter where it's declared below.
+/// D1 does not support multiple SQL statements inside one `prepare()` call,
+/// so statements within a migration are split on `;`; all of them plus the
+/// `_migrations` ledger row then run as a single `db.batch()` so the
+/// migration is atomic — see `apply_pending_migrations`. A migration's SQL
+/// is also checksummed against `_migrations` once it's applied, so editing a
+/// migration in place after deploy fails loudly instead of silently
+/// diverging from what ran in other environments. If the batch itself fails,
+/// statements are replayed individually to classify the error: one that
+/// means "already in this state" (e.g. column/index already exists) is
+/// ignored; anything else aborts the migration and it is not recorded as
+/// applied — see `is_benign_migration_error`/`apply_pending_migrations`.
+const MIGRATIONS: &[(&str, &str)] = &[
+    ("0002_enrichment", "
+        ALTER TABLE ashby_boards ADD COLUMN company_name  TEXT;
+        ALTER TABLE ashby_boards ADD COLUMN industry_tags TEXT;
+        ALTER TABLE ashby_boards ADD COLUMN tech_signals  TEXT;
+        ALTER TABLE ashby_boards ADD COLUMN enriched_at   TEXT;
+        CREATE INDEX IF NOT EXISTS idx_boards_company  ON ashby_boards(company_name);
+        CREATE INDEX IF NOT EXISTS idx_boards_industry ON ashby_boards(industry_tags);
+    "),
+    ("0005_companies_ashby_enrichment", "
+        ALTER TABLE companies ADD COLUMN ashby_industry_tags TEXT;
+        ALTER TABLE companies ADD COLUMN ashby_tech_signals  TEXT;
+        ALTER TABLE companies ADD COLUMN ashby_size_signal   TEXT;
+        ALTER TABLE companies ADD COLUMN ashby_enriched_at   TEXT;
+    "),
+    ("0003_jobs_external_id_unique", "
+        CREATE UNIQUE INDEX IF NOT EXISTS idx_jobs_external_id ON jobs(external_id);
+    "),
+    ("0004_ashby_boards_sync", "
+        ALTER TABLE ashby_boards ADD COLUMN last_synced_at TEXT;
+        ALTER TABLE ashby_boards ADD COLUMN job_count      INTEGER;
+        ALTER TABLE ashby_boards ADD COLUMN is_active      INTEGER DEFAULT 1;
+    "),
+    ("0006_dedup_and_unique_external_id", "
+        DELETE FROM jobs WHERE id NOT IN (SELECT MIN(id) FROM jobs GROUP BY external_id);
+        CREATE UNIQUE INDEX IF NOT EXISTS idx_jobs_external_id ON jobs(external_id);
+    "),
+    ("0007_job_bodies_split", "
+        CREATE TABLE IF NOT EXISTS job_bodies (
+            hash       TEXT PRIMARY KEY,
+            body       TEXT NOT NULL,
+            first_seen TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+        ALTER TABLE jobs ADD COLUMN description_hash TEXT;
+        CREATE INDEX IF NOT EXISTS idx_jobs_description_hash ON jobs(description_hash);
+    "),
+    ("0008_jobs_content_hash", "
+        ALTER TABLE jobs ADD COLUMN content_hash TEXT;
+    "),
+    ("0009_ashby_boards_lifecycle", "
+        ALTER TABLE ashby_boards ADD COLUMN lifecycle_state TEXT DEFAULT 'discovered';
+        ALTER TABLE ashby_boards ADD COLUMN consecutive_failures INTEGER DEFAULT 0;
+        CREATE INDEX IF NOT EXISTS idx_boards_lifecycle ON ashby_boards(lifecycle_state);
+    "),
+    ("0010_jobs_salary_range", "
+        ALTER TABLE jobs ADD COLUMN salary_min REAL;
+        ALTER TABLE jobs ADD COLUMN salary_max REAL;
+        ALTER TABLE jobs ADD COLUMN salary_currency TEXT;
+        CREATE INDEX IF NOT EXISTS idx_jobs_salary_min ON jobs(salary_min);
+    "),
+    ("0011_tasks", "
+        CREATE TABLE IF NOT EXISTS tasks (
+            uid         TEXT PRIMARY KEY,
+            kind        TEXT NOT NULL,
+            status      TEXT NOT NULL DEFAULT 'enqueued',
+            params      TEXT NOT NULL DEFAULT '{}',
+            result      TEXT,
+            error       TEXT,
+            enqueued_at TEXT NOT NULL DEFAULT (datetime('now')),
+            started_at  TEXT,
+            finished_at TEXT
+        );
+        CREATE INDEX IF NOT EXISTS idx_tasks_status ON tasks(status);
+        CREATE INDEX IF NOT EXISTS idx_tasks_kind ON tasks(kind);
+    "),
+    ("0012_sync_runs", "
+        CREATE TABLE IF NOT EXISTS sync_runs (
+            id          INTEGER PRIMARY KEY AUTOINCREMENT,
+            source_kind TEXT NOT NULL,
+            site        TEXT NOT NULL,
+            fetched     INTEGER NOT NULL DEFAULT 0,
+            inserted    INTEGER NOT NULL DEFAULT 0,
+            updated     INTEGER NOT NULL DEFAULT 0,
+            skipped     INTEGER NOT NULL DEFAULT 0,
+            errors      INTEGER NOT NULL DEFAULT 0,
+            duration_ms INTEGER NOT NULL DEFAULT 0,
+            ran_at      TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+        CREATE INDEX IF NOT EXISTS idx_sync_runs_source_kind ON sync_runs(source_kind);
+        CREATE INDEX IF NOT EXISTS idx_sync_runs_ran_at ON sync_runs(ran_at);
+    "),
+    ("0013_job_locations", "
+        ALTER TABLE jobs ADD COLUMN location_city TEXT;
+        ALTER TABLE jobs ADD COLUMN location_region TEXT;
+        ALTER TABLE jobs ADD COLUMN location_country TEXT;
+        ALTER TABLE jobs ADD COLUMN location_remote INTEGER DEFAULT 0;
+        ALTER TABLE jobs ADD COLUMN location_lat REAL;
+        ALTER TABLE jobs ADD COLUMN location_lng REAL;
+        CREATE TABLE IF NOT EXISTS job_locations (
+            id          INTEGER PRIMARY KEY AUTOINCREMENT,
+            external_id TEXT NOT NULL,
+            raw         TEXT NOT NULL,
+            city        TEXT,
+            region      TEXT,
+            country     TEXT,
+            remote      INTEGER NOT NULL DEFAULT 0,
+            lat         REAL,
+            lng         REAL
+        );
+        CREATE INDEX IF NOT EXISTS idx_job_locations_external_id ON job_locations(external_id);
+        CREATE INDEX IF NOT EXISTS idx_job_locations_lat ON job_locations(lat);
+        CREATE INDEX IF NOT EXISTS idx_job_locations_lng ON job_locations(lng);
+    "),
+    ("0014_board_sync_retry", "
+        ALTER TABLE ashby_boards ADD COLUMN retry_count INTEGER DEFAULT 0;
+        ALTER TABLE ashby_boards ADD COLUMN next_retry_at TEXT;
+        ALTER TABLE ashby_boards ADD COLUMN last_error TEXT;
+        ALTER TABLE ashby_boards ADD COLUMN sync_state TEXT DEFAULT 'pending';
+        ALTER TABLE greenhouse_boards ADD COLUMN retry_count INTEGER DEFAULT 0;
+        ALTER TABLE greenhouse_boards ADD COLUMN next_retry_at TEXT;
+        ALTER TABLE greenhouse_boards ADD COLUMN last_error TEXT;
+        ALTER TABLE greenhouse_boards ADD COLUMN sync_state TEXT DEFAULT 'pending';
+        ALTER TABLE workable_boards ADD COLUMN retry_count INTEGER DEFAULT 0;
+        ALTER TABLE workable_boards ADD COLUMN next_retry_at TEXT;
+        ALTER TABLE workable_boards ADD COLUMN last_error TEXT;
+        ALTER TABLE workable_boards ADD COLUMN sync_state TEXT DEFAULT 'pending';
+        ALTER TABLE lever_boards ADD COLUMN retry_count INTEGER DEFAULT 0;
+        ALTER TABLE lever_boards ADD COLUMN next_retry_at TEXT;
+        ALTER TABLE lever_boards ADD COLUMN last_error TEXT;
+        ALTER TABLE lever_boards ADD COLUMN sync_state TEXT DEFAULT 'pending';
+        CREATE INDEX IF NOT EXISTS idx_ashby_boards_next_retry ON ashby_boards(next_retry_at);
+        CREATE INDEX IF NOT EXISTS idx_gh_boards_next_retry ON greenhouse_boards(next_retry_at);
+        CREATE INDEX IF NOT EXISTS idx_wb_boards_next_retry ON workable_boards(next_retry_at);
+        CREATE INDEX IF NOT EXISTS idx_lever_boards_next_retry ON lever_boards(next_retry_at);
+    "),
+    ("0015_invalid_records", "
+        CREATE TABLE IF NOT EXISTS _invalid_records (
+            id             TEXT PRIMARY KEY,
+            kind           TEXT NOT NULL,
+            ref_id         TEXT NOT NULL,
+            raw_payload    TEXT NOT NULL,
+            error          TEXT NOT NULL,
+            seen_at        TEXT NOT NULL DEFAULT (datetime('now')),
+            reprocessed_at TEXT
+        );
+        CREATE INDEX IF NOT EXISTS idx_invalid_records_kind ON _invalid_records(kind);
+        CREATE INDEX IF NOT EXISTS idx_invalid_records_reprocessed_at ON _invalid_records(reprocessed_at);
+    "),
+    ("0016_crawl_progress_timings", "
+        ALTER TABLE crawl_progress ADD COLUMN timings TEXT;
+    "),
+    ("0017_worker_state", "
+        CREATE TABLE IF NOT EXISTS worker_state (
+            name             TEXT PRIMARY KEY,
+            status           TEXT NOT NULL DEFAULT 'idle',
+            control          TEXT NOT NULL DEFAULT 'run',
+            items_processed  INTEGER NOT NULL DEFAULT 0,
+            last_error       TEXT,
+            last_tick_at     TEXT,
+            created_at       TEXT NOT NULL DEFAULT (datetime('now')),
+            updated_at       TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+    "),
+    ("0018_jobs_lifecycle", "
+        ALTER TABLE jobs ADD COLUMN status TEXT DEFAULT 'open';
+        ALTER TABLE jobs ADD COLUMN closed_at TEXT;
+        CREATE INDEX IF NOT EXISTS idx_jobs_status ON jobs(status);
+    "),
+    ("0019_run_stats", "
+        CREATE TABLE IF NOT EXISTS run_stats (
+            metric        TEXT PRIMARY KEY,
+            avg_value     REAL NOT NULL,
+            sample_count  INTEGER NOT NULL DEFAULT 0,
+            updated_at    TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+    "),
+    ("0020_run_metrics", "
+        CREATE TABLE IF NOT EXISTS run_metrics (
+            id               INTEGER PRIMARY KEY AUTOINCREMENT,
+            started_at       TEXT NOT NULL DEFAULT (datetime('now')),
+            duration_ms      REAL NOT NULL,
+            http_wait_ms     REAL NOT NULL,
+            subrequests      INTEGER NOT NULL DEFAULT 0,
+            pages_crawled    INTEGER NOT NULL DEFAULT 0,
+            jobs_synced      INTEGER NOT NULL DEFAULT 0,
+            boards_enriched  INTEGER NOT NULL DEFAULT 0,
+            error_count      INTEGER NOT NULL DEFAULT 0,
+            occupancy        REAL NOT NULL DEFAULT 0
+        );
+        CREATE INDEX IF NOT EXISTS idx_run_metrics_started_at ON run_metrics(started_at);
+    "),
+    ("0021_search_index_snapshots", "
+        CREATE TABLE IF NOT EXISTS search_index_snapshots (
+            name         TEXT PRIMARY KEY,
+            corpus_hash  TEXT NOT NULL,
+            payload      TEXT NOT NULL,
+            updated_at   TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+    "),
+];
+
+/// Ledger state after a call to `apply_pending_migrations`, so a caller can
+/// decide whether to keep serving traffic (e.g. refuse requests if the
+/// schema failed to migrate).
+pub(crate) struct MigrationReport {
+    pub(crate) applied: Vec<String>,
+    pub(crate) already_applied: Vec<String>,
+    /// `(migration name, statement, error)` for statements whose error was
+    /// classified as benign (e.g. a column/index that already exists) and
+    /// so didn't abort the migration — surfaced rather than silently eaten,
+    /// so a re-deploy onto a hand-patched schema still shows up somewhere.
+    pub(crate) benign_skips: Vec<(String, String, String)>,
+}
+
+/// Numeric prefix of a migration name (`"0003_foo"` → `3`), used to sort
+/// `MIGRATIONS` into monotonic order before applying. Names without a
+/// parseable numeric prefix sort last rather than failing the whole run.
+fn migration_sequence(name: &str) -> u32 {
+    name.split('_').next().and_then(|s| s.parse().ok()).unwrap_or(u32::MAX)
+}
+
+/// Whether a D1 statement error is a known-benign "already in this state"
+/// case — i.e. the schema change it describes has already happened, most
+/// often because the schema was hand-patched before the checksummed ledger
+/// existed. Anything else is a real failure and must abort the migration.
+fn is_benign_migration_error(msg: &str) -> bool {
+    let msg = msg.to_lowercase();
+    msg.contains("duplicate column name") || msg.contains("already exists")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn duplicate_column_is_benign() {
+        assert!(is_benign_migration_error("SqliteError: duplicate column name: foo"));
+    }
+
+    #[test]
+    fn already_exists_is_benign_case_insensitively() {
+        assert!(is_benign_migration_error("table \"widgets\" ALREADY EXISTS"));
+    }
+
+    #[test]
+    fn unrelated_error_is_not_benign() {
+        assert!(!is_benign_migration_error("SqliteError: no such table: widgets"));
+    }
+
+    #[test]
+    fn migration_sequence_parses_numeric_prefix() {
+        assert_eq!(migration_sequence("0003_foo"), 3);
+        assert_eq!(migration_sequence("0021_search_index_snapshots"), 21);
+    }
+
+    #[test]
+    fn migration_sequence_sorts_unparseable_names_last() {
+        assert_eq!(migration_sequence("no_prefix_here"), u32::MAX);
+    }
+}
+
+pub(crate) async fn apply_pending_migrations(db: &D1Database) -> Result<MigrationReport> {
+    // Ensure the migrations tracking table exists, with a checksum column so
+    // a committed migration's text can't be silently edited after the fact.
+    db.prepare(
+        "CREATE TABLE IF NOT EXISTS _migrations (
+            name       TEXT PRIMARY KEY,
+            checksum   TEXT NOT NULL DEFAULT '',
+            applied_at TEXT NOT NULL DEFAULT (datetime('now'))
+        )"
+    )
+    .bind(&[])?
+    .run()
+    .await?;
+    // Deployments from before the checksum column existed won't have it yet;
+    // add it if missing (fails harmlessly, same as the ALTER TABLEs below, if
+    // it's already there).
+    let _ = db.prepare("ALTER TABLE _migrations ADD COLUMN checksum TEXT NOT NULL DEFAULT ''")
+        .bind(&[])?
+        .run()
+        .await;
+
+    let mut ordered: Vec<&(&str, &str)> = MIGRATIONS.iter().collect();
+    ordered.sort_by_key(|(name, _)| migration_sequence(name));
+
+    let mut report = MigrationReport { applied: Vec::new(), already_applied: Vec::new(), benign_skips: Vec::new() };
+
+    for (name, sql) in ordered {
+        let checksum = crate::sha256_hex(sql.as_bytes());
+        let existing = db
+            .prepare("SELECT checksum FROM _migrations WHERE name=?1")
+            .bind(&[(*name).into()])?
+            .first::<serde_json::Value>(None)
+            .await?;
+
+        if let Some(row) = existing {
+            let recorded = row["checksum"].as_str().unwrap_or("");
+            // Deployments migrated before the checksum column existed have
+            // an empty recorded checksum — backfill trust rather than fail.
+            if !recorded.is_empty() && recorded != checksum {
+                return Err(Error::RustError(format!(
+                    "migration '{name}' text has changed since it was applied (recorded checksum {recorded}, current {checksum}) — migrations are append-only; add a new migration instead of editing this one"
+                )));
+            }
+            report.already_applied.push((*name).to_string());
+            continue;
+        }
+
+        // Run each statement (D1 limitation on multi-statement `prepare`),
+        // plus the `_migrations` ledger insert, as a single `db.batch()` so
+        // the common case stays atomic — a mid-migration failure never
+        // leaves earlier statements committed against the live DB while the
+        // migration goes unrecorded. Only when the batch itself fails do we
+        // fall back to running statements one at a time so the failure can
+        // be classified: a benign one (schema already in the target state)
+        // is logged and skipped and the ledger row is still written; any
+        // other error aborts the migration immediately — it is NOT recorded
+        // as applied, so the next request retries it instead of silently
+        // treating a broken migration as done.
+        let statements: Vec<&str> = sql.split(';').map(str::trim).filter(|s| !s.is_empty()).collect();
+        let mut stmts = Vec::with_capacity(statements.len() + 1);
+        for stmt in &statements {
+            stmts.push(db.prepare(stmt).bind(&[])?);
+        }
+        stmts.push(
+            db.prepare("INSERT INTO _migrations (name, checksum) VALUES (?1, ?2)")
+                .bind(&[(*name).into(), checksum.clone().into()])?,
+        );
+
+        if let Err(batch_err) = db.batch(stmts).await {
+            console_log!(
+                "[migrations] '{}': batch failed ({:?}), falling back to per-statement classification",
+                name, batch_err
+            );
+            for stmt in &statements {
+                if let Err(e) = db.prepare(stmt).bind(&[])?.run().await {
+                    let msg = format!("{e:?}");
+                    if is_benign_migration_error(&msg) {
+                        console_log!("[migrations] '{}': statement already applied, skipping ({})", name, msg);
+                        report.benign_skips.push(((*name).to_string(), (*stmt).to_string(), msg));
+                    } else {
+                        return Err(Error::RustError(format!(
+                            "migration '{name}' failed on statement `{stmt}`: {msg} — not recorded as applied"
+                        )));
+                    }
+                }
+            }
+
+            db.prepare("INSERT INTO _migrations (name, checksum) VALUES (?1, ?2)")
+                .bind(&[(*name).into(), checksum.clone().into()])?
+                .run()
+                .await?;
+        }
+
+        report.applied.push((*name).to_string());
+        console_log!("[migrations] Applied: {}", name);
+    }
+
+    Ok(report)
+}
@@ -0,0 +1,334 @@
+use worker::wasm_bindgen::JsValue;
+
+// ═══════════════════════════════════════════════════════════════════════════
+// Field-aware search query parser
+// ═══════════════════════════════════════════════════════════════════════════
+//
+// Turns a raw search string like
+//   rust workplace_type:remote location:"Berlin" -country:US salary_min:>100000
+// into free-text terms ("rust") plus typed filter predicates that compile to a
+// parameterized SQL `WHERE` clause over the `jobs` table. Unknown field names
+// degrade to free text instead of erroring, so a query built by hand never 400s
+// just because it guessed a facet that doesn't exist.
+
+/// A single `field:value` clause, possibly negated.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Filter {
+    pub field: String,
+    pub op: FilterOp,
+    pub value: FilterValue,
+    pub negate: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FilterOp {
+    Eq,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FilterValue {
+    Text(String),
+    Number(f64),
+}
+
+/// A `near:"<place>" within:<radius>` clause — resolved to coordinates and
+/// checked against `job_locations` via haversine distance rather than
+/// compiled to SQL directly (D1/SQLite has no trig functions to do it there).
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeoQuery {
+    pub place: String,
+    pub radius_km: f64,
+}
+
+const DEFAULT_RADIUS_KM: f64 = 50.0;
+
+/// Result of parsing a raw query string.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParsedQuery {
+    /// Free-text terms (including quoted phrases). `search_jobs_filtered`
+    /// matches each one with a `LIKE` over title/company_name/location; they
+    /// are not BM25-scored.
+    pub terms: Vec<String>,
+    /// Typed filter predicates, in the order they were written.
+    pub filters: Vec<Filter>,
+    /// `near:"Place" within:50km`, if present. `within` with no matching
+    /// `near` is ignored (nothing to measure distance from); `near` with no
+    /// `within` defaults to `DEFAULT_RADIUS_KM`.
+    pub geo: Option<GeoQuery>,
+}
+
+impl ParsedQuery {
+    /// Free-text terms rejoined into a single string, e.g. for handing to the
+    /// existing BM25 index unchanged.
+    pub fn text_query(&self) -> String {
+        self.terms.join(" ")
+    }
+}
+
+/// Parse a `within:` value like `50km` or `30mi` into kilometers. A bare
+/// number with no unit is treated as kilometers.
+fn parse_radius_km(raw: &str) -> Option<f64> {
+    let raw = raw.trim();
+    if let Some(n) = raw.strip_suffix("km") {
+        return n.trim().parse::<f64>().ok();
+    }
+    if let Some(n) = raw.strip_suffix("mi") {
+        return n.trim().parse::<f64>().ok().map(|mi| mi * 1.60934);
+    }
+    raw.parse::<f64>().ok()
+}
+
+/// Facets that map onto real `jobs` columns (the Lever-derived set named in
+/// the request this parser was built for: location, team, department,
+/// workplace_type, country, salary_min, salary_max). `team`/`department` live
+/// inside the `categories` JSON blob rather than their own columns, so they
+/// compile to a `json_extract` expression instead of a bare column name.
+fn field_column(field: &str) -> Option<&'static str> {
+    match field {
+        "location" => Some("location"),
+        "workplace_type" => Some("workplace_type"),
+        "country" => Some("country"),
+        "team" => Some("json_extract(categories, '$.team')"),
+        "department" => Some("json_extract(categories, '$.department')"),
+        "salary_min" => Some("salary_min"),
+        "salary_max" => Some("salary_max"),
+        _ => None,
+    }
+}
+
+fn is_numeric_field(field: &str) -> bool {
+    matches!(field, "salary_min" | "salary_max")
+}
+
+/// Split a raw query string into whitespace-separated tokens, treating
+/// `"..."` as a single token (quotes are stripped, not tokenized on).
+fn tokenize(input: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+
+    for c in input.chars() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            c if c.is_whitespace() && !in_quotes => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+}
+
+/// Parse a `field:value` token (with the leading `-` already stripped) into
+/// an operator + typed value. `value` may carry a leading `>`, `>=`, `<`, or
+/// `<=` for range filters (`salary_min:>100000`); anything else is `Eq`.
+fn parse_op_value(field: &str, raw_value: &str) -> (FilterOp, FilterValue) {
+    let (op, text) = if let Some(rest) = raw_value.strip_prefix(">=") {
+        (FilterOp::Gte, rest)
+    } else if let Some(rest) = raw_value.strip_prefix("<=") {
+        (FilterOp::Lte, rest)
+    } else if let Some(rest) = raw_value.strip_prefix('>') {
+        (FilterOp::Gt, rest)
+    } else if let Some(rest) = raw_value.strip_prefix('<') {
+        (FilterOp::Lt, rest)
+    } else {
+        (FilterOp::Eq, raw_value)
+    };
+
+    if is_numeric_field(field) {
+        if let Ok(n) = text.parse::<f64>() {
+            return (op, FilterValue::Number(n));
+        }
+    }
+    (op, FilterValue::Text(text.to_string()))
+}
+
+/// Parse a raw search string into free-text terms and filter predicates.
+/// Unknown field names (e.g. a typo, or a facet we don't expose) fall back to
+/// free text rather than erroring — the clause is kept verbatim as a term.
+pub fn parse_query(input: &str) -> ParsedQuery {
+    let mut parsed = ParsedQuery::default();
+    let mut near_place: Option<String> = None;
+    let mut within_radius_km: Option<f64> = None;
+
+    for token in tokenize(input) {
+        let (negate, rest) = match token.strip_prefix('-') {
+            Some(r) if r.contains(':') => (true, r),
+            _ => (false, token.as_str()),
+        };
+
+        match rest.split_once(':') {
+            Some(("near", place)) if !negate && !place.is_empty() => {
+                near_place = Some(place.to_string());
+            }
+            Some(("within", radius)) if !negate && !radius.is_empty() => {
+                within_radius_km = parse_radius_km(radius);
+            }
+            Some((field, raw_value)) if field_column(field).is_some() && !raw_value.is_empty() => {
+                let (op, value) = parse_op_value(field, raw_value);
+                parsed.filters.push(Filter { field: field.to_string(), op, value, negate });
+            }
+            _ => parsed.terms.push(token),
+        }
+    }
+
+    if let Some(place) = near_place {
+        parsed.geo = Some(GeoQuery { place, radius_km: within_radius_km.unwrap_or(DEFAULT_RADIUS_KM) });
+    }
+
+    parsed
+}
+
+/// Compile the filter list into a parameterized SQL `WHERE` clause fragment
+/// (no leading `WHERE`, empty string when there are no filters) plus its
+/// bind values in `?`-placeholder order. Filters on the same field combine
+/// with OR (e.g. repeated `location:` clauses); distinct fields combine with
+/// AND. An empty filter list compiles to `("", [])`, so callers can fall back
+/// to today's unfiltered scan exactly when there's nothing to filter on.
+pub fn compile_where(filters: &[Filter]) -> (String, Vec<JsValue>) {
+    if filters.is_empty() {
+        return (String::new(), Vec::new());
+    }
+
+    let mut field_order: Vec<&str> = Vec::new();
+    for f in filters {
+        if !field_order.contains(&f.field.as_str()) {
+            field_order.push(&f.field);
+        }
+    }
+
+    let mut binds = Vec::with_capacity(filters.len());
+    let mut groups = Vec::with_capacity(field_order.len());
+
+    for field in field_order {
+        let column = field_column(field).expect("parse_query only emits known fields");
+        let mut clauses = Vec::new();
+        for f in filters.iter().filter(|f| f.field == field) {
+            let op_sql = match f.op {
+                FilterOp::Eq => "=",
+                FilterOp::Gt => ">",
+                FilterOp::Gte => ">=",
+                FilterOp::Lt => "<",
+                FilterOp::Lte => "<=",
+            };
+            let clause = format!("{column} {op_sql} ?");
+            clauses.push(if f.negate { format!("NOT ({clause})") } else { clause });
+            binds.push(match &f.value {
+                FilterValue::Text(s) => s.clone().into(),
+                FilterValue::Number(n) => JsValue::from_f64(*n),
+            });
+        }
+        groups.push(format!("({})", clauses.join(" OR ")));
+    }
+
+    (groups.join(" AND "), binds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_query_free_text_only() {
+        let parsed = parse_query("rust engineer");
+        assert_eq!(parsed.terms, vec!["rust", "engineer"]);
+        assert!(parsed.filters.is_empty());
+        assert_eq!(parsed.geo, None);
+    }
+
+    #[test]
+    fn parse_query_quoted_phrase_is_one_term() {
+        let parsed = parse_query("rust \"staff engineer\" location:Berlin");
+        assert_eq!(parsed.terms, vec!["rust", "staff engineer"]);
+        assert_eq!(parsed.filters.len(), 1);
+        assert_eq!(parsed.filters[0].field, "location");
+    }
+
+    #[test]
+    fn parse_query_negated_filter() {
+        let parsed = parse_query("-country:US");
+        assert_eq!(parsed.filters.len(), 1);
+        assert!(parsed.filters[0].negate);
+        assert_eq!(parsed.filters[0].value, FilterValue::Text("US".to_string()));
+    }
+
+    #[test]
+    fn parse_query_numeric_range_filter() {
+        let parsed = parse_query("salary_min:>100000");
+        assert_eq!(parsed.filters.len(), 1);
+        assert_eq!(parsed.filters[0].op, FilterOp::Gt);
+        assert_eq!(parsed.filters[0].value, FilterValue::Number(100000.0));
+    }
+
+    #[test]
+    fn parse_query_unknown_field_falls_back_to_free_text() {
+        let parsed = parse_query("totally_made_up:value");
+        assert!(parsed.filters.is_empty());
+        assert_eq!(parsed.terms, vec!["totally_made_up:value"]);
+    }
+
+    #[test]
+    fn parse_query_geo_with_explicit_radius() {
+        let parsed = parse_query("near:\"Berlin\" within:30km");
+        let geo = parsed.geo.expect("geo clause should be present");
+        assert_eq!(geo.place, "Berlin");
+        assert!((geo.radius_km - 30.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn parse_query_geo_defaults_radius_when_no_within() {
+        let parsed = parse_query("near:\"Lisbon\"");
+        let geo = parsed.geo.expect("geo clause should be present");
+        assert!((geo.radius_km - DEFAULT_RADIUS_KM).abs() < 1e-9);
+    }
+
+    #[test]
+    fn compile_where_empty_filters_is_empty() {
+        let (sql, binds) = compile_where(&[]);
+        assert_eq!(sql, "");
+        assert!(binds.is_empty());
+    }
+
+    #[test]
+    fn compile_where_same_field_ors_distinct_fields_and() {
+        let filters = vec![
+            Filter { field: "location".into(), op: FilterOp::Eq, value: FilterValue::Text("Berlin".into()), negate: false },
+            Filter { field: "location".into(), op: FilterOp::Eq, value: FilterValue::Text("Lisbon".into()), negate: false },
+            Filter { field: "workplace_type".into(), op: FilterOp::Eq, value: FilterValue::Text("remote".into()), negate: false },
+        ];
+        let (sql, binds) = compile_where(&filters);
+        assert_eq!(sql, "(location = ? OR location = ?) AND (workplace_type = ?)");
+        assert_eq!(binds.len(), 3);
+        assert_eq!(binds[0].as_string().as_deref(), Some("Berlin"));
+        assert_eq!(binds[1].as_string().as_deref(), Some("Lisbon"));
+        assert_eq!(binds[2].as_string().as_deref(), Some("remote"));
+    }
+
+    #[test]
+    fn compile_where_negated_clause_is_wrapped_in_not() {
+        let filters = vec![
+            Filter { field: "country".into(), op: FilterOp::Eq, value: FilterValue::Text("US".into()), negate: true },
+        ];
+        let (sql, _binds) = compile_where(&filters);
+        assert_eq!(sql, "(NOT (country = ?))");
+    }
+
+    #[test]
+    fn compile_where_numeric_bind_round_trips() {
+        let filters = vec![
+            Filter { field: "salary_min".into(), op: FilterOp::Gte, value: FilterValue::Number(90_000.0), negate: false },
+        ];
+        let (sql, binds) = compile_where(&filters);
+        assert_eq!(sql, "(salary_min >= ?)");
+        assert_eq!(binds[0].as_f64(), Some(90_000.0));
+    }
+}
@@ -0,0 +1,330 @@
+use worker::*;
+
+use crate::{greenhouse, lever, workable};
+
+// ═══════════════════════════════════════════════════════════════════════════
+// Async task queue — crawl/sync/enrich pipelines run out-of-band
+// ═══════════════════════════════════════════════════════════════════════════
+//
+// Tool handlers (`crawl_index`, `enrich_board`, …) used to return
+// `{"action": "GET /crawl", ...}` and expect the caller to forward the HTTP
+// request itself — synchronous, and bounded by a single Worker invocation.
+// This module gives them a `tasks` table to enqueue into instead: a handler
+// inserts a row and returns its `uid` immediately; `drain_tasks` (called from
+// the cron tick) dequeues and runs the matching pipeline, transitioning the
+// row through enqueued → processing → succeeded/failed.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskKind {
+    Crawl,
+    LeverSync,
+    GreenhouseSync,
+    WorkableSync,
+    Enrich,
+    /// One full cron tick (`cron_handler_inner`'s CDX crawl + Ashby/Greenhouse
+    /// job-sync + enrich cycle) — recorded so that run, too, leaves a
+    /// queryable record via `GET /tasks` instead of only `crawl_progress`.
+    CronCycle,
+}
+
+impl TaskKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TaskKind::Crawl => "crawl",
+            TaskKind::LeverSync => "lever_sync",
+            TaskKind::GreenhouseSync => "greenhouse_sync",
+            TaskKind::WorkableSync => "workable_sync",
+            TaskKind::Enrich => "enrich",
+            TaskKind::CronCycle => "cron_cycle",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "crawl" => Some(TaskKind::Crawl),
+            "lever_sync" => Some(TaskKind::LeverSync),
+            "greenhouse_sync" => Some(TaskKind::GreenhouseSync),
+            "workable_sync" => Some(TaskKind::WorkableSync),
+            "enrich" => Some(TaskKind::Enrich),
+            "cron_cycle" => Some(TaskKind::CronCycle),
+            _ => None,
+        }
+    }
+}
+
+/// Short, unique-enough task uid: first 20 hex chars of a hash over the task
+/// kind, a millisecond timestamp, and a random draw — collisions would need
+/// two tasks of the same kind enqueued in the same millisecond with the same
+/// `Math::random()` draw, which isn't worth guarding further for a queue this
+/// size.
+fn new_task_uid(kind: TaskKind) -> String {
+    let raw = format!("{}-{}-{}", kind.as_str(), js_sys::Date::now(), js_sys::Math::random());
+    crate::sha256_hex(raw.as_bytes())[..20].to_string()
+}
+
+/// Insert a new `enqueued` row and return its uid for the caller to poll.
+pub async fn enqueue_task(db: &D1Database, kind: TaskKind, params: serde_json::Value) -> Result<String> {
+    let uid = new_task_uid(kind);
+    db.prepare(
+        "INSERT INTO tasks (uid, kind, status, params, enqueued_at) VALUES (?1, ?2, 'enqueued', ?3, datetime('now'))"
+    ).bind(&[
+        uid.clone().into(),
+        kind.as_str().into(),
+        serde_json::to_string(&params).unwrap_or_else(|_| "{}".to_string()).into(),
+    ])?.run().await?;
+    Ok(uid)
+}
+
+/// GET /tasks/{uid} — fetch a single task row, or `None` if it doesn't exist.
+pub async fn get_task(db: &D1Database, uid: &str) -> Result<Option<serde_json::Value>> {
+    db.prepare("SELECT * FROM tasks WHERE uid=?1")
+        .bind(&[uid.into()])?
+        .first::<serde_json::Value>(None)
+        .await
+}
+
+/// GET /tasks?type=&status=&from=&limit= — most recent tasks first,
+/// optionally filtered by one-or-more comma-separated kinds/statuses and
+/// paginated via `from`/the returned `next` cursor.
+///
+/// The cursor is the row's SQLite `rowid`, not the public `uid` — `uid` is a
+/// content hash (see `new_task_uid`) and isn't sortable, but every `tasks`
+/// row still has an implicit monotonically-increasing `rowid` since the
+/// table's primary key isn't an `INTEGER` column. Returns `(rows, next)`,
+/// where `next` is `Some(rowid)` to pass back as `from` for the next page,
+/// or `None` once there's nothing older left.
+pub async fn list_tasks(
+    db: &D1Database,
+    statuses: &[String],
+    kinds: &[String],
+    before: Option<i64>,
+    limit: u32,
+) -> Result<(Vec<serde_json::Value>, Option<i64>)> {
+    let mut clauses = Vec::new();
+    let mut binds: Vec<worker::wasm_bindgen::JsValue> = Vec::new();
+    if !statuses.is_empty() {
+        clauses.push(format!("status IN ({})", vec!["?"; statuses.len()].join(",")));
+        for s in statuses {
+            binds.push(s.as_str().into());
+        }
+    }
+    if !kinds.is_empty() {
+        clauses.push(format!("kind IN ({})", vec!["?"; kinds.len()].join(",")));
+        for k in kinds {
+            binds.push(k.as_str().into());
+        }
+    }
+    if let Some(cursor) = before {
+        clauses.push("rowid < ?".to_string());
+        binds.push((cursor as f64).into());
+    }
+    let where_sql = if clauses.is_empty() { "1=1".to_string() } else { clauses.join(" AND ") };
+    // Fetch one extra row so we can tell whether a further page exists
+    // without a second COUNT(*) round-trip.
+    let sql = format!("SELECT rowid AS seq, * FROM tasks WHERE {where_sql} ORDER BY rowid DESC LIMIT ?");
+    binds.push(((limit as u64 + 1) as f64).into());
+
+    let mut rows = db.prepare(&sql).bind(&binds)?.all().await?.results::<serde_json::Value>()?;
+    let next = if rows.len() > limit as usize {
+        rows.truncate(limit as usize);
+        rows.last().and_then(|r| r["seq"].as_i64())
+    } else {
+        None
+    };
+    Ok((rows, next))
+}
+
+/// Record a `sync_runs` row for a board/site whose HTTP fetch or JSON parse
+/// failed outright, so `error_rate_per_source` in `GET /stats` counts it —
+/// otherwise a site that 404s or returns garbage every run would never show
+/// up there, since `upsert_lever_jobs_to_d1`/`upsert_greenhouse_jobs_to_d1`
+/// (which record the success-path metrics) never get called. Also feeds the
+/// failure into `record_sync_failure`'s per-board retry/backoff tracking, so
+/// a board that keeps 404ing backs off instead of being retried every run.
+async fn record_fetch_failure(db: &D1Database, source_kind: &str, site: &str, err: &Error) {
+    crate::record_sync_run(db, source_kind, site, &crate::SyncRunMetrics {
+        fetched: 0,
+        inserted: 0,
+        updated: 0,
+        skipped: 0,
+        errors: 1,
+        duration_ms: 0,
+    }).await;
+    if let Err(e) = crate::record_sync_failure(db, source_kind, site, &format!("{err:?}")).await {
+        console_log!("[tasks] record_sync_failure({}, '{}') skipped: {:?}", source_kind, site, e);
+    }
+}
+
+/// Run one pipeline to completion and return a small JSON summary of what it did.
+async fn run_pipeline(db: &D1Database, kind: TaskKind, params: &serde_json::Value) -> Result<serde_json::Value> {
+    match kind {
+        // Driven directly by `cron_handler`, not dequeued via `run_next_task` —
+        // its `tasks` row is only ever created and marked processing/succeeded/
+        // failed from there, never routed through this pipeline dispatch.
+        TaskKind::CronCycle => Err(Error::RustError(
+            "cron_cycle tasks are run by cron_handler directly, not via run_pipeline".into()
+        )),
+        TaskKind::Crawl => {
+            let crawl_id = params.get("crawl_id").and_then(|v| v.as_str()).unwrap_or("CC-MAIN-2025-52");
+            let pages_per_run = params.get("pages_per_run").and_then(|v| v.as_u64()).unwrap_or(3) as u32;
+            crate::run_crawl_batch(db, crawl_id, pages_per_run, crate::RETRY_MAX_ATTEMPTS).await
+        }
+        TaskKind::LeverSync => {
+            let site = params.get("site").and_then(|v| v.as_str())
+                .ok_or_else(|| Error::RustError("lever_sync task missing 'site' param".into()))?;
+            let postings = match lever::fetch_lever_board_jobs(site).await {
+                Ok(postings) => postings,
+                Err(e) => {
+                    record_fetch_failure(db, "lever", site, &e).await;
+                    return Err(e);
+                }
+            };
+            let outcome = lever::upsert_lever_jobs_to_d1(db, &postings, site).await?;
+            Ok(serde_json::json!({
+                "site": site,
+                "jobs_synced": outcome.committed,
+                "jobs_failed": outcome.failed.len(),
+            }))
+        }
+        TaskKind::GreenhouseSync => {
+            let token = params.get("token").and_then(|v| v.as_str())
+                .ok_or_else(|| Error::RustError("greenhouse_sync task missing 'token' param".into()))?;
+            let board = match greenhouse::fetch_greenhouse_board_jobs(token).await {
+                Ok(board) => board,
+                Err(e) => {
+                    record_fetch_failure(db, "greenhouse", token, &e).await;
+                    return Err(e);
+                }
+            };
+            let outcome = greenhouse::upsert_greenhouse_jobs_to_d1(db, &board.jobs, token, board.name.as_deref().unwrap_or("")).await?;
+            Ok(serde_json::json!({
+                "token": token,
+                "jobs_synced": outcome.committed,
+                "jobs_failed": outcome.failed.len(),
+            }))
+        }
+        TaskKind::WorkableSync => {
+            let shortcode = params.get("shortcode").and_then(|v| v.as_str())
+                .ok_or_else(|| Error::RustError("workable_sync task missing 'shortcode' param".into()))?;
+            let board = match workable::fetch_workable_board_jobs(shortcode).await {
+                Ok(board) => board,
+                Err(e) => {
+                    record_fetch_failure(db, "workable", shortcode, &e).await;
+                    return Err(e);
+                }
+            };
+            let outcome = workable::upsert_workable_jobs_to_d1(db, &board, shortcode).await?;
+            Ok(serde_json::json!({
+                "shortcode": shortcode,
+                "jobs_synced": outcome.committed,
+                "jobs_failed": outcome.failed.len(),
+            }))
+        }
+        TaskKind::Enrich => {
+            let slug = params.get("slug").and_then(|v| v.as_str())
+                .ok_or_else(|| Error::RustError("enrich task missing 'slug' param".into()))?;
+            let row = db
+                .prepare("SELECT slug, url, last_seen, crawl_id FROM ashby_boards WHERE slug=?1")
+                .bind(&[slug.into()])?
+                .first::<serde_json::Value>(None).await?
+                .ok_or_else(|| Error::RustError(format!("no board found for slug '{slug}'")))?;
+            let board = crate::AshbyBoard {
+                slug: row["slug"].as_str().unwrap_or(slug).to_string(),
+                url: row["url"].as_str().unwrap_or_default().to_string(),
+                timestamp: row["last_seen"].as_str().unwrap_or_default().to_string(),
+                crawl_id: row["crawl_id"].as_str().unwrap_or_default().to_string(),
+                status: None,
+                mime: None,
+                warc_file: None,
+                warc_offset: None,
+                warc_length: None,
+            };
+            let enriched = crate::auto_enrich_boards(db, std::slice::from_ref(&board)).await?;
+            Ok(serde_json::json!({
+                "slug": slug,
+                "enriched": enriched.written,
+                "enrich_skipped": enriched.skipped,
+                "enrich_failed": enriched.failed.len(),
+            }))
+        }
+    }
+}
+
+/// Pop the oldest `enqueued` task, if any, and run it to completion —
+/// succeeded/failed status and a result/error summary are written back
+/// either way. Returns `true` if a task was found and run, `false` if the
+/// queue was empty.
+pub async fn run_next_task(db: &D1Database) -> Result<bool> {
+    let next = db
+        .prepare("SELECT uid, kind, params FROM tasks WHERE status='enqueued' ORDER BY enqueued_at ASC LIMIT 1")
+        .bind(&[])?
+        .first::<serde_json::Value>(None)
+        .await?;
+    let Some(row) = next else { return Ok(false) };
+
+    let uid = row["uid"].as_str().unwrap_or_default().to_string();
+    let kind_str = row["kind"].as_str().unwrap_or_default().to_string();
+    let params: serde_json::Value = row["params"].as_str()
+        .and_then(|s| serde_json::from_str(s).ok())
+        .unwrap_or(serde_json::json!({}));
+
+    // Claim it — the `status='enqueued'` guard means a second overlapping cron
+    // tick that raced us here updates zero rows instead of double-running it.
+    db.prepare("UPDATE tasks SET status='processing', started_at=datetime('now') WHERE uid=?1 AND status='enqueued'")
+        .bind(&[uid.clone().into()])?
+        .run().await?;
+
+    let Some(kind) = TaskKind::parse(&kind_str) else {
+        mark_failed(db, &uid, &format!("unknown task kind '{kind_str}'")).await?;
+        return Ok(true);
+    };
+
+    match run_pipeline(db, kind, &params).await {
+        Ok(result) => mark_succeeded(db, &uid, &result).await?,
+        Err(e) => {
+            console_log!("[tasks] {} '{}' failed: {:?}", kind_str, uid, e);
+            mark_failed(db, &uid, &format!("{e:?}")).await?;
+        }
+    }
+    Ok(true)
+}
+
+/// Mark an `enqueued` task `processing` — called right before its pipeline
+/// starts, so `GET /tasks/{uid}` reflects it's underway rather than still
+/// queued.
+pub async fn mark_processing(db: &D1Database, uid: &str) -> Result<()> {
+    db.prepare("UPDATE tasks SET status='processing', started_at=datetime('now') WHERE uid=?1")
+        .bind(&[uid.into()])?
+        .run().await?;
+    Ok(())
+}
+
+/// Mark a task `succeeded`, storing `result` as its JSON details.
+pub async fn mark_succeeded(db: &D1Database, uid: &str, result: &serde_json::Value) -> Result<()> {
+    db.prepare("UPDATE tasks SET status='succeeded', finished_at=datetime('now'), result=?1 WHERE uid=?2")
+        .bind(&[serde_json::to_string(result).unwrap_or_default().into(), uid.into()])?
+        .run().await?;
+    Ok(())
+}
+
+/// Mark a task `failed`, storing `error` as its error detail.
+pub async fn mark_failed(db: &D1Database, uid: &str, error: &str) -> Result<()> {
+    db.prepare("UPDATE tasks SET status='failed', finished_at=datetime('now'), error=?1 WHERE uid=?2")
+        .bind(&[error.into(), uid.into()])?
+        .run().await?;
+    Ok(())
+}
+
+/// Drain up to `max` queued tasks, one at a time, stopping early once the
+/// queue is empty. Called from the cron tick so long-running pipelines make
+/// progress without blocking an HTTP request.
+pub async fn drain_tasks(db: &D1Database, max: usize) -> Result<usize> {
+    let mut ran = 0;
+    while ran < max {
+        if !run_next_task(db).await? {
+            break;
+        }
+        ran += 1;
+    }
+    Ok(ran)
+}
@@ -0,0 +1,180 @@
+use std::io::Read;
+
+use flate2::read::GzDecoder;
+use worker::*;
+
+use crate::types::DiscoveredBoard;
+
+// ═══════════════════════════════════════════════════════════════════════════
+// Common Crawl WARC payload retrieval
+// ═══════════════════════════════════════════════════════════════════════════
+//
+// `DiscoveredBoard` already carries `warc_file`/`warc_offset`/`warc_length` from
+// the CDX index, but nothing previously read the archived page itself. This
+// module turns those pointers into the actual archived HTML so discovery-time
+// enrichment doesn't need to hit the live ATS API.
+
+/// Metadata scraped from an archived board page.
+#[derive(Debug, Default, Clone)]
+pub struct ArchivedPageMeta {
+    pub title: Option<String>,
+    pub og_site_name: Option<String>,
+    pub og_description: Option<String>,
+    pub meta_description: Option<String>,
+}
+
+impl ArchivedPageMeta {
+    /// Best-effort company name: prefer `og:site_name`, then a cleaned `<title>`.
+    pub fn company_name(&self) -> Option<String> {
+        self.og_site_name.clone().or_else(|| {
+            self.title.as_ref().map(|t| {
+                t.split(['|', '-', '·'])
+                    .next()
+                    .unwrap_or(t)
+                    .trim()
+                    .to_string()
+            })
+        })
+    }
+}
+
+/// Fetch the raw bytes of a single WARC record via an HTTP range request
+/// against the Common Crawl data host, then gunzip it. Each WARC record in
+/// CC is stored as an independent single-member gzip stream, so one
+/// `flate2` pass over the range response is sufficient.
+pub async fn fetch_warc_record(warc_file: &str, offset: u64, length: u64) -> Result<Vec<u8>> {
+    let url = format!("https://data.commoncrawl.org/{warc_file}");
+    let range = format!("bytes={}-{}", offset, offset + length.saturating_sub(1));
+
+    let mut headers = Headers::new();
+    headers.set("Range", &range)?;
+    let req = Request::new_with_init(
+        &url,
+        RequestInit::new().with_method(Method::Get).with_headers(headers),
+    )?;
+
+    let mut resp = Fetch::Request(req).send().await?;
+    let status = resp.status_code();
+    if status != 206 && status != 200 {
+        return Err(Error::RustError(format!(
+            "WARC range request returned {status} for {warc_file} @ {offset}+{length}"
+        )));
+    }
+    let gz_bytes = resp.bytes().await?;
+
+    let mut decoder = GzDecoder::new(&gz_bytes[..]);
+    let mut out = Vec::with_capacity(gz_bytes.len() * 3);
+    decoder
+        .read_to_end(&mut out)
+        .map_err(|e| Error::RustError(format!("WARC gunzip failed: {e}")))?;
+    Ok(out)
+}
+
+/// Split a raw WARC record into its HTML body by skipping the `WARC/1.0`
+/// record header and the embedded HTTP response status line/headers, each of
+/// which ends at the first blank line.
+pub fn extract_html_body(record: &[u8]) -> Option<String> {
+    let text = String::from_utf8_lossy(record);
+    let after_warc_header = skip_block(&text, 0)?;
+    let after_http_header = skip_block(&text, after_warc_header)?;
+    Some(text[after_http_header..].to_string())
+}
+
+/// Find the end of the next CRLFCRLF/LFLF-delimited header block starting at `from`.
+fn skip_block(text: &str, from: usize) -> Option<usize> {
+    let rest = &text[from..];
+    let end = rest.find("\r\n\r\n").map(|i| i + 4)
+        .or_else(|| rest.find("\n\n").map(|i| i + 2))?;
+    Some(from + end)
+}
+
+/// Extract `<title>`, OpenGraph, and meta-description tags from archived HTML.
+/// Deliberately dependency-free (no HTML parser) — scans for a small set of
+/// well-known tag shapes, tolerant of attribute ordering and casing.
+pub fn extract_meta(html: &str) -> ArchivedPageMeta {
+    ArchivedPageMeta {
+        title: extract_tag_text(html, "title"),
+        og_site_name: extract_meta_content(html, "og:site_name"),
+        og_description: extract_meta_content(html, "og:description"),
+        meta_description: extract_meta_content(html, "description"),
+    }
+}
+
+fn extract_tag_text(html: &str, tag: &str) -> Option<String> {
+    let open = format!("<{tag}");
+    let lower = html.to_lowercase();
+    let start = lower.find(&open)?;
+    let open_end = lower[start..].find('>')? + start + 1;
+    let close = lower[open_end..].find(&format!("</{tag}>"))? + open_end;
+    let raw = &html[open_end..close];
+    let decoded = decode_entities(raw.trim());
+    if decoded.is_empty() { None } else { Some(decoded) }
+}
+
+/// Find `<meta property="X" content="Y">` or `<meta name="X" content="Y">`
+/// (attribute order and quoting can vary, so this scans attribute-by-attribute
+/// rather than assuming a fixed layout).
+fn extract_meta_content(html: &str, key: &str) -> Option<String> {
+    let lower = html.to_lowercase();
+    let mut search_from = 0usize;
+    while let Some(rel_start) = lower[search_from..].find("<meta") {
+        let tag_start = search_from + rel_start;
+        let tag_end = lower[tag_start..].find('>').map(|i| tag_start + i)?;
+        let tag = &html[tag_start..tag_end];
+        let tag_lower = &lower[tag_start..tag_end];
+        let matches_key = attr_value(tag_lower, tag, "property") == Some(key.to_string())
+            || attr_value(tag_lower, tag, "name") == Some(key.to_string());
+        if matches_key {
+            if let Some(content) = attr_value(tag_lower, tag, "content") {
+                let decoded = decode_entities(&content);
+                if !decoded.is_empty() {
+                    return Some(decoded);
+                }
+            }
+        }
+        search_from = tag_end + 1;
+    }
+    None
+}
+
+/// Pull `attr="value"` (or `attr='value'`) out of a tag, matching on the
+/// lowercase tag for attribute names but slicing the original for the value.
+fn attr_value(tag_lower: &str, tag_original: &str, attr: &str) -> Option<String> {
+    let needle = format!("{attr}=");
+    let idx = tag_lower.find(&needle)?;
+    let after = idx + needle.len();
+    let quote = tag_original.as_bytes().get(after).copied()?;
+    if quote != b'"' && quote != b'\'' {
+        return None;
+    }
+    let value_start = after + 1;
+    let rest = &tag_original[value_start..];
+    let end = rest.find(quote as char)?;
+    Some(rest[..end].to_string())
+}
+
+fn decode_entities(s: &str) -> String {
+    s.replace("&amp;", "&")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&nbsp;", " ")
+}
+
+/// Fetch + parse the archived page for a discovered board, returning `None`
+/// when the board has no WARC pointers (e.g. a synthetic/manual entry) rather
+/// than erroring.
+pub async fn fetch_archived_board_meta(board: &DiscoveredBoard) -> Result<Option<ArchivedPageMeta>> {
+    let (warc_file, offset, length) = match (&board.warc_file, board.warc_offset, board.warc_length) {
+        (Some(f), Some(o), Some(l)) => (f, o, l),
+        _ => return Ok(None),
+    };
+
+    let record = fetch_warc_record(warc_file, offset, length).await?;
+    let html = match extract_html_body(&record) {
+        Some(h) => h,
+        None => return Ok(None),
+    };
+    Ok(Some(extract_meta(&html)))
+}
@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 use worker::*;
 
+use crate::ats::{self, AtsSource, ExtraColumn, ExtraUpdateMode, JobRow};
+
 // ═══════════════════════════════════════════════════════════════════════════
 // Workable Widget API v1 types
 // ═══════════════════════════════════════════════════════════════════════════
@@ -96,95 +98,68 @@ pub async fn fetch_workable_board_jobs(shortcode: &str) -> Result<WorkableBoardR
 }
 
 // ═══════════════════════════════════════════════════════════════════════════
-// UPSERT
+// UPSERT — via the generic `ats::AtsSource` pipeline
 // ═══════════════════════════════════════════════════════════════════════════
 
-/// Upsert Workable jobs into D1 `jobs` table.
-/// External ID = job `url` (canonical `https://apply.workable.com/j/{shortcode}`).
-/// `telecommuting: true` → `workplace_type = 'remote'`.
-pub async fn upsert_workable_jobs_to_d1(
-    db: &D1Database,
-    response: &WorkableBoardResponse,
-    shortcode: &str,
-) -> Result<usize> {
-    let company_name = response.name.as_deref().unwrap_or("");
-    // Fallback: title-case the shortcode if the API didn't return a name
-    let company_name_owned: String;
-    let company_name = if company_name.is_empty() {
-        company_name_owned = shortcode
-            .split(|c: char| c == '-' || c == '_')
-            .map(|w| {
-                let mut chars = w.chars();
-                match chars.next() {
-                    None => String::new(),
-                    Some(c) => c.to_uppercase().to_string() + chars.as_str(),
+pub struct WorkableSource;
+
+impl AtsSource for WorkableSource {
+    type Posting = WorkableJob;
+
+    fn source_kind() -> &'static str { "workable" }
+    fn board_table() -> &'static str { "workable_boards" }
+    fn board_key_column() -> &'static str { "shortcode" }
+    fn board_url(site: &str) -> String { format!("https://apply.workable.com/{site}") }
+
+    async fn fetch(site: &str) -> Result<Vec<Self::Posting>> {
+        fetch_workable_board_jobs(site).await.map(|board| board.jobs)
+    }
+
+    fn external_id(job: &Self::Posting) -> Option<String> {
+        job.url.as_deref().filter(|u| !u.is_empty()).map(str::to_string)
+    }
+
+    fn raw_locations(job: &Self::Posting) -> Vec<String> {
+        let mut locations: Vec<String> = Vec::new();
+        let primary = match (job.city.as_deref(), job.country.as_deref()) {
+            (Some(city), Some(country)) if !city.is_empty() && !country.is_empty() => Some(format!("{city}, {country}")),
+            (Some(city), _) if !city.is_empty() => Some(city.to_string()),
+            (_, Some(country)) if !country.is_empty() => Some(country.to_string()),
+            _ => None,
+        };
+        if let Some(primary) = primary {
+            locations.push(primary);
+        }
+        if let Some(extra) = job.locations.as_ref() {
+            for loc in extra {
+                if loc.hidden.unwrap_or(false) {
+                    continue;
+                }
+                let text = match (loc.city.as_deref(), loc.country.as_deref()) {
+                    (Some(city), Some(country)) if !city.is_empty() && !country.is_empty() => Some(format!("{city}, {country}")),
+                    (Some(city), _) if !city.is_empty() => Some(city.to_string()),
+                    (_, Some(country)) if !country.is_empty() => Some(country.to_string()),
+                    _ => None,
+                };
+                if let Some(text) = text {
+                    if !locations.contains(&text) {
+                        locations.push(text);
+                    }
                 }
-            })
-            .collect::<Vec<_>>()
-            .join(" ");
-        &company_name_owned
-    } else {
-        company_name
-    };
-
-    const JOB_SQL: &str = "INSERT INTO jobs (
-                external_id, source_kind, source_id, company_key, company_name,
-                title, url, location, country,
-                posted_at,
-                categories, workplace_type,
-                departments, ats_created_at, updated_at
-            ) VALUES (
-                ?1, 'workable', ?2, ?3, ?4,
-                ?5, ?6, NULLIF(?7,''), NULLIF(?8,''),
-                COALESCE(NULLIF(?9,''), datetime('now')),
-                NULLIF(?10,''), NULLIF(?11,''),
-                NULLIF(?12,''), NULLIF(?9,''), datetime('now')
-            )
-            ON CONFLICT(external_id) DO UPDATE SET
-                source_id=excluded.source_id,
-                company_key=excluded.company_key,
-                company_name=COALESCE(excluded.company_name, company_name),
-                title=excluded.title,
-                url=excluded.url,
-                location=COALESCE(excluded.location, location),
-                country=COALESCE(excluded.country, country),
-                posted_at=COALESCE(excluded.posted_at, posted_at),
-                categories=excluded.categories,
-                workplace_type=COALESCE(excluded.workplace_type, workplace_type),
-                departments=excluded.departments,
-                ats_created_at=excluded.ats_created_at,
-                updated_at=datetime('now')";
-
-    let mut stmts = Vec::with_capacity(response.jobs.len() + 2);
-    let mut count = 0usize;
-
-    for job in &response.jobs {
-        let url = job.url.as_deref().unwrap_or("");
-        if url.is_empty() {
-            console_log!("[job-sync:workable] skipping job '{}' (no url) from account {}", job.title, shortcode);
-            continue;
+            }
         }
-        let external_id = url.to_string();
+        locations
+    }
 
-        // Build location string from city + country
+    fn to_job_row(job: &Self::Posting, site: &str, company_name: &str) -> JobRow {
         let location = match (job.city.as_deref(), job.country.as_deref()) {
-            (Some(city), Some(country)) if !city.is_empty() && !country.is_empty() => format!("{}, {}", city, country),
+            (Some(city), Some(country)) if !city.is_empty() && !country.is_empty() => format!("{city}, {country}"),
             (Some(city), _) if !city.is_empty() => city.to_string(),
             (_, Some(country)) if !country.is_empty() => country.to_string(),
             _ => String::new(),
         };
-
-        let workplace_type = if job.telecommuting.unwrap_or(false) {
-            "remote"
-        } else {
-            "on-site"
-        };
-
-        let posted_at = job.published_on.as_deref()
-            .or(job.created_at.as_deref())
-            .unwrap_or("");
-
-        // Store employment_type, experience, function, industry as JSON categories
+        let workplace_type = if job.telecommuting.unwrap_or(false) { "remote" } else { "on-site" };
+        let posted_at = job.published_on.clone().or_else(|| job.created_at.clone()).unwrap_or_default();
         let categories_json = serde_json::to_string(&serde_json::json!({
             "employment_type": job.employment_type,
             "experience": job.experience,
@@ -193,57 +168,32 @@ pub async fn upsert_workable_jobs_to_d1(
             "education": job.education,
         })).unwrap_or_default();
 
-        let department = job.department.as_deref().unwrap_or("");
-
-        stmts.push(db.prepare(JOB_SQL).bind(&[
-            external_id.into(),                     // ?1  external_id
-            shortcode.into(),                       // ?2  source_id
-            shortcode.into(),                       // ?3  company_key
-            company_name.to_string().into(),        // ?4  company_name
-            job.title.clone().into(),               // ?5  title
-            url.into(),                             // ?6  url
-            location.into(),                        // ?7  location
-            job.country.as_deref().unwrap_or("").into(), // ?8  country
-            posted_at.into(),                       // ?9  posted_at / ats_created_at
-            categories_json.into(),                 // ?10 categories
-            workplace_type.into(),                  // ?11 workplace_type
-            department.into(),                      // ?12 departments
-        ])?);
-        count += 1;
-    }
-
-    // Track in workable_boards table
-    stmts.push(db.prepare(
-        "INSERT INTO workable_boards (shortcode, url, first_seen, last_seen, crawl_id, last_synced_at, job_count, is_active)
-         VALUES (?1, ?2, datetime('now'), datetime('now'), 'job-sync', datetime('now'), ?3, 1)
-         ON CONFLICT(shortcode) DO UPDATE SET
-           last_synced_at=datetime('now'),
-           job_count=?3,
-           is_active=1,
-           updated_at=datetime('now')"
-    ).bind(&[
-        shortcode.into(),
-        format!("https://apply.workable.com/{}", shortcode).into(),
-        (count as f64).into(),
-    ])?);
-
-    // Update company name from the API response
-    if !company_name.is_empty() {
-        stmts.push(db.prepare(
-            "UPDATE companies SET name=?1, updated_at=datetime('now') WHERE key=?2 AND (name IS NULL OR name='' OR name=key)"
-        ).bind(&[
-            company_name.to_string().into(),
-            shortcode.into(),
-        ])?);
-    } else {
-        stmts.push(db.prepare("UPDATE companies SET updated_at=datetime('now') WHERE key=?1")
-            .bind(&[shortcode.into()])?);
-    }
-
-    const BATCH_SIZE: usize = 100;
-    for chunk in stmts.chunks(BATCH_SIZE) {
-        let _ = db.batch(chunk.to_vec()).await;
+        JobRow {
+            source_id: site.to_string(),
+            company_key: site.to_string(),
+            company_name: company_name.to_string(),
+            title: job.title.clone(),
+            url: job.url.clone().unwrap_or_default(),
+            location,
+            posted_at,
+            country: job.country.clone().unwrap_or_default(),
+            workplace_type: workplace_type.to_string(),
+            extra: vec![
+                ExtraColumn::text("categories", &categories_json, ExtraUpdateMode::Overwrite),
+                ExtraColumn::text("departments", job.department.as_deref().unwrap_or(""), ExtraUpdateMode::Overwrite),
+            ],
+            ..Default::default()
+        }
     }
+}
 
-    Ok(count)
+/// Upsert Workable jobs into D1 `jobs` table.
+/// External ID = job `url` (canonical `https://apply.workable.com/j/{shortcode}`).
+/// `telecommuting: true` → `workplace_type = 'remote'`.
+pub async fn upsert_workable_jobs_to_d1(
+    db: &D1Database,
+    response: &WorkableBoardResponse,
+    shortcode: &str,
+) -> Result<crate::BatchOutcome> {
+    ats::upsert_jobs_to_d1::<WorkableSource>(db, &response.jobs, shortcode, response.name.as_deref()).await
 }
@@ -0,0 +1,178 @@
+use worker::*;
+
+// ═══════════════════════════════════════════════════════════════════════════
+// Background worker supervisor — introspectable cron phases
+// ═══════════════════════════════════════════════════════════════════════════
+//
+// `cron_handler_inner` used to run its two phases (CDX crawl, Ashby/
+// Greenhouse job-sync) as one opaque function — `GET /progress` was the only
+// window into what a crawl was doing, and there was no way to pause one
+// phase without resetting `crawl_progress` outright. Each phase is now a
+// named `Worker` whose `step()` outcome and any operator-set control flag
+// live in a `worker_state` row, so `GET /workers` reports per-phase status
+// and `POST /workers/:name/{pause,resume,cancel}` can steer one phase
+// without touching the other's progress. See `CdxCrawlWorker`/
+// `JobSyncWorker` in `lib.rs` for the two registered workers.
+
+/// Outcome of one `Worker::step()` call.
+#[derive(Debug, Clone)]
+pub enum WorkerState {
+    /// Did a unit of work; there's more to do next tick.
+    Busy,
+    /// Nothing to do right now. The `f64` is advisory only — cron still
+    /// drives the actual tick cadence, this just records how soon a retry
+    /// would plausibly find more work.
+    Idle(f64),
+    /// This phase is fully caught up (e.g. the crawl reached its last page).
+    Done,
+    Errored(String),
+}
+
+impl WorkerState {
+    fn status_str(&self) -> &'static str {
+        match self {
+            WorkerState::Busy => "active",
+            WorkerState::Idle(_) => "idle",
+            WorkerState::Done => "done",
+            WorkerState::Errored(_) => "dead",
+        }
+    }
+}
+
+/// Operator control signal for a worker, persisted per name and checked at
+/// the top of every `WorkerManager::tick`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorkerControl {
+    Run,
+    Paused,
+    Cancelled,
+}
+
+impl WorkerControl {
+    fn as_str(self) -> &'static str {
+        match self {
+            WorkerControl::Run => "run",
+            WorkerControl::Paused => "paused",
+            WorkerControl::Cancelled => "cancelled",
+        }
+    }
+
+    fn parse(s: &str) -> Self {
+        match s {
+            "paused" => WorkerControl::Paused,
+            "cancelled" => WorkerControl::Cancelled,
+            _ => WorkerControl::Run,
+        }
+    }
+}
+
+/// One unit-of-work step for a named background phase. Implementors
+/// (`CdxCrawlWorker`/`JobSyncWorker` in `lib.rs`) re-derive whatever state
+/// they need to resume from `crawl_progress`/D1 on every call, so they're
+/// zero-sized — `WorkerManager` only deals with the `step()` contract and
+/// the persisted `worker_state` row, not worker-internal state.
+pub trait Worker {
+    fn name(&self) -> &'static str;
+    async fn step(&mut self, db: &D1Database) -> Result<WorkerState>;
+}
+
+/// Ensure a `worker_state` row exists for `name`, returning its current
+/// control flag. A worker seen for the first time defaults to `run`.
+async fn ensure_worker_row(db: &D1Database, name: &str) -> Result<WorkerControl> {
+    db.prepare(
+        "INSERT INTO worker_state (name, status, control, items_processed, updated_at)
+         VALUES (?1, 'idle', 'run', 0, datetime('now'))
+         ON CONFLICT(name) DO NOTHING"
+    ).bind(&[name.into()])?.run().await?;
+
+    let row = db.prepare("SELECT control FROM worker_state WHERE name=?1")
+        .bind(&[name.into()])?
+        .first::<serde_json::Value>(None)
+        .await?;
+    Ok(row.and_then(|r| r["control"].as_str().map(WorkerControl::parse)).unwrap_or(WorkerControl::Run))
+}
+
+/// Persist the outcome of one tick — status/last-tick/items-processed
+/// always update; `last_error` is overwritten (cleared to `NULL` on a
+/// non-`Errored` result) so a resolved failure doesn't linger in
+/// `GET /workers`.
+async fn record_tick(db: &D1Database, name: &str, state: &WorkerState, items_this_tick: u32) -> Result<()> {
+    let error: Option<&str> = match state {
+        WorkerState::Errored(msg) => Some(msg.as_str()),
+        _ => None,
+    };
+    db.prepare(
+        "UPDATE worker_state SET
+            status = ?1,
+            last_error = ?2,
+            items_processed = items_processed + ?3,
+            last_tick_at = datetime('now'),
+            updated_at = datetime('now')
+         WHERE name = ?4"
+    ).bind(&[
+        state.status_str().into(),
+        error.into(),
+        (items_this_tick as f64).into(),
+        name.into(),
+    ])?.run().await?;
+    Ok(())
+}
+
+pub struct WorkerManager;
+
+impl WorkerManager {
+    /// Run one tick of `worker`, creating its `worker_state` row on first
+    /// sight. A `paused` control flag skips the tick entirely — no call to
+    /// `record_tick`, so `last_tick_at`/`updated_at`/`status` are left exactly
+    /// as they were before the pause, and the row sinks toward the bottom of
+    /// `GET /workers`' `updated_at DESC` ordering the longer it stays paused;
+    /// `cancelled` marks it `done` without calling `step()` until a `resume`
+    /// flips it back to `run`. Returns the resulting `WorkerState` so the
+    /// caller can log it — a `step()` error is captured as
+    /// `WorkerState::Errored` rather than propagated, so one dead worker
+    /// doesn't abort its sibling's tick.
+    pub async fn tick(db: &D1Database, worker: &mut impl Worker) -> Result<WorkerState> {
+        let name = worker.name();
+        let control = ensure_worker_row(db, name).await?;
+        match control {
+            WorkerControl::Paused => {
+                console_log!("[workers] '{}' is paused — skipping this tick", name);
+                return Ok(WorkerState::Idle(0.0));
+            }
+            WorkerControl::Cancelled => {
+                record_tick(db, name, &WorkerState::Done, 0).await?;
+                return Ok(WorkerState::Done);
+            }
+            WorkerControl::Run => {}
+        }
+
+        let (state, items) = match worker.step(db).await {
+            Ok(state) => {
+                let items = u32::from(matches!(state, WorkerState::Busy));
+                (state, items)
+            }
+            Err(e) => (WorkerState::Errored(format!("{e:?}")), 0),
+        };
+        record_tick(db, name, &state, items).await?;
+        Ok(state)
+    }
+
+    /// Set a worker's control flag (creating its row first if it doesn't
+    /// exist yet) — backs `POST /workers/:name/{pause,resume,cancel}`.
+    pub async fn set_control(db: &D1Database, name: &str, control: WorkerControl) -> Result<()> {
+        ensure_worker_row(db, name).await?;
+        db.prepare("UPDATE worker_state SET control=?1, updated_at=datetime('now') WHERE name=?2")
+            .bind(&[control.as_str().into(), name.into()])?
+            .run().await?;
+        Ok(())
+    }
+
+    /// `GET /workers` listing — every row in `worker_state`, most-recently
+    /// ticked first.
+    pub async fn list(db: &D1Database) -> Result<Vec<serde_json::Value>> {
+        db.prepare("SELECT * FROM worker_state ORDER BY updated_at DESC")
+            .bind(&[])?
+            .all().await?
+            .results::<serde_json::Value>()
+    }
+}